@@ -9,5 +9,5 @@ fn main() {
     let i: Spectrum = Spectrum::new(50.0);
     let light_to_world: Transform = Transform::default();
     let _point_light: PointLight =
-        PointLight::new(&light_to_world, &MediumInterface::default(), &i);
+        PointLight::new(&light_to_world, &MediumInterface::default(), &i, None);
 }
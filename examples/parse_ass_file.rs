@@ -885,6 +885,7 @@ fn main() {
                                                     &cur_transform,
                                                     &mi,
                                                     &(color * intensity),
+                                                    None,
                                                 ));
                                                 lights.push(point_light);
                                                 println!("}}");
@@ -896,6 +897,7 @@ fn main() {
                                                     &(color * intensity),
                                                     cone_angle,
                                                     cone_angle - cone_delta_angle,
+                                                    None,
                                                 ));
                                                 lights.push(spot_light);
                                                 println!("}}");
@@ -1104,7 +1106,7 @@ fn main() {
                                                             specular_color,
                                                         ));
                                                         let mirror =
-                                                            Arc::new(MirrorMaterial { kr: kr });
+                                                            Arc::new(MirrorMaterial::new(kr, None, None));
                                                         named_materials
                                                             .insert(node_name.clone(), mirror);
                                                     } else {
@@ -1131,6 +1133,8 @@ fn main() {
                                                             roughness,
                                                             None,
                                                             None,
+                                                            None,
+                                                            None,
                                                             remap_roughness,
                                                         ));
                                                         named_materials
@@ -1143,8 +1147,9 @@ fn main() {
                                                     let sigma = Arc::new(ConstantTexture::new(
                                                         0.0 as Float,
                                                     ));
-                                                    let matte =
-                                                        Arc::new(MatteMaterial::new(kd, sigma));
+                                                    let matte = Arc::new(MatteMaterial::new(
+                                                        kd, sigma, None, None,
+                                                    ));
                                                     named_materials
                                                         .insert(node_name.clone(), matte);
                                                 }
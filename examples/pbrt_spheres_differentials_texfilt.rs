@@ -356,7 +356,7 @@ fn main() {
     let mut render_options: RenderOptions = RenderOptions::new(scene_description);
     // add triangles created above (not meshes)
     let kr = Arc::new(ConstantTexture::new(Spectrum::new(0.9)));
-    let mirror = Arc::new(MirrorMaterial::new(kr));
+    let mirror = Arc::new(MirrorMaterial::new(kr, None, None));
     let kr = Arc::new(ConstantTexture::new(Spectrum::new(1.0)));
     let kt = Arc::new(ConstantTexture::new(Spectrum::new(1.0)));
     let u_roughness = Arc::new(ConstantTexture::new(0.0 as Float));
@@ -368,13 +368,15 @@ fn main() {
         u_roughness: u_roughness,
         v_roughness: v_roughness,
         index: index,
+        bump_map: None,
+        normal_map: None,
         remap_roughness: true,
     });
     if matches.opt_present("n") || matches.opt_present("m") {
         // use no texture
         let kd = Arc::new(ConstantTexture::new(Spectrum::new(0.5)));
         let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
-        let matte = Arc::new(MatteMaterial::new(kd, sigma));
+        let matte = Arc::new(MatteMaterial::new(kd, sigma, None, None));
         for triangle in render_options.triangles {
             let geo_prim = Arc::new(GeometricPrimitive::new(
                 triangle,
@@ -443,7 +445,7 @@ fn main() {
         });
         let checker = Arc::new(Checkerboard2DTexture::new(mapping, tex1, tex2));
         let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
-        let matte = Arc::new(MatteMaterial::new(checker, sigma));
+        let matte = Arc::new(MatteMaterial::new(checker, sigma, None, None));
         for triangle in render_options.triangles {
             let geo_prim = Arc::new(GeometricPrimitive::new(
                 triangle,
@@ -503,7 +505,7 @@ fn main() {
             convert_to_spectrum,
         ));
         let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
-        let matte = Arc::new(MatteMaterial::new(lines_tex, sigma));
+        let matte = Arc::new(MatteMaterial::new(lines_tex, sigma, None, None));
         for triangle in render_options.triangles {
             let geo_prim = Arc::new(GeometricPrimitive::new(
                 triangle,
@@ -2,8 +2,7 @@
 use std;
 use std::sync::Arc;
 // others
-use time::PreciseTime;
-use typed_arena::Arena;
+use rayon::prelude::*;
 // pbrt
 use core::geometry::{bnd3_union_bnd3, bnd3_union_pnt3};
 use core::geometry::{Bounds3f, Point3f, Ray, Vector3f};
@@ -11,7 +10,7 @@ use core::interaction::SurfaceInteraction;
 use core::light::AreaLight;
 use core::material::Material;
 use core::paramset::ParamSet;
-use core::pbrt::Float;
+use core::pbrt::{gamma, Float};
 use core::primitive::Primitive;
 
 // see bvh.h
@@ -42,16 +41,16 @@ impl BVHPrimitiveInfo {
 }
 
 #[derive(Debug)]
-pub struct BVHBuildNode<'a> {
+pub struct BVHBuildNode {
     pub bounds: Bounds3f,
-    pub child1: Option<&'a mut BVHBuildNode<'a>>,
-    pub child2: Option<&'a mut BVHBuildNode<'a>>,
+    pub child1: Option<Box<BVHBuildNode>>,
+    pub child2: Option<Box<BVHBuildNode>>,
     pub split_axis: u8,
     pub first_prim_offset: usize,
     pub n_primitives: usize,
 }
 
-impl<'a> Default for BVHBuildNode<'a> {
+impl Default for BVHBuildNode {
     fn default() -> Self {
         BVHBuildNode {
             bounds: Bounds3f::default(),
@@ -64,7 +63,7 @@ impl<'a> Default for BVHBuildNode<'a> {
     }
 }
 
-impl<'a> BVHBuildNode<'a> {
+impl BVHBuildNode {
     pub fn init_leaf(&mut self, first: usize, n: usize, b: &Bounds3f) {
         self.first_prim_offset = first;
         self.n_primitives = n;
@@ -72,12 +71,7 @@ impl<'a> BVHBuildNode<'a> {
         self.child1 = None;
         self.child2 = None;
     }
-    pub fn init_interior(
-        &mut self,
-        axis: u8,
-        c0: &'a mut BVHBuildNode<'a>,
-        c1: &'a mut BVHBuildNode<'a>,
-    ) {
+    pub fn init_interior(&mut self, axis: u8, c0: Box<BVHBuildNode>, c1: Box<BVHBuildNode>) {
         self.n_primitives = 0;
         self.bounds = bnd3_union_bnd3(&c0.bounds, &c1.bounds);
         self.child1 = Some(c0);
@@ -101,6 +95,18 @@ impl Default for BucketInfo {
     }
 }
 
+#[derive(Debug, Default, Copy, Clone)]
+struct MortonPrimitive {
+    primitive_index: usize,
+    morton_code: u32,
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+struct LBVHTreelet {
+    start_index: usize,
+    n_primitives: usize,
+}
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct LinearBVHNode {
     bounds: Bounds3f,
@@ -111,12 +117,223 @@ pub struct LinearBVHNode {
     axis: u8, // TODO? pad
 }
 
+const N_SAH_BUCKETS: usize = 12;
+// number of rays traversing the BVH together (see _intersect_batch()_)
+const RAY_PACKET_SIZE: usize = 64;
+// below this many primitives, subtrees are built on the current thread
+const PARALLEL_BUILD_MIN_PRIMITIVES: usize = 4096;
+
+// BVHAccel Utility Functions
+
+fn left_shift_3(x: u32) -> u32 {
+    assert!(x <= (1_u32 << 10));
+    let mut x: u32 = x;
+    if x == (1_u32 << 10) {
+        x -= 1;
+    }
+    x = (x | (x << 16)) & 0b00000011000000000000000011111111;
+    // x = ---- --98 ---- ---- ---- ---- 7654 3210
+    x = (x | (x << 8)) & 0b00000011000000001111000000001111;
+    // x = ---- --98 ---- ---- 7654 ---- ---- 3210
+    x = (x | (x << 4)) & 0b00000011000011000011000011000011;
+    // x = ---- --98 ---- 76-- --54 ---- 32-- --10
+    x = (x | (x << 2)) & 0b00001001001001001001001001001001;
+    // x = ---- 9--8 --7- -6-- 5--4 --3- -2-- 1--0
+    x
+}
+
+fn encode_morton_3(v: &Vector3f) -> u32 {
+    assert!(v.x >= 0.0 as Float);
+    assert!(v.y >= 0.0 as Float);
+    assert!(v.z >= 0.0 as Float);
+    (left_shift_3(v.z as u32) << 2) | (left_shift_3(v.y as u32) << 1) | left_shift_3(v.x as u32)
+}
+
+fn radix_sort(v: &mut Vec<MortonPrimitive>) {
+    const BITS_PER_PASS: usize = 6;
+    const N_BITS: usize = 30;
+    assert_eq!(
+        N_BITS % BITS_PER_PASS,
+        0,
+        "Radix sort bits_per_pass must evenly divide n_bits"
+    );
+    const N_PASSES: usize = N_BITS / BITS_PER_PASS;
+    const N_BUCKETS: usize = 1 << BITS_PER_PASS;
+    const BIT_MASK: u32 = (1 << BITS_PER_PASS) - 1;
+    // each chunk of the input is distributed into its own buckets in
+    // parallel, concatenating the buckets in chunk order keeps the sort
+    // stable
+    let chunk_size: usize = std::cmp::max(4096, v.len() / (4 * rayon::current_num_threads()) + 1);
+    for pass in 0..N_PASSES {
+        // perform one pass of radix sort, sorting _BITS_PER_PASS_ bits
+        let low_bit: usize = pass * BITS_PER_PASS;
+        let chunk_buckets: Vec<Vec<Vec<MortonPrimitive>>> = v
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                let mut buckets: Vec<Vec<MortonPrimitive>> = vec![Vec::new(); N_BUCKETS];
+                for mp in chunk {
+                    let bucket: usize = ((mp.morton_code >> low_bit) & BIT_MASK) as usize;
+                    buckets[bucket].push(*mp);
+                }
+                buckets
+            })
+            .collect();
+        // store sorted values in _v_
+        v.clear();
+        for bucket in 0..N_BUCKETS {
+            for buckets in &chunk_buckets {
+                v.extend_from_slice(&buckets[bucket]);
+            }
+        }
+    }
+}
+
+/// Rearranges _v_ so that all elements for which _pred_ is true
+/// precede the others and returns the number of those elements (like
+/// std::partition in C++).
+fn partition<T, P>(v: &mut [T], pred: P) -> usize
+where
+    P: Fn(&T) -> bool,
+{
+    let mut first: usize = 0;
+    for i in 0..v.len() {
+        if pred(&v[i]) {
+            v.swap(first, i);
+            first += 1;
+        }
+    }
+    first
+}
+
+fn sah_bucket(centroid_bounds: &Bounds3f, centroid: &Point3f, dim: u8) -> usize {
+    let b: usize = (N_SAH_BUCKETS as Float * centroid_bounds.offset(centroid)[dim]) as usize;
+    if b == N_SAH_BUCKETS {
+        N_SAH_BUCKETS - 1
+    } else {
+        b
+    }
+}
+
+/// Bins the primitive centroids along _dim_ and returns the SAH cost
+/// of splitting after each bucket.
+fn sah_bucket_costs(
+    primitive_info: &[BVHPrimitiveInfo],
+    bounds: &Bounds3f,
+    centroid_bounds: &Bounds3f,
+    dim: u8,
+) -> [Float; N_SAH_BUCKETS - 1] {
+    // initialize _BucketInfo_ for SAH partition buckets
+    let mut buckets: [BucketInfo; N_SAH_BUCKETS] = [BucketInfo::default(); N_SAH_BUCKETS];
+    for pi in primitive_info {
+        let b: usize = sah_bucket(centroid_bounds, &pi.centroid, dim);
+        buckets[b].count += 1;
+        buckets[b].bounds = bnd3_union_bnd3(&buckets[b].bounds, &pi.bounds);
+    }
+    // sweep from the right to accumulate the bounds above each split
+    let mut area_above: [Float; N_SAH_BUCKETS] = [0.0; N_SAH_BUCKETS];
+    let mut count_above: [usize; N_SAH_BUCKETS] = [0; N_SAH_BUCKETS];
+    let mut b1: Bounds3f = Bounds3f::default();
+    let mut count1: usize = 0;
+    for i in (1..N_SAH_BUCKETS).rev() {
+        b1 = bnd3_union_bnd3(&b1, &buckets[i].bounds);
+        count1 += buckets[i].count;
+        area_above[i] = b1.surface_area();
+        count_above[i] = count1;
+    }
+    // sweep from the left and compute costs for splitting after each bucket
+    let mut cost: [Float; N_SAH_BUCKETS - 1] = [0.0; N_SAH_BUCKETS - 1];
+    let mut b0: Bounds3f = Bounds3f::default();
+    let mut count0: usize = 0;
+    for i in 0..(N_SAH_BUCKETS - 1) {
+        b0 = bnd3_union_bnd3(&b0, &buckets[i].bounds);
+        count0 += buckets[i].count;
+        cost[i] = 1.0
+            + (count0 as Float * b0.surface_area()
+                + count_above[i + 1] as Float * area_above[i + 1])
+                / bounds.surface_area();
+    }
+    cost
+}
+
+pub const MAX_BVH_BRANCHING: usize = 8;
+
+/// Node of a 4- or 8-wide BVH, collapsed from the binary BVH. The
+/// child bounds are stored as structure of arrays so that all
+/// children can be tested against a ray in one batch.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct WideBVHNode {
+    pub bounds_min: [[Float; MAX_BVH_BRANCHING]; 3],
+    pub bounds_max: [[Float; MAX_BVH_BRANCHING]; 3],
+    // wide node index (interior child) or primitives offset (leaf)
+    pub offset: [usize; MAX_BVH_BRANCHING],
+    // zero for interior children
+    pub n_primitives: [usize; MAX_BVH_BRANCHING],
+    pub n_children: u8,
+    // for each ray direction octant, the children in the order the
+    // binary BVH would visit them
+    pub child_order: [[u8; MAX_BVH_BRANCHING]; 8],
+}
+
+impl WideBVHNode {
+    /// Same slab test as _Bounds3::intersect_p()_ for all children at
+    /// once; returns which children are hit and their entry distances.
+    pub fn intersect_children(
+        &self,
+        ray: &Ray,
+        inv_dir: &Vector3f,
+        dir_is_neg: [u8; 3],
+        t_entry: &mut [Float; MAX_BVH_BRANCHING],
+    ) -> [bool; MAX_BVH_BRANCHING] {
+        let robust: Float = 1.0 + 2.0 * gamma(3_i32);
+        let (near_x, far_x) = if dir_is_neg[0] == 1_u8 {
+            (&self.bounds_max[0], &self.bounds_min[0])
+        } else {
+            (&self.bounds_min[0], &self.bounds_max[0])
+        };
+        let (near_y, far_y) = if dir_is_neg[1] == 1_u8 {
+            (&self.bounds_max[1], &self.bounds_min[1])
+        } else {
+            (&self.bounds_min[1], &self.bounds_max[1])
+        };
+        let (near_z, far_z) = if dir_is_neg[2] == 1_u8 {
+            (&self.bounds_max[2], &self.bounds_min[2])
+        } else {
+            (&self.bounds_min[2], &self.bounds_max[2])
+        };
+        let mut hits: [bool; MAX_BVH_BRANCHING] = [false; MAX_BVH_BRANCHING];
+        for i in 0..MAX_BVH_BRANCHING {
+            // check for ray intersection against $x$ and $y$ slabs
+            let mut t_min: Float = (near_x[i] - ray.o.x) * inv_dir.x;
+            let mut t_max: Float = (far_x[i] - ray.o.x) * inv_dir.x;
+            let ty_min: Float = (near_y[i] - ray.o.y) * inv_dir.y;
+            let mut ty_max: Float = (far_y[i] - ray.o.y) * inv_dir.y;
+            t_max *= robust;
+            ty_max *= robust;
+            let miss_xy: bool = (t_min > ty_max) | (ty_min > t_max);
+            t_min = if ty_min > t_min { ty_min } else { t_min };
+            t_max = if ty_max < t_max { ty_max } else { t_max };
+            // check for ray intersection against $z$ slab
+            let tz_min: Float = (near_z[i] - ray.o.z) * inv_dir.z;
+            let mut tz_max: Float = (far_z[i] - ray.o.z) * inv_dir.z;
+            tz_max *= robust;
+            let miss_z: bool = (t_min > tz_max) | (tz_min > t_max);
+            t_min = if tz_min > t_min { tz_min } else { t_min };
+            t_max = if tz_max < t_max { tz_max } else { t_max };
+            t_entry[i] = t_min;
+            hits[i] = !miss_xy & !miss_z & (t_min < ray.t_max) & (t_max > 0.0);
+        }
+        hits
+    }
+}
+
 // BVHAccel -> Aggregate -> Primitive
 pub struct BVHAccel {
     max_prims_in_node: usize,
     split_method: SplitMethod,
     pub primitives: Vec<Arc<Primitive + Sync + Send>>,
     pub nodes: Vec<LinearBVHNode>,
+    // empty unless the binary BVH was collapsed (see _collapse()_)
+    pub wide_nodes: Vec<WideBVHNode>,
 }
 
 impl BVHAccel {
@@ -130,38 +347,45 @@ impl BVHAccel {
             split_method: split_method.clone(),
             primitives: p,
             nodes: Vec::new(),
+            wide_nodes: Vec::new(),
         });
         let num_prims = bvh.primitives.len();
-        let mut primitive_info = vec![BVHPrimitiveInfo::default(); num_prims];
-        for i in 0..num_prims {
-            let world_bound = bvh.primitives[i].world_bound();
-            primitive_info[i] = BVHPrimitiveInfo::new(i, world_bound);
-        }
-        // TODO: if (splitMethod == SplitMethod::HLBVH)
-        let mut arena: Arena<BVHBuildNode> = Arena::with_capacity(1024 * 1024);
+        let mut primitive_info: Vec<BVHPrimitiveInfo> = bvh
+            .primitives
+            .par_iter()
+            .enumerate()
+            .map(|(i, prim)| BVHPrimitiveInfo::new(i, prim.world_bound()))
+            .collect();
+        // build BVH tree for primitives using _primitive_info_
         let mut total_nodes: usize = 0;
         let mut ordered_prims: Vec<Arc<Primitive + Sync + Send>> = Vec::with_capacity(num_prims);
-        println!("BVHAccel::recursive_build(..., {}, ...)", num_prims);
-        let start = PreciseTime::now();
-        let root = BVHAccel::recursive_build(
-            bvh.clone(), // instead of self
-            &mut arena,
-            &mut primitive_info,
-            0,
-            num_prims,
-            &mut total_nodes,
-            &mut ordered_prims,
-        );
-        let end = PreciseTime::now();
-        println!("{} seconds for building BVH ...", start.to(end));
+        let root: Box<BVHBuildNode>;
+        match split_method {
+            SplitMethod::HLBVH => {
+                root = BVHAccel::hlbvh_build(
+                    &bvh, // instead of self
+                    &primitive_info,
+                    &mut total_nodes,
+                    &mut ordered_prims,
+                );
+            }
+            _ => {
+                root = BVHAccel::recursive_build(
+                    &bvh, // instead of self
+                    &mut primitive_info,
+                    0,
+                    &mut total_nodes,
+                );
+                // leaves reference primitives in the partitioned order
+                for pi in &primitive_info {
+                    ordered_prims.push(bvh.primitives[pi.primitive_number].clone());
+                }
+            }
+        }
         // flatten first
         let mut nodes = vec![LinearBVHNode::default(); total_nodes];
         let mut offset: usize = 0;
-        println!("BVHAccel::flatten_bvh_tree(...)");
-        let start = PreciseTime::now();
-        BVHAccel::flatten_bvh_tree(root, &mut nodes, &mut offset);
-        let end = PreciseTime::now();
-        println!("{} seconds for flattening BVH ...", start.to(end));
+        BVHAccel::flatten_bvh_tree(&root, &mut nodes, &mut offset);
         assert!(nodes.len() == total_nodes);
         // primitives.swap(orderedPrims);
         let bvh_ordered_prims = Arc::new(BVHAccel {
@@ -169,6 +393,7 @@ impl BVHAccel {
             split_method: split_method.clone(),
             primitives: ordered_prims,
             nodes: nodes,
+            wide_nodes: Vec::new(),
         });
         let unwrapped = Arc::try_unwrap(bvh_ordered_prims);
         unwrapped.ok().unwrap()
@@ -192,190 +417,761 @@ impl BVHAccel {
             split_method = SplitMethod::SAH;
         }
         let max_prims_in_node: i32 = ps.find_one_int("maxnodeprims", 4);
-        Arc::new(BVHAccel::new(
-            prims.clone(),
-            max_prims_in_node as usize,
-            split_method,
-        ))
-    }
-    pub fn recursive_build<'a>(
-        bvh: Arc<BVHAccel>,
-        arena: &'a Arena<BVHBuildNode<'a>>,
-        primitive_info: &mut Vec<BVHPrimitiveInfo>,
-        start: usize,
-        end: usize,
+        let mut branching: i32 = ps.find_one_int("branching", 2);
+        if branching != 2 && branching != 4 && branching != 8 {
+            println!(
+                "WARNING: BVH branching factor {} unsupported.  Using 2.",
+                branching
+            );
+            branching = 2;
+        }
+        let mut bvh: BVHAccel =
+            BVHAccel::new(prims.clone(), max_prims_in_node as usize, split_method);
+        if branching > 2 {
+            bvh.collapse(branching as usize);
+        }
+        Arc::new(bvh)
+    }
+    /// Collapses the binary BVH into a BVH with up to _branching_
+    /// children per node, which is then used for traversal.
+    pub fn collapse(&mut self, branching: usize) {
+        assert!(branching >= 2 && branching <= MAX_BVH_BRANCHING);
+        self.wide_nodes.clear();
+        if self.nodes.len() == 0 {
+            return;
+        }
+        let mut wide_nodes: Vec<WideBVHNode> = Vec::new();
+        BVHAccel::flatten_wide_bvh(&self.nodes, 0, branching, &mut wide_nodes);
+        self.wide_nodes = wide_nodes;
+    }
+    fn flatten_wide_bvh(
+        nodes: &Vec<LinearBVHNode>,
+        binary_index: usize,
+        branching: usize,
+        wide_nodes: &mut Vec<WideBVHNode>,
+    ) -> usize {
+        let my_offset: usize = wide_nodes.len();
+        wide_nodes.push(WideBVHNode::default());
+        // pull the binary subtree's nodes up into this node, opening
+        // the interior child with the largest surface area first
+        let mut children: Vec<usize> = Vec::with_capacity(branching);
+        children.push(binary_index);
+        while children.len() < branching {
+            let mut open: Option<usize> = None;
+            let mut max_area: Float = -1.0 as Float;
+            for (i, c) in children.iter().enumerate() {
+                if nodes[*c].n_primitives == 0 && nodes[*c].bounds.surface_area() > max_area {
+                    max_area = nodes[*c].bounds.surface_area();
+                    open = Some(i);
+                }
+            }
+            if let Some(i) = open {
+                let c: usize = children[i];
+                children[i] = c + 1;
+                children.push(nodes[c].offset);
+            } else {
+                break;
+            }
+        }
+        let mut wide: WideBVHNode = WideBVHNode::default();
+        wide.n_children = children.len() as u8;
+        for (i, c) in children.iter().enumerate() {
+            let node: LinearBVHNode = nodes[*c];
+            for axis in 0..3_u8 {
+                wide.bounds_min[axis as usize][i] = node.bounds.p_min[axis];
+                wide.bounds_max[axis as usize][i] = node.bounds.p_max[axis];
+            }
+            if node.n_primitives > 0 {
+                wide.offset[i] = node.offset;
+                wide.n_primitives[i] = node.n_primitives;
+            } else {
+                wide.offset[i] = BVHAccel::flatten_wide_bvh(nodes, *c, branching, wide_nodes);
+            }
+        }
+        for octant in 0..8 {
+            let mut n: usize = 0;
+            BVHAccel::wide_child_order(
+                nodes,
+                binary_index,
+                &children,
+                octant,
+                &mut wide.child_order[octant],
+                &mut n,
+            );
+            assert_eq!(n, children.len());
+        }
+        wide_nodes[my_offset] = wide;
+        my_offset
+    }
+    fn wide_child_order(
+        nodes: &Vec<LinearBVHNode>,
+        binary_index: usize,
+        children: &Vec<usize>,
+        octant: usize,
+        order: &mut [u8; MAX_BVH_BRANCHING],
+        n: &mut usize,
+    ) {
+        if let Some(i) = children.iter().position(|c| *c == binary_index) {
+            order[*n] = i as u8;
+            *n += 1;
+            return;
+        }
+        // opened interior node, visit near child first (like the
+        // binary traversal)
+        let node: LinearBVHNode = nodes[binary_index];
+        if (octant >> node.axis) & 1 == 1 {
+            BVHAccel::wide_child_order(nodes, node.offset, children, octant, order, n);
+            BVHAccel::wide_child_order(nodes, binary_index + 1, children, octant, order, n);
+        } else {
+            BVHAccel::wide_child_order(nodes, binary_index + 1, children, octant, order, n);
+            BVHAccel::wide_child_order(nodes, node.offset, children, octant, order, n);
+        }
+    }
+    fn intersect_wide(&self, ray: &mut Ray) -> Option<SurfaceInteraction> {
+        let inv_dir: Vector3f = Vector3f {
+            x: 1.0 / ray.d.x,
+            y: 1.0 / ray.d.y,
+            z: 1.0 / ray.d.z,
+        };
+        let dir_is_neg: [u8; 3] = [
+            (inv_dir.x < 0.0) as u8,
+            (inv_dir.y < 0.0) as u8,
+            (inv_dir.z < 0.0) as u8,
+        ];
+        if !self.nodes[0].bounds.intersect_p(ray, &inv_dir, dir_is_neg) {
+            return None;
+        }
+        let octant: usize =
+            dir_is_neg[0] as usize | (dir_is_neg[1] as usize) << 1 | (dir_is_neg[2] as usize) << 2;
+        let mut hit: bool = false;
+        let mut si: SurfaceInteraction = SurfaceInteraction::default();
+        // (offset, n_primitives, entry distance) of children to visit
+        let mut nodes_to_visit: [(usize, usize, Float); 64 * MAX_BVH_BRANCHING] =
+            [(0_usize, 0_usize, 0.0 as Float); 64 * MAX_BVH_BRANCHING];
+        // start at the root wide node
+        nodes_to_visit[0] = (0_usize, 0_usize, -std::f32::INFINITY as Float);
+        let mut to_visit_offset: usize = 1;
+        let mut t_entry: [Float; MAX_BVH_BRANCHING] = [0.0 as Float; MAX_BVH_BRANCHING];
+        while to_visit_offset > 0 {
+            to_visit_offset -= 1;
+            let (offset, n_primitives, t_min) = nodes_to_visit[to_visit_offset];
+            // children were tested before closer hits shortened the ray
+            if !(t_min < ray.t_max) {
+                continue;
+            }
+            if n_primitives > 0 {
+                // intersect ray with primitives in leaf BVH node
+                for i in 0..n_primitives {
+                    if let Some(isect) = self.primitives[offset + i].intersect(ray) {
+                        si = isect;
+                        hit = true;
+                    }
+                }
+            } else {
+                // put children hit by the ray on _nodes_to_visit_ stack,
+                // near children end up on top
+                let node: &WideBVHNode = &self.wide_nodes[offset];
+                let hits = node.intersect_children(ray, &inv_dir, dir_is_neg, &mut t_entry);
+                for k in (0..node.n_children as usize).rev() {
+                    let i: usize = node.child_order[octant][k] as usize;
+                    if hits[i] {
+                        nodes_to_visit[to_visit_offset] =
+                            (node.offset[i], node.n_primitives[i], t_entry[i]);
+                        to_visit_offset += 1;
+                    }
+                }
+            }
+        }
+        if hit {
+            Some(si)
+        } else {
+            None
+        }
+    }
+    /// Traverses the binary BVH with a packet of rays. Each node is
+    /// tested against the rays from the first one still active
+    /// onwards; rays in front of it missed an ancestor node.
+    fn intersect_packet(&self, rays: &mut [Ray]) -> Vec<Option<SurfaceInteraction>> {
+        let n: usize = rays.len();
+        assert!(n <= RAY_PACKET_SIZE);
+        let mut isects: Vec<Option<SurfaceInteraction>> = Vec::with_capacity(n);
+        for _ in 0..n {
+            isects.push(None);
+        }
+        if self.nodes.len() == 0 {
+            return isects;
+        }
+        let mut inv_dirs: [Vector3f; RAY_PACKET_SIZE] = [Vector3f::default(); RAY_PACKET_SIZE];
+        let mut dirs_is_neg: [[u8; 3]; RAY_PACKET_SIZE] = [[0_u8; 3]; RAY_PACKET_SIZE];
+        for r in 0..n {
+            inv_dirs[r] = Vector3f {
+                x: 1.0 / rays[r].d.x,
+                y: 1.0 / rays[r].d.y,
+                z: 1.0 / rays[r].d.z,
+            };
+            dirs_is_neg[r] = [
+                (inv_dirs[r].x < 0.0) as u8,
+                (inv_dirs[r].y < 0.0) as u8,
+                (inv_dirs[r].z < 0.0) as u8,
+            ];
+        }
+        // follow packet through BVH nodes to find primitive intersections
+        let mut to_visit_offset: usize = 0;
+        let mut current_node_index: usize = 0;
+        let mut first_active: usize = 0;
+        // (node index, first active ray) of nodes to visit
+        let mut nodes_to_visit: [(usize, usize); 64] = [(0_usize, 0_usize); 64];
+        loop {
+            let node: LinearBVHNode = self.nodes[current_node_index];
+            // find first ray of the packet hitting the node
+            let mut first: usize = first_active;
+            while first < n
+                && !node
+                    .bounds
+                    .intersect_p(&rays[first], &inv_dirs[first], dirs_is_neg[first])
+            {
+                first += 1;
+            }
+            if first < n && node.n_primitives == 0 {
+                // put far BVH node (as seen by the first active ray)
+                // on _nodes_to_visit_ stack, advance to near node
+                if dirs_is_neg[first][node.axis as usize] == 1_u8 {
+                    nodes_to_visit[to_visit_offset] = (current_node_index + 1, first);
+                    current_node_index = node.offset;
+                } else {
+                    nodes_to_visit[to_visit_offset] = (node.offset, first);
+                    current_node_index += 1;
+                }
+                to_visit_offset += 1;
+                first_active = first;
+                continue;
+            }
+            if first < n {
+                // intersect rays hitting the leaf with its primitives
+                for r in first..n {
+                    if r != first
+                        && !node
+                            .bounds
+                            .intersect_p(&rays[r], &inv_dirs[r], dirs_is_neg[r])
+                    {
+                        continue;
+                    }
+                    for i in 0..node.n_primitives {
+                        if let Some(isect) =
+                            self.primitives[node.offset + i].intersect(&mut rays[r])
+                        {
+                            isects[r] = Some(isect);
+                        }
+                    }
+                }
+            }
+            if to_visit_offset == 0 {
+                break;
+            }
+            to_visit_offset -= 1;
+            current_node_index = nodes_to_visit[to_visit_offset].0;
+            first_active = nodes_to_visit[to_visit_offset].1;
+        }
+        isects
+    }
+    fn intersect_p_packet(&self, rays: &[Ray]) -> Vec<bool> {
+        let n: usize = rays.len();
+        assert!(n <= RAY_PACKET_SIZE);
+        let mut occluded: Vec<bool> = vec![false; n];
+        if self.nodes.len() == 0 {
+            return occluded;
+        }
+        let mut inv_dirs: [Vector3f; RAY_PACKET_SIZE] = [Vector3f::default(); RAY_PACKET_SIZE];
+        let mut dirs_is_neg: [[u8; 3]; RAY_PACKET_SIZE] = [[0_u8; 3]; RAY_PACKET_SIZE];
+        for r in 0..n {
+            inv_dirs[r] = Vector3f {
+                x: 1.0 / rays[r].d.x,
+                y: 1.0 / rays[r].d.y,
+                z: 1.0 / rays[r].d.z,
+            };
+            dirs_is_neg[r] = [
+                (inv_dirs[r].x < 0.0) as u8,
+                (inv_dirs[r].y < 0.0) as u8,
+                (inv_dirs[r].z < 0.0) as u8,
+            ];
+        }
+        let mut to_visit_offset: usize = 0;
+        let mut current_node_index: usize = 0;
+        let mut first_active: usize = 0;
+        let mut nodes_to_visit: [(usize, usize); 64] = [(0_usize, 0_usize); 64];
+        loop {
+            let node: LinearBVHNode = self.nodes[current_node_index];
+            // find first unoccluded ray of the packet hitting the node
+            let mut first: usize = first_active;
+            while first < n
+                && (occluded[first]
+                    || !node
+                        .bounds
+                        .intersect_p(&rays[first], &inv_dirs[first], dirs_is_neg[first]))
+            {
+                first += 1;
+            }
+            if first < n && node.n_primitives == 0 {
+                if dirs_is_neg[first][node.axis as usize] == 1_u8 {
+                    nodes_to_visit[to_visit_offset] = (current_node_index + 1, first);
+                    current_node_index = node.offset;
+                } else {
+                    nodes_to_visit[to_visit_offset] = (node.offset, first);
+                    current_node_index += 1;
+                }
+                to_visit_offset += 1;
+                first_active = first;
+                continue;
+            }
+            if first < n {
+                for r in first..n {
+                    if occluded[r]
+                        || (r != first
+                            && !node
+                                .bounds
+                                .intersect_p(&rays[r], &inv_dirs[r], dirs_is_neg[r]))
+                    {
+                        continue;
+                    }
+                    for i in 0..node.n_primitives {
+                        if self.primitives[node.offset + i].intersect_p(&rays[r]) {
+                            occluded[r] = true;
+                            break;
+                        }
+                    }
+                }
+            }
+            if to_visit_offset == 0 {
+                break;
+            }
+            to_visit_offset -= 1;
+            current_node_index = nodes_to_visit[to_visit_offset].0;
+            first_active = nodes_to_visit[to_visit_offset].1;
+        }
+        occluded
+    }
+    fn intersect_p_wide(&self, ray: &Ray) -> bool {
+        let inv_dir: Vector3f = Vector3f {
+            x: 1.0 / ray.d.x,
+            y: 1.0 / ray.d.y,
+            z: 1.0 / ray.d.z,
+        };
+        let dir_is_neg: [u8; 3] = [
+            (inv_dir.x < 0.0) as u8,
+            (inv_dir.y < 0.0) as u8,
+            (inv_dir.z < 0.0) as u8,
+        ];
+        if !self.nodes[0].bounds.intersect_p(ray, &inv_dir, dir_is_neg) {
+            return false;
+        }
+        let octant: usize =
+            dir_is_neg[0] as usize | (dir_is_neg[1] as usize) << 1 | (dir_is_neg[2] as usize) << 2;
+        // (offset, n_primitives) of children to visit
+        let mut nodes_to_visit: [(usize, usize); 64 * MAX_BVH_BRANCHING] =
+            [(0_usize, 0_usize); 64 * MAX_BVH_BRANCHING];
+        // start at the root wide node
+        let mut to_visit_offset: usize = 1;
+        let mut t_entry: [Float; MAX_BVH_BRANCHING] = [0.0 as Float; MAX_BVH_BRANCHING];
+        while to_visit_offset > 0 {
+            to_visit_offset -= 1;
+            let (offset, n_primitives) = nodes_to_visit[to_visit_offset];
+            if n_primitives > 0 {
+                for i in 0..n_primitives {
+                    if self.primitives[offset + i].intersect_p(ray) {
+                        return true;
+                    }
+                }
+            } else {
+                let node: &WideBVHNode = &self.wide_nodes[offset];
+                let hits = node.intersect_children(ray, &inv_dir, dir_is_neg, &mut t_entry);
+                for k in (0..node.n_children as usize).rev() {
+                    let i: usize = node.child_order[octant][k] as usize;
+                    if hits[i] {
+                        nodes_to_visit[to_visit_offset] = (node.offset[i], node.n_primitives[i]);
+                        to_visit_offset += 1;
+                    }
+                }
+            }
+        }
+        false
+    }
+    /// Builds the subtree for _primitive_info_, which starts at
+    /// _first_prim_offset_ in the final primitive order. Primitives
+    /// are partitioned in place, so a leaf references the primitives
+    /// at the positions its slice ends up occupying. Subtrees with at
+    /// least _PARALLEL_BUILD_MIN_PRIMITIVES_ primitives are built in
+    /// parallel.
+    pub fn recursive_build(
+        bvh: &BVHAccel,
+        primitive_info: &mut [BVHPrimitiveInfo],
+        first_prim_offset: usize,
         total_nodes: &mut usize,
-        ordered_prims: &mut Vec<Arc<Primitive + Sync + Send>>,
-    ) -> &'a mut BVHBuildNode<'a> {
-        assert_ne!(start, end);
-        let node: &mut BVHBuildNode<'a> = arena.alloc(BVHBuildNode::default());
+    ) -> Box<BVHBuildNode> {
+        let n_primitives: usize = primitive_info.len();
+        assert_ne!(n_primitives, 0);
+        let mut node: Box<BVHBuildNode> = Box::new(BVHBuildNode::default());
         *total_nodes += 1_usize;
         // compute bounds of all primitives in BVH node
         let mut bounds: Bounds3f = Bounds3f::default();
-        for i in start..end {
-            bounds = bnd3_union_bnd3(&bounds, &primitive_info[i].bounds);
+        for pi in primitive_info.iter() {
+            bounds = bnd3_union_bnd3(&bounds, &pi.bounds);
         }
-        let n_primitives: usize = end - start;
         if n_primitives == 1 {
             // create leaf _BVHBuildNode_
-            let first_prim_offset: usize = ordered_prims.len();
-            for i in start..end {
-                let prim_num: usize = primitive_info[i].primitive_number;
-                ordered_prims.push(bvh.primitives[prim_num].clone());
-            }
             node.init_leaf(first_prim_offset, n_primitives, &bounds);
             return node;
-        } else {
-            // compute bound of primitive centroids, choose split dimension _dim_
-            let mut centroid_bounds: Bounds3f = Bounds3f::default();
-            for i in start..end {
-                centroid_bounds = bnd3_union_pnt3(&centroid_bounds, &primitive_info[i].centroid);
-            }
-            let dim: u8 = centroid_bounds.maximum_extent();
-            // partition primitives into two sets and build children
-            let mut mid: usize = (start + end) / 2_usize;
-            if centroid_bounds.p_max[dim] == centroid_bounds.p_min[dim] {
-                // create leaf _BVHBuildNode_
-                let first_prim_offset: usize = ordered_prims.len();
-                for i in start..end {
-                    let prim_num: usize = primitive_info[i].primitive_number;
-                    ordered_prims.push(bvh.primitives[prim_num].clone());
+        }
+        // compute bound of primitive centroids, choose split dimension _dim_
+        let mut centroid_bounds: Bounds3f = Bounds3f::default();
+        for pi in primitive_info.iter() {
+            centroid_bounds = bnd3_union_pnt3(&centroid_bounds, &pi.centroid);
+        }
+        let mut dim: u8 = centroid_bounds.maximum_extent();
+        if centroid_bounds.p_max[dim] == centroid_bounds.p_min[dim] {
+            // create leaf _BVHBuildNode_
+            node.init_leaf(first_prim_offset, n_primitives, &bounds);
+            return node;
+        }
+        // partition primitives into two sets and build children
+        let mut mid: usize;
+        // partition primitives based on _splitMethod_
+        match bvh.split_method {
+            SplitMethod::Middle => {
+                // partition primitives through node's midpoint
+                let pmid: Float = (centroid_bounds.p_min[dim] + centroid_bounds.p_max[dim]) / 2.0;
+                mid = partition(primitive_info, |pi| pi.centroid[dim] < pmid);
+                if mid == 0 || mid == n_primitives {
+                    // for lots of prims with large overlapping bounding
+                    // boxes, this may fail to partition; in that case
+                    // fall back to equal counts
+                    mid = BVHAccel::split_equal_counts(primitive_info, dim);
                 }
-                node.init_leaf(first_prim_offset, n_primitives, &bounds);
-                return node;
-            } else {
-                // partition primitives based on _splitMethod_
-                match bvh.split_method {
-                    SplitMethod::Middle => {
-                        // TODO
-                    }
-                    SplitMethod::EqualCounts => {
-                        // TODO
+            }
+            SplitMethod::EqualCounts => {
+                mid = BVHAccel::split_equal_counts(primitive_info, dim);
+            }
+            SplitMethod::SAH | SplitMethod::HLBVH => {
+                if n_primitives <= 2 {
+                    // partition primitives into equally-sized subsets
+                    mid = n_primitives / 2;
+                    if primitive_info[n_primitives - 1].centroid[dim]
+                        < primitive_info[0].centroid[dim]
+                    {
+                        primitive_info.swap(0, n_primitives - 1);
                     }
-                    SplitMethod::SAH | SplitMethod::HLBVH => {
-                        if n_primitives <= 2 {
-                            mid = (start + end) / 2;
-                            if start != end - 1 {
-                                if primitive_info[end - 1].centroid[dim]
-                                    < primitive_info[start].centroid[dim]
-                                {
-                                    primitive_info.swap(start, end - 1);
-                                }
-                            }
-                        } else {
-                            // allocate _BucketInfo_ for SAH partition buckets
-                            let n_buckets: usize = 12;
-                            let mut buckets: [BucketInfo; 12] = [BucketInfo::default(); 12];
-                            // initialize _BucketInfo_ for SAH partition buckets
-                            for i in start..end {
-                                let mut b: usize = (n_buckets as Float * centroid_bounds
-                                    .offset(&primitive_info[i].centroid)[dim])
-                                    as usize;
-                                if b == n_buckets {
-                                    b = n_buckets - 1;
-                                }
-                                // assert!(b >= 0_usize, "b >= 0");
-                                assert!(b < n_buckets, "b < {}", n_buckets);
-                                buckets[b].count += 1;
-                                buckets[b].bounds =
-                                    bnd3_union_bnd3(&buckets[b].bounds, &primitive_info[i].bounds);
-                            }
-                            // compute costs for splitting after each bucket
-                            let mut cost: [Float; 11] = [0.0; 11];
-                            for i in 0..(n_buckets - 1) {
-                                let mut b0: Bounds3f = Bounds3f::default();
-                                let mut b1: Bounds3f = Bounds3f::default();
-                                let mut count0: usize = 0;
-                                let mut count1: usize = 0;
-                                for j in 0..(i + 1) {
-                                    b0 = bnd3_union_bnd3(&b0, &buckets[j].bounds);
-                                    count0 += buckets[j].count;
-                                }
-                                for j in (i + 1)..n_buckets {
-                                    b1 = bnd3_union_bnd3(&b1, &buckets[j].bounds);
-                                    count1 += buckets[j].count;
-                                }
-                                cost[i] = 1.0
-                                    + (count0 as Float * b0.surface_area()
-                                        + count1 as Float * b1.surface_area())
-                                        / bounds.surface_area();
-                            }
-                            // find bucket to split at that minimizes SAH metric
-                            let mut min_cost: Float = cost[0];
-                            let mut min_cost_split_bucket: usize = 0;
-                            for i in 0..(n_buckets - 1) {
-                                if cost[i] < min_cost {
-                                    min_cost = cost[i];
-                                    min_cost_split_bucket = i;
-                                }
-                            }
-                            // either create leaf or split primitives
-                            // at selected SAH bucket
-                            let leaf_cost: Float = n_primitives as Float;
-                            if n_primitives > bvh.max_prims_in_node || min_cost < leaf_cost {
-                                let (mut left, mut right): (
-                                    Vec<BVHPrimitiveInfo>,
-                                    Vec<BVHPrimitiveInfo>,
-                                ) = primitive_info[start..end].into_iter().partition(|&pi| {
-                                    let mut b: usize = (n_buckets as Float
-                                        * centroid_bounds.offset(&pi.centroid)[dim])
-                                        as usize;
-                                    if b == n_buckets {
-                                        b = n_buckets - 1;
-                                    }
-                                    // assert!(b >= 0_usize, "b >= 0");
-                                    assert!(b < n_buckets, "b < {}", n_buckets);
-                                    b <= min_cost_split_bucket
-                                });
-                                mid = start + left.len();
-                                let combined_len = left.len() + right.len();
-                                if combined_len == primitive_info.len() {
-                                    primitive_info.clear();
-                                    primitive_info.append(&mut left);
-                                    primitive_info.append(&mut right);
-                                } else {
-                                    primitive_info.splice(start..mid, left.iter().cloned());
-                                    primitive_info.splice(mid..end, right.iter().cloned());
-                                }
-                            } else {
-                                // create leaf _BVHBuildNode_
-                                let first_prim_offset: usize = ordered_prims.len();
-                                for i in start..end {
-                                    let prim_num: usize = primitive_info[i].primitive_number;
-                                    ordered_prims.push(bvh.primitives[prim_num].clone());
-                                }
-                                node.init_leaf(first_prim_offset, n_primitives, &bounds);
-                                return node;
+                } else {
+                    // find the bucket split with minimal SAH cost over
+                    // all three axes
+                    let mut min_cost: Float = std::f32::INFINITY as Float;
+                    let mut min_cost_split_bucket: usize = 0;
+                    for axis in 0..3_u8 {
+                        if centroid_bounds.p_max[axis] == centroid_bounds.p_min[axis] {
+                            continue;
+                        }
+                        let cost: [Float; N_SAH_BUCKETS - 1] =
+                            sah_bucket_costs(primitive_info, &bounds, &centroid_bounds, axis);
+                        for i in 0..(N_SAH_BUCKETS - 1) {
+                            if cost[i] < min_cost {
+                                min_cost = cost[i];
+                                min_cost_split_bucket = i;
+                                dim = axis;
                             }
                         }
                     }
+                    // either create leaf or split primitives at
+                    // selected SAH bucket
+                    let leaf_cost: Float = n_primitives as Float;
+                    if n_primitives > bvh.max_prims_in_node || min_cost < leaf_cost {
+                        mid = partition(primitive_info, |pi| {
+                            sah_bucket(&centroid_bounds, &pi.centroid, dim) <= min_cost_split_bucket
+                        });
+                    } else {
+                        // create leaf _BVHBuildNode_
+                        node.init_leaf(first_prim_offset, n_primitives, &bounds);
+                        return node;
+                    }
+                }
+            }
+        }
+        let (left, right) = primitive_info.split_at_mut(mid);
+        let c0: Box<BVHBuildNode>;
+        let c1: Box<BVHBuildNode>;
+        if n_primitives >= PARALLEL_BUILD_MIN_PRIMITIVES {
+            let mut nodes0: usize = 0;
+            let mut nodes1: usize = 0;
+            let (b0, b1) = rayon::join(
+                || BVHAccel::recursive_build(bvh, left, first_prim_offset, &mut nodes0),
+                || BVHAccel::recursive_build(bvh, right, first_prim_offset + mid, &mut nodes1),
+            );
+            *total_nodes += nodes0 + nodes1;
+            c0 = b0;
+            c1 = b1;
+        } else {
+            c0 = BVHAccel::recursive_build(bvh, left, first_prim_offset, total_nodes);
+            c1 = BVHAccel::recursive_build(bvh, right, first_prim_offset + mid, total_nodes);
+        }
+        node.init_interior(dim, c0, c1);
+        node
+    }
+    fn split_equal_counts(primitive_info: &mut [BVHPrimitiveInfo], dim: u8) -> usize {
+        // partition primitives into equally-sized subsets
+        let mid: usize = primitive_info.len() / 2;
+        primitive_info.select_nth_unstable_by(mid, |a, b| {
+            a.centroid[dim].partial_cmp(&b.centroid[dim]).unwrap()
+        });
+        mid
+    }
+    fn hlbvh_build(
+        bvh: &BVHAccel,
+        primitive_info: &Vec<BVHPrimitiveInfo>,
+        total_nodes: &mut usize,
+        ordered_prims: &mut Vec<Arc<Primitive + Sync + Send>>,
+    ) -> Box<BVHBuildNode> {
+        // compute bounding box of all primitive centroids
+        let mut bounds: Bounds3f = Bounds3f::default();
+        for pi in primitive_info {
+            bounds = bnd3_union_pnt3(&bounds, &pi.centroid);
+        }
+        // compute Morton indices of primitives
+        let mut morton_prims: Vec<MortonPrimitive> = primitive_info
+            .par_iter()
+            .map(|pi| {
+                const MORTON_BITS: u32 = 10;
+                const MORTON_SCALE: u32 = 1 << MORTON_BITS;
+                let centroid_offset: Vector3f = bounds.offset(&pi.centroid);
+                MortonPrimitive {
+                    primitive_index: pi.primitive_number,
+                    morton_code: encode_morton_3(&(centroid_offset * MORTON_SCALE as Float)),
                 }
-                // make sure we get result for c1 before c0
-                let c1 = BVHAccel::recursive_build(
-                    bvh.clone(),
-                    arena,
+            })
+            .collect();
+        // radix sort primitive Morton indices
+        radix_sort(&mut morton_prims);
+        // create LBVH treelets at bottom of BVH
+
+        // find intervals of primitives for each treelet
+        let mut treelets_to_build: Vec<LBVHTreelet> = Vec::new();
+        let mut start: usize = 0;
+        for end in 1..(morton_prims.len() + 1) {
+            let mask: u32 = 0b00111111111111000000000000000000;
+            if end == morton_prims.len()
+                || (morton_prims[start].morton_code & mask)
+                    != (morton_prims[end].morton_code & mask)
+            {
+                // add entry to _treelets_to_build_ for this treelet
+                let n_primitives: usize = end - start;
+                treelets_to_build.push(LBVHTreelet {
+                    start_index: start,
+                    n_primitives: n_primitives,
+                });
+                start = end;
+            }
+        }
+        // create LBVHs for treelets in parallel
+        let max_prims_in_node: usize = bvh.max_prims_in_node;
+        let emitted: Vec<(Box<BVHBuildNode>, usize)> = treelets_to_build
+            .par_iter()
+            .map(|tr| {
+                // generate _i_th LBVH treelet
+                let mut nodes_created: usize = 0;
+                const FIRST_BIT_INDEX: i32 = 29 - 12;
+                let build_node = BVHAccel::emit_lbvh(
                     primitive_info,
-                    mid,
-                    end,
-                    total_nodes,
-                    ordered_prims,
+                    &morton_prims[tr.start_index..(tr.start_index + tr.n_primitives)],
+                    tr.start_index,
+                    &mut nodes_created,
+                    FIRST_BIT_INDEX,
+                    max_prims_in_node,
                 );
-                let c0 = BVHAccel::recursive_build(
-                    bvh.clone(),
-                    arena,
+                (build_node, nodes_created)
+            })
+            .collect();
+        // leaves reference primitives in Morton order
+        for mp in &morton_prims {
+            ordered_prims.push(bvh.primitives[mp.primitive_index].clone());
+        }
+        // create and return SAH BVH from LBVH treelets
+        let mut finished_treelets: Vec<Box<BVHBuildNode>> = Vec::with_capacity(emitted.len());
+        for (build_node, nodes_created) in emitted {
+            *total_nodes += nodes_created;
+            finished_treelets.push(build_node);
+        }
+        BVHAccel::build_upper_sah(finished_treelets, total_nodes)
+    }
+    fn emit_lbvh(
+        primitive_info: &Vec<BVHPrimitiveInfo>,
+        morton_prims: &[MortonPrimitive],
+        first_prim_offset: usize,
+        total_nodes: &mut usize,
+        bit_index: i32,
+        max_prims_in_node: usize,
+    ) -> Box<BVHBuildNode> {
+        let n_primitives: usize = morton_prims.len();
+        assert!(n_primitives > 0);
+        if bit_index == -1 || n_primitives < max_prims_in_node {
+            // create and return leaf node of LBVH treelet
+            *total_nodes += 1;
+            let mut node: Box<BVHBuildNode> = Box::new(BVHBuildNode::default());
+            let mut bounds: Bounds3f = Bounds3f::default();
+            for mp in morton_prims {
+                bounds = bnd3_union_bnd3(&bounds, &primitive_info[mp.primitive_index].bounds);
+            }
+            node.init_leaf(first_prim_offset, n_primitives, &bounds);
+            return node;
+        } else {
+            let mask: u32 = 1_u32 << bit_index;
+            // advance to next subtree level if there's no LBVH split for this bit
+            if (morton_prims[0].morton_code & mask)
+                == (morton_prims[n_primitives - 1].morton_code & mask)
+            {
+                return BVHAccel::emit_lbvh(
                     primitive_info,
-                    start,
-                    mid,
+                    morton_prims,
+                    first_prim_offset,
                     total_nodes,
-                    ordered_prims,
+                    bit_index - 1,
+                    max_prims_in_node,
                 );
-                node.init_interior(dim, c0, c1);
             }
+            // find LBVH split point for this dimension
+            let mut search_start: usize = 0;
+            let mut search_end: usize = n_primitives - 1;
+            while search_start + 1 != search_end {
+                assert_ne!(search_start, search_end);
+                let mid: usize = (search_start + search_end) / 2;
+                if (morton_prims[search_start].morton_code & mask)
+                    == (morton_prims[mid].morton_code & mask)
+                {
+                    search_start = mid;
+                } else {
+                    assert_eq!(
+                        morton_prims[mid].morton_code & mask,
+                        morton_prims[search_end].morton_code & mask
+                    );
+                    search_end = mid;
+                }
+            }
+            let split_offset: usize = search_end;
+            assert!(split_offset <= n_primitives - 1);
+            // create and return interior LBVH node
+            *total_nodes += 1;
+            let mut node: Box<BVHBuildNode> = Box::new(BVHBuildNode::default());
+            let c0 = BVHAccel::emit_lbvh(
+                primitive_info,
+                &morton_prims[..split_offset],
+                first_prim_offset,
+                total_nodes,
+                bit_index - 1,
+                max_prims_in_node,
+            );
+            let c1 = BVHAccel::emit_lbvh(
+                primitive_info,
+                &morton_prims[split_offset..],
+                first_prim_offset + split_offset,
+                total_nodes,
+                bit_index - 1,
+                max_prims_in_node,
+            );
+            let axis: u8 = (bit_index % 3) as u8;
+            node.init_interior(axis, c0, c1);
+            return node;
+        }
+    }
+    fn build_upper_sah(
+        mut treelet_roots: Vec<Box<BVHBuildNode>>,
+        total_nodes: &mut usize,
+    ) -> Box<BVHBuildNode> {
+        assert!(!treelet_roots.is_empty());
+        let n_nodes: usize = treelet_roots.len();
+        if n_nodes == 1 {
+            return treelet_roots.pop().unwrap();
+        }
+        *total_nodes += 1;
+        let mut node: Box<BVHBuildNode> = Box::new(BVHBuildNode::default());
+        // compute bounds of all nodes under this HLBVH node
+        let mut bounds: Bounds3f = Bounds3f::default();
+        for root in &treelet_roots {
+            bounds = bnd3_union_bnd3(&bounds, &root.bounds);
+        }
+        // compute bound of HLBVH node centroids, choose split dimension _dim_
+        let mut centroid_bounds: Bounds3f = Bounds3f::default();
+        for root in &treelet_roots {
+            let centroid: Point3f = (root.bounds.p_min + root.bounds.p_max) * 0.5;
+            centroid_bounds = bnd3_union_pnt3(&centroid_bounds, &centroid);
+        }
+        let dim: u8 = centroid_bounds.maximum_extent();
+        let (left, right): (Vec<Box<BVHBuildNode>>, Vec<Box<BVHBuildNode>>);
+        if centroid_bounds.p_max[dim] == centroid_bounds.p_min[dim] {
+            // all centroids coincide, split the treelets into equal halves
+            right = treelet_roots.split_off(n_nodes / 2);
+            left = treelet_roots;
+        } else {
+            // allocate _BucketInfo_ for SAH partition buckets
+            let n_buckets: usize = 12;
+            let mut buckets: [BucketInfo; 12] = [BucketInfo::default(); 12];
+            let bucket_index = |b: &Bounds3f| -> usize {
+                let centroid: Float = (b.p_min[dim] + b.p_max[dim]) * 0.5;
+                let b: usize = (n_buckets as Float
+                    * ((centroid - centroid_bounds.p_min[dim])
+                        / (centroid_bounds.p_max[dim] - centroid_bounds.p_min[dim])))
+                    as usize;
+                if b == n_buckets {
+                    n_buckets - 1
+                } else {
+                    b
+                }
+            };
+            // initialize _BucketInfo_ for HLBVH SAH partition buckets
+            for root in &treelet_roots {
+                let b: usize = bucket_index(&root.bounds);
+                assert!(b < n_buckets, "b < {}", n_buckets);
+                buckets[b].count += 1;
+                buckets[b].bounds = bnd3_union_bnd3(&buckets[b].bounds, &root.bounds);
+            }
+            // compute costs for splitting after each bucket
+            let mut cost: [Float; 11] = [0.0; 11];
+            for i in 0..(n_buckets - 1) {
+                let mut b0: Bounds3f = Bounds3f::default();
+                let mut b1: Bounds3f = Bounds3f::default();
+                let mut count0: usize = 0;
+                let mut count1: usize = 0;
+                for j in 0..(i + 1) {
+                    b0 = bnd3_union_bnd3(&b0, &buckets[j].bounds);
+                    count0 += buckets[j].count;
+                }
+                for j in (i + 1)..n_buckets {
+                    b1 = bnd3_union_bnd3(&b1, &buckets[j].bounds);
+                    count1 += buckets[j].count;
+                }
+                cost[i] = 0.125
+                    + (count0 as Float * b0.surface_area() + count1 as Float * b1.surface_area())
+                        / bounds.surface_area();
+            }
+            // find bucket to split at that minimizes SAH metric
+            let mut min_cost: Float = cost[0];
+            let mut min_cost_split_bucket: usize = 0;
+            for i in 0..(n_buckets - 1) {
+                if cost[i] < min_cost {
+                    min_cost = cost[i];
+                    min_cost_split_bucket = i;
+                }
+            }
+            // split nodes and create interior HLBVH SAH node
+            let (l, r): (Vec<Box<BVHBuildNode>>, Vec<Box<BVHBuildNode>>) = treelet_roots
+                .into_iter()
+                .partition(|root| bucket_index(&root.bounds) <= min_cost_split_bucket);
+            left = l;
+            right = r;
         }
-        return node;
+        assert!(!left.is_empty());
+        assert!(!right.is_empty());
+        let c0 = BVHAccel::build_upper_sah(left, total_nodes);
+        let c1 = BVHAccel::build_upper_sah(right, total_nodes);
+        node.init_interior(dim, c0, c1);
+        node
     }
-    fn flatten_bvh_tree<'a>(
-        node: &mut BVHBuildNode<'a>,
+    fn flatten_bvh_tree(
+        node: &BVHBuildNode,
         nodes: &mut Vec<LinearBVHNode>,
         offset: &mut usize,
     ) -> usize {
@@ -392,10 +1188,10 @@ impl BVHAccel {
             nodes[my_offset] = linear_node;
         } else {
             // interior
-            if let Some(ref mut child1) = node.child1 {
+            if let Some(ref child1) = node.child1 {
                 BVHAccel::flatten_bvh_tree(child1, nodes, offset);
             }
-            if let Some(ref mut child2) = node.child2 {
+            if let Some(ref child2) = node.child2 {
                 let linear_node = LinearBVHNode {
                     bounds: node.bounds,
                     offset: BVHAccel::flatten_bvh_tree(child2, nodes, offset),
@@ -421,6 +1217,9 @@ impl Primitive for BVHAccel {
         if self.nodes.len() == 0 {
             return None;
         }
+        if !self.wide_nodes.is_empty() {
+            return self.intersect_wide(ray);
+        }
         // TODO: ProfilePhase p(Prof::AccelIntersect);
         let mut hit: bool = false;
         let inv_dir: Vector3f = Vector3f {
@@ -489,6 +1288,9 @@ impl Primitive for BVHAccel {
         if self.nodes.len() == 0 {
             return false;
         }
+        if !self.wide_nodes.is_empty() {
+            return self.intersect_p_wide(ray);
+        }
         // TODO: ProfilePhase p(Prof::AccelIntersectP);
         let inv_dir: Vector3f = Vector3f {
             x: 1.0 / ray.d.x,
@@ -540,6 +1342,20 @@ impl Primitive for BVHAccel {
         }
         false
     }
+    fn intersect_batch(&self, rays: &mut [Ray]) -> Vec<Option<SurfaceInteraction>> {
+        let mut isects: Vec<Option<SurfaceInteraction>> = Vec::with_capacity(rays.len());
+        for packet in rays.chunks_mut(RAY_PACKET_SIZE) {
+            isects.extend(self.intersect_packet(packet));
+        }
+        isects
+    }
+    fn intersect_p_batch(&self, rays: &[Ray]) -> Vec<bool> {
+        let mut occluded: Vec<bool> = Vec::with_capacity(rays.len());
+        for packet in rays.chunks(RAY_PACKET_SIZE) {
+            occluded.extend(self.intersect_p_packet(packet));
+        }
+        occluded
+    }
     fn get_material(&self) -> Option<Arc<Material + Send + Sync>> {
         None
     }
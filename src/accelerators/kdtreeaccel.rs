@@ -0,0 +1,548 @@
+// std
+use std;
+use std::sync::Arc;
+// pbrt
+use core::geometry::bnd3_union_bnd3;
+use core::geometry::{Bounds3f, Ray, Vector3f};
+use core::interaction::SurfaceInteraction;
+use core::light::AreaLight;
+use core::material::Material;
+use core::paramset::ParamSet;
+use core::pbrt::log_2_int_i32;
+use core::pbrt::Float;
+use core::primitive::Primitive;
+
+// see kdtreeaccel.h
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct KdAccelNode {
+    // in C++ a union { Float split;                 // interior
+    //                  int onePrimitive;            // leaf
+    //                  int primitiveIndicesOffset;  // leaf
+    //                };
+    split: Float,
+    one_primitive: usize,
+    primitive_indices_offset: usize,
+    // in C++ a union { int flags;       // both
+    //                  int nPrims;      // leaf
+    //                  int aboveChild;  // interior
+    //                };
+    flags: u32,
+}
+
+impl KdAccelNode {
+    pub fn init_leaf(&mut self, prim_nums: &[usize], primitive_indices: &mut Vec<usize>) {
+        let np: usize = prim_nums.len();
+        self.flags = 3_u32;
+        self.flags |= (np as u32) << 2;
+        // store primitive ids for leaf node
+        if np == 0 {
+            self.one_primitive = 0_usize;
+        } else if np == 1 {
+            self.one_primitive = prim_nums[0];
+        } else {
+            self.primitive_indices_offset = primitive_indices.len();
+            for prim_num in prim_nums {
+                primitive_indices.push(*prim_num);
+            }
+        }
+    }
+    pub fn init_interior(&mut self, axis: u8, ac: usize, s: Float) {
+        self.split = s;
+        self.flags = axis as u32;
+        self.flags |= (ac as u32) << 2;
+    }
+    pub fn split_pos(&self) -> Float {
+        self.split
+    }
+    pub fn n_primitives(&self) -> usize {
+        (self.flags >> 2) as usize
+    }
+    pub fn split_axis(&self) -> u8 {
+        (self.flags & 3) as u8
+    }
+    pub fn is_leaf(&self) -> bool {
+        (self.flags & 3) == 3
+    }
+    pub fn above_child(&self) -> usize {
+        (self.flags >> 2) as usize
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum EdgeType {
+    Start = 0,
+    End = 1,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct BoundEdge {
+    pub t: Float,
+    pub prim_num: usize,
+    pub edge_type: EdgeType,
+}
+
+impl Default for BoundEdge {
+    fn default() -> Self {
+        BoundEdge {
+            t: 0.0 as Float,
+            prim_num: 0_usize,
+            edge_type: EdgeType::Start,
+        }
+    }
+}
+
+impl BoundEdge {
+    pub fn new(t: Float, prim_num: usize, starting: bool) -> Self {
+        BoundEdge {
+            t: t,
+            prim_num: prim_num,
+            edge_type: if starting {
+                EdgeType::Start
+            } else {
+                EdgeType::End
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+struct KdToDo {
+    node: usize,
+    t_min: Float,
+    t_max: Float,
+}
+
+// KdTreeAccel -> Aggregate -> Primitive
+pub struct KdTreeAccel {
+    pub isect_cost: i32,
+    pub traversal_cost: i32,
+    pub max_prims: i32,
+    pub empty_bonus: Float,
+    pub primitives: Vec<Arc<Primitive + Sync + Send>>,
+    pub primitive_indices: Vec<usize>,
+    pub nodes: Vec<KdAccelNode>,
+    pub bounds: Bounds3f,
+}
+
+impl KdTreeAccel {
+    pub fn new(
+        p: Vec<Arc<Primitive + Sync + Send>>,
+        isect_cost: i32,
+        traversal_cost: i32,
+        empty_bonus: Float,
+        max_prims: i32,
+        max_depth: i32,
+    ) -> Self {
+        let mut kd_tree: KdTreeAccel = KdTreeAccel {
+            isect_cost: isect_cost,
+            traversal_cost: traversal_cost,
+            max_prims: max_prims,
+            empty_bonus: empty_bonus,
+            primitives: p,
+            primitive_indices: Vec::new(),
+            nodes: Vec::new(),
+            bounds: Bounds3f::default(),
+        };
+        // build kd-tree for accelerator
+        let num_prims: usize = kd_tree.primitives.len();
+        let mut max_depth: i32 = max_depth;
+        if max_depth <= 0 {
+            max_depth = (8.0 as Float + 1.3 as Float * log_2_int_i32(num_prims as i32) as Float)
+                .round() as i32;
+        }
+        // compute bounds for kd-tree construction
+        let mut prim_bounds: Vec<Bounds3f> = Vec::with_capacity(num_prims);
+        for i in 0..num_prims {
+            let b: Bounds3f = kd_tree.primitives[i].world_bound();
+            if i == 0 {
+                kd_tree.bounds = b;
+            } else {
+                kd_tree.bounds = bnd3_union_bnd3(&kd_tree.bounds, &b);
+            }
+            prim_bounds.push(b);
+        }
+        // allocate working memory for kd-tree construction
+        let mut edges: [Vec<BoundEdge>; 3] = [
+            vec![BoundEdge::default(); 2 * num_prims],
+            vec![BoundEdge::default(); 2 * num_prims],
+            vec![BoundEdge::default(); 2 * num_prims],
+        ];
+        // initialize _prim_nums_ for kd-tree construction
+        let prim_nums: Vec<usize> = (0..num_prims).collect();
+        // start recursive construction of kd-tree
+        let bounds: Bounds3f = kd_tree.bounds;
+        kd_tree.build_tree(
+            0_usize,
+            &bounds,
+            &prim_bounds,
+            &prim_nums[..],
+            max_depth,
+            &mut edges,
+            0_i32,
+        );
+        kd_tree
+    }
+    pub fn create(prims: Vec<Arc<Primitive + Send + Sync>>, ps: &ParamSet) -> Arc<KdTreeAccel> {
+        let isect_cost: i32 = ps.find_one_int("intersectcost", 80);
+        let trav_cost: i32 = ps.find_one_int("traversalcost", 1);
+        let empty_bonus: Float = ps.find_one_float("emptybonus", 0.5 as Float);
+        let max_prims: i32 = ps.find_one_int("maxprims", 1);
+        let max_depth: i32 = ps.find_one_int("maxdepth", -1);
+        Arc::new(KdTreeAccel::new(
+            prims.clone(),
+            isect_cost,
+            trav_cost,
+            empty_bonus,
+            max_prims,
+            max_depth,
+        ))
+    }
+    fn build_tree(
+        &mut self,
+        node_num: usize,
+        node_bounds: &Bounds3f,
+        all_prim_bounds: &Vec<Bounds3f>,
+        prim_nums: &[usize],
+        depth: i32,
+        edges: &mut [Vec<BoundEdge>; 3],
+        bad_refines: i32,
+    ) {
+        assert_eq!(node_num, self.nodes.len());
+        // get next free node from _nodes_ array
+        self.nodes.push(KdAccelNode::default());
+        let n_primitives: usize = prim_nums.len();
+        // initialize leaf node if termination criteria met
+        if n_primitives <= self.max_prims as usize || depth == 0 {
+            self.nodes[node_num].init_leaf(prim_nums, &mut self.primitive_indices);
+            return;
+        }
+        // initialize interior node and continue recursion
+
+        // choose split axis position for interior node
+        let mut best_axis: i32 = -1;
+        let mut best_offset: i32 = -1;
+        let mut best_cost: Float = std::f32::INFINITY;
+        let old_cost: Float = self.isect_cost as Float * n_primitives as Float;
+        let total_sa: Float = node_bounds.surface_area();
+        let inv_total_sa: Float = 1.0 as Float / total_sa;
+        let d: Vector3f = node_bounds.p_max - node_bounds.p_min;
+        // choose which axis to split along
+        let mut axis: u8 = node_bounds.maximum_extent();
+        let mut retries: i32 = 0;
+        loop {
+            // initialize edges for _axis_
+            for i in 0..n_primitives {
+                let pn: usize = prim_nums[i];
+                let bounds: &Bounds3f = &all_prim_bounds[pn];
+                edges[axis as usize][2 * i] = BoundEdge::new(bounds.p_min[axis], pn, true);
+                edges[axis as usize][2 * i + 1] = BoundEdge::new(bounds.p_max[axis], pn, false);
+            }
+            // sort _edges_ for _axis_
+            edges[axis as usize][0..(2 * n_primitives)].sort_by(|e0, e1| {
+                if e0.t == e1.t {
+                    (e0.edge_type as i32).cmp(&(e1.edge_type as i32))
+                } else {
+                    e0.t.partial_cmp(&e1.t).unwrap()
+                }
+            });
+            // compute cost of all splits for _axis_ to find best
+            let mut n_below: usize = 0;
+            let mut n_above: usize = n_primitives;
+            for i in 0..(2 * n_primitives) {
+                if edges[axis as usize][i].edge_type == EdgeType::End {
+                    n_above -= 1;
+                }
+                let edge_t: Float = edges[axis as usize][i].t;
+                if edge_t > node_bounds.p_min[axis] && edge_t < node_bounds.p_max[axis] {
+                    // compute cost for split at _i_th edge
+
+                    // compute child surface areas for split at _edge_t_
+                    let other_axis_0: u8 = (axis + 1) % 3;
+                    let other_axis_1: u8 = (axis + 2) % 3;
+                    let below_sa: Float = 2.0 as Float
+                        * (d[other_axis_0] * d[other_axis_1]
+                            + (edge_t - node_bounds.p_min[axis])
+                                * (d[other_axis_0] + d[other_axis_1]));
+                    let above_sa: Float = 2.0 as Float
+                        * (d[other_axis_0] * d[other_axis_1]
+                            + (node_bounds.p_max[axis] - edge_t)
+                                * (d[other_axis_0] + d[other_axis_1]));
+                    let p_below: Float = below_sa * inv_total_sa;
+                    let p_above: Float = above_sa * inv_total_sa;
+                    let eb: Float = if n_above == 0 || n_below == 0 {
+                        self.empty_bonus
+                    } else {
+                        0.0 as Float
+                    };
+                    let cost: Float = self.traversal_cost as Float
+                        + self.isect_cost as Float
+                            * (1.0 as Float - eb)
+                            * (p_below * n_below as Float + p_above * n_above as Float);
+                    // update best split if this is lowest cost so far
+                    if cost < best_cost {
+                        best_cost = cost;
+                        best_axis = axis as i32;
+                        best_offset = i as i32;
+                    }
+                }
+                if edges[axis as usize][i].edge_type == EdgeType::Start {
+                    n_below += 1;
+                }
+            }
+            assert!(n_below == n_primitives && n_above == 0);
+            // create leaf if no good splits were found
+            if best_axis == -1 && retries < 2 {
+                retries += 1;
+                axis = (axis + 1) % 3;
+            } else {
+                break;
+            }
+        }
+        let mut bad_refines: i32 = bad_refines;
+        if best_cost > old_cost {
+            bad_refines += 1;
+        }
+        if (best_cost > 4.0 as Float * old_cost && n_primitives < 16)
+            || best_axis == -1
+            || bad_refines == 3
+        {
+            self.nodes[node_num].init_leaf(prim_nums, &mut self.primitive_indices);
+            return;
+        }
+        // classify primitives with respect to split
+        let best_axis: usize = best_axis as usize;
+        let best_offset: usize = best_offset as usize;
+        let mut prims0: Vec<usize> = Vec::new();
+        let mut prims1: Vec<usize> = Vec::new();
+        for i in 0..best_offset {
+            if edges[best_axis][i].edge_type == EdgeType::Start {
+                prims0.push(edges[best_axis][i].prim_num);
+            }
+        }
+        for i in (best_offset + 1)..(2 * n_primitives) {
+            if edges[best_axis][i].edge_type == EdgeType::End {
+                prims1.push(edges[best_axis][i].prim_num);
+            }
+        }
+        // recursively initialize children nodes
+        let t_split: Float = edges[best_axis][best_offset].t;
+        let mut bounds0: Bounds3f = *node_bounds;
+        let mut bounds1: Bounds3f = *node_bounds;
+        bounds0.p_max[best_axis as u8] = t_split;
+        bounds1.p_min[best_axis as u8] = t_split;
+        self.build_tree(
+            node_num + 1,
+            &bounds0,
+            all_prim_bounds,
+            &prims0[..],
+            depth - 1,
+            edges,
+            bad_refines,
+        );
+        let above_child: usize = self.nodes.len();
+        self.nodes[node_num].init_interior(best_axis as u8, above_child, t_split);
+        self.build_tree(
+            above_child,
+            &bounds1,
+            all_prim_bounds,
+            &prims1[..],
+            depth - 1,
+            edges,
+            bad_refines,
+        );
+    }
+}
+
+impl Primitive for KdTreeAccel {
+    fn world_bound(&self) -> Bounds3f {
+        self.bounds
+    }
+    fn intersect(&self, ray: &mut Ray) -> Option<SurfaceInteraction> {
+        // TODO: ProfilePhase p(Prof::AccelIntersect);
+        // compute initial parametric range of ray inside kd-tree extent
+        let mut t_min: Float = 0.0 as Float;
+        let mut t_max: Float = 0.0 as Float;
+        if self.nodes.is_empty() || !self.bounds.intersect_b(ray, &mut t_min, &mut t_max) {
+            return None;
+        }
+        // prepare to traverse kd-tree for ray
+        let inv_dir: Vector3f = Vector3f {
+            x: 1.0 / ray.d.x,
+            y: 1.0 / ray.d.y,
+            z: 1.0 / ray.d.z,
+        };
+        const MAX_TODO: usize = 64;
+        let mut todo: [KdToDo; MAX_TODO] = [KdToDo::default(); MAX_TODO];
+        let mut todo_pos: usize = 0;
+        // traverse kd-tree nodes in order for ray
+        let mut hit: bool = false;
+        let mut si: SurfaceInteraction = SurfaceInteraction::default();
+        let mut node_index: usize = 0;
+        loop {
+            // bail out if we found a hit closer than the current node
+            if ray.t_max < t_min {
+                break;
+            }
+            let node: KdAccelNode = self.nodes[node_index];
+            if !node.is_leaf() {
+                // process kd-tree interior node
+
+                // compute parametric distance along ray to split plane
+                let axis: u8 = node.split_axis();
+                let t_plane: Float = (node.split_pos() - ray.o[axis]) * inv_dir[axis];
+                // get node children pointers for ray
+                let first_child: usize;
+                let second_child: usize;
+                let below_first: bool = (ray.o[axis] < node.split_pos())
+                    || (ray.o[axis] == node.split_pos() && ray.d[axis] <= 0.0 as Float);
+                if below_first {
+                    first_child = node_index + 1;
+                    second_child = node.above_child();
+                } else {
+                    first_child = node.above_child();
+                    second_child = node_index + 1;
+                }
+                // advance to next child node, possibly enqueue other child
+                if t_plane > t_max || t_plane <= 0.0 as Float {
+                    node_index = first_child;
+                } else if t_plane < t_min {
+                    node_index = second_child;
+                } else {
+                    // enqueue _second_child_ in todo list
+                    todo[todo_pos].node = second_child;
+                    todo[todo_pos].t_min = t_plane;
+                    todo[todo_pos].t_max = t_max;
+                    todo_pos += 1;
+                    node_index = first_child;
+                    t_max = t_plane;
+                }
+            } else {
+                // check for intersections inside leaf node
+                let n_primitives: usize = node.n_primitives();
+                if n_primitives == 1 {
+                    let p: &Arc<Primitive + Sync + Send> = &self.primitives[node.one_primitive];
+                    // check one primitive inside leaf node
+                    if let Some(isect) = p.intersect(ray) {
+                        si = isect;
+                        hit = true;
+                    }
+                } else {
+                    for i in 0..n_primitives {
+                        let index: usize =
+                            self.primitive_indices[node.primitive_indices_offset + i];
+                        let p: &Arc<Primitive + Sync + Send> = &self.primitives[index];
+                        // check one primitive inside leaf node
+                        if let Some(isect) = p.intersect(ray) {
+                            si = isect;
+                            hit = true;
+                        }
+                    }
+                }
+                // grab next node to process from todo list
+                if todo_pos > 0 {
+                    todo_pos -= 1;
+                    node_index = todo[todo_pos].node;
+                    t_min = todo[todo_pos].t_min;
+                    t_max = todo[todo_pos].t_max;
+                } else {
+                    break;
+                }
+            }
+        }
+        if hit {
+            Some(si)
+        } else {
+            None
+        }
+    }
+    fn intersect_p(&self, ray: &Ray) -> bool {
+        // TODO: ProfilePhase p(Prof::AccelIntersectP);
+        // compute initial parametric range of ray inside kd-tree extent
+        let mut t_min: Float = 0.0 as Float;
+        let mut t_max: Float = 0.0 as Float;
+        if self.nodes.is_empty() || !self.bounds.intersect_b(ray, &mut t_min, &mut t_max) {
+            return false;
+        }
+        // prepare to traverse kd-tree for ray
+        let inv_dir: Vector3f = Vector3f {
+            x: 1.0 / ray.d.x,
+            y: 1.0 / ray.d.y,
+            z: 1.0 / ray.d.z,
+        };
+        const MAX_TODO: usize = 64;
+        let mut todo: [KdToDo; MAX_TODO] = [KdToDo::default(); MAX_TODO];
+        let mut todo_pos: usize = 0;
+        let mut node_index: usize = 0;
+        loop {
+            let node: KdAccelNode = self.nodes[node_index];
+            if node.is_leaf() {
+                // check for shadow ray intersections inside leaf node
+                let n_primitives: usize = node.n_primitives();
+                if n_primitives == 1 {
+                    let p: &Arc<Primitive + Sync + Send> = &self.primitives[node.one_primitive];
+                    if p.intersect_p(ray) {
+                        return true;
+                    }
+                } else {
+                    for i in 0..n_primitives {
+                        let index: usize =
+                            self.primitive_indices[node.primitive_indices_offset + i];
+                        let p: &Arc<Primitive + Sync + Send> = &self.primitives[index];
+                        if p.intersect_p(ray) {
+                            return true;
+                        }
+                    }
+                }
+                // grab next node to process from todo list
+                if todo_pos > 0 {
+                    todo_pos -= 1;
+                    node_index = todo[todo_pos].node;
+                    t_min = todo[todo_pos].t_min;
+                    t_max = todo[todo_pos].t_max;
+                } else {
+                    break;
+                }
+            } else {
+                // process kd-tree interior node
+
+                // compute parametric distance along ray to split plane
+                let axis: u8 = node.split_axis();
+                let t_plane: Float = (node.split_pos() - ray.o[axis]) * inv_dir[axis];
+                // get node children pointers for ray
+                let first_child: usize;
+                let second_child: usize;
+                let below_first: bool = (ray.o[axis] < node.split_pos())
+                    || (ray.o[axis] == node.split_pos() && ray.d[axis] <= 0.0 as Float);
+                if below_first {
+                    first_child = node_index + 1;
+                    second_child = node.above_child();
+                } else {
+                    first_child = node.above_child();
+                    second_child = node_index + 1;
+                }
+                // advance to next child node, possibly enqueue other child
+                if t_plane > t_max || t_plane <= 0.0 as Float {
+                    node_index = first_child;
+                } else if t_plane < t_min {
+                    node_index = second_child;
+                } else {
+                    // enqueue _second_child_ in todo list
+                    todo[todo_pos].node = second_child;
+                    todo[todo_pos].t_min = t_plane;
+                    todo[todo_pos].t_max = t_max;
+                    todo_pos += 1;
+                    node_index = first_child;
+                    t_max = t_plane;
+                }
+            }
+        }
+        false
+    }
+    fn get_material(&self) -> Option<Arc<Material + Send + Sync>> {
+        None
+    }
+    fn get_area_light(&self) -> Option<Arc<AreaLight + Send + Sync>> {
+        None
+    }
+}
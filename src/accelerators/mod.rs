@@ -9,3 +9,4 @@
 //! - KdTreeAccel
 
 pub mod bvh;
+pub mod kdtreeaccel;
@@ -5,6 +5,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 // pbrt
 use accelerators::bvh::{BVHAccel, SplitMethod};
+use accelerators::kdtreeaccel::KdTreeAccel;
 use cameras::environment::EnvironmentCamera;
 use cameras::orthographic::OrthographicCamera;
 use cameras::perspective::PerspectiveCamera;
@@ -12,27 +13,33 @@ use cameras::realistic::RealisticCamera;
 use core::camera::Camera;
 use core::film::Film;
 use core::filter::Filter;
-use core::geometry::{vec3_coordinate_system, vec3_cross_vec3};
+use core::geometry::{bnd2_intersect_bnd2, vec3_coordinate_system, vec3_cross_vec3};
 use core::geometry::{Bounds2f, Bounds2i, Normal3f, Point2f, Point2i, Point3f, Vector3f};
+use core::ies::IesProfile;
+use core::imageio::read_image_linear;
 use core::integrator::SamplerIntegrator;
 use core::light::Light;
 use core::material::Material;
 use core::medium::get_medium_scattering_properties;
 use core::medium::{Medium, MediumInterface};
-use core::mipmap::ImageWrap;
+use core::mipmap::{ImageWrap, MipMap};
 use core::paramset::{ParamSet, TextureParams};
 use core::pbrt::{clamp_t, lerp};
 use core::pbrt::{Float, Spectrum};
 use core::primitive::{GeometricPrimitive, Primitive, TransformedPrimitive};
+use core::ptex::{PtexCache, PtexFile};
 use core::reflection::FourierBSDFTable;
 use core::sampler::Sampler;
 use core::scene::Scene;
 use core::shape::Shape;
+use core::texture::{CylindricalMapping2D, SphericalMapping2D};
+use core::texture::{IdentityMapping3D, TextureMapping3D};
 use core::texture::{PlanarMapping2D, Texture, TextureMapping2D, UVMapping2D};
 use core::transform::{AnimatedTransform, Matrix4x4, Transform};
 use filters::boxfilter::BoxFilter;
 use filters::gaussian::GaussianFilter;
 use filters::mitchell::MitchellNetravali;
+use filters::sinc::LanczosSincFilter;
 use filters::triangle::TriangleFilter;
 use integrators::ao::AOIntegrator;
 use integrators::bdpt::render_bdpt;
@@ -42,52 +49,79 @@ use integrators::mlt::render_mlt;
 use integrators::mlt::MLTIntegrator;
 use integrators::path::PathIntegrator;
 use integrators::render;
+use integrators::sppm::render_sppm;
+use integrators::sppm::SPPMIntegrator;
+use integrators::volpath::VolPathIntegrator;
+use integrators::whitted::WhittedIntegrator;
 use lights::diffuse::DiffuseAreaLight;
 use lights::distant::DistantLight;
+use lights::goniometric::GonioPhotometricLight;
 use lights::infinite::InfiniteAreaLight;
 use lights::point::PointLight;
+use lights::projection::ProjectionLight;
 use lights::spot::SpotLight;
 use materials::disney::DisneyMaterial;
 use materials::fourier::FourierMaterial;
 use materials::glass::GlassMaterial;
 use materials::hair::HairMaterial;
+use materials::kdsubsurface::KdSubsurfaceMaterial;
 use materials::matte::MatteMaterial;
 use materials::metal::MetalMaterial;
 use materials::mirror::MirrorMaterial;
 use materials::mixmat::MixMaterial;
 use materials::plastic::PlasticMaterial;
 use materials::substrate::SubstrateMaterial;
+use materials::subsurface::SubsurfaceMaterial;
+use materials::translucent::TranslucentMaterial;
 use materials::uber::UberMaterial;
+use media::grid::GridDensityMedium;
 use media::homogeneous::HomogeneousMedium;
 use samplers::halton::HaltonSampler;
+use samplers::maxmin::MaxMinDistSampler;
 use samplers::random::RandomSampler;
 use samplers::sobol::SobolSampler;
+use samplers::stratified::StratifiedSampler;
 use samplers::zerotwosequence::ZeroTwoSequenceSampler;
+use shapes::cone::Cone;
 use shapes::curve::create_curve_shape;
 use shapes::cylinder::Cylinder;
 use shapes::disk::Disk;
+use shapes::heightfield::create_heightfield;
+use shapes::hyperboloid::Hyperboloid;
 use shapes::loopsubdiv::loop_subdivide;
 use shapes::nurbs::nurbs_evaluate_surface;
 use shapes::nurbs::Homogeneous3;
+use shapes::paraboloid::Paraboloid;
 use shapes::plymesh::create_ply_mesh;
 use shapes::sphere::Sphere;
-use shapes::triangle::{Triangle, TriangleMesh};
-use textures::checkerboard::Checkerboard2DTexture;
+use shapes::triangle::{create_triangle_mesh, Triangle, TriangleMesh};
+use textures::bilerp::BilerpTexture;
+use textures::checkerboard::{Checkerboard2DTexture, Checkerboard3DTexture};
 use textures::constant::ConstantTexture;
+use textures::dots::DotsTexture;
+use textures::fbm::FBmTexture;
 use textures::imagemap::ImageTexture;
 use textures::imagemap::{convert_to_float, convert_to_spectrum};
+use textures::marble::MarbleTexture;
+use textures::mix::MixTexture;
+use textures::ptex::PtexTexture;
 use textures::scale::ScaleTexture;
+use textures::uv::UVTexture;
+use textures::windy::WindyTexture;
+use textures::wrinkled::WrinkledTexture;
 
 // see api.cpp
 
 pub struct BsdfState {
     pub loaded_bsdfs: HashMap<String, Arc<FourierBSDFTable>>,
+    pub normal_maps: HashMap<String, Option<Arc<MipMap<Spectrum>>>>,
 }
 
 impl Default for BsdfState {
     fn default() -> Self {
         BsdfState {
             loaded_bsdfs: HashMap::new(),
+            normal_maps: HashMap::new(),
         }
     }
 }
@@ -104,6 +138,7 @@ pub struct ApiState {
     pushed_transforms: Vec<TransformSet>,
     pushed_active_transform_bits: Vec<u8>,
     param_set: ParamSet,
+    ptex_cache: PtexCache,
 }
 
 impl Default for ApiState {
@@ -139,6 +174,7 @@ impl Default for ApiState {
             pushed_transforms: Vec::new(),
             pushed_active_transform_bits: Vec::new(),
             param_set: ParamSet::default(),
+            ptex_cache: PtexCache::default(),
         }
     }
 }
@@ -260,7 +296,7 @@ impl GraphicsState {
             float_textures.clone(),
             spectrum_textures.clone(),
         );
-        let mtl: Arc<Material + Send + Sync> = MatteMaterial::create(&mut tp);
+        let mtl: Arc<Material + Send + Sync> = MatteMaterial::create(&mut tp, None);
         let mut named_materials: HashMap<String, Option<Arc<Material + Send + Sync>>> =
             HashMap::new();
         named_materials.insert(String::from("matte"), Some(mtl));
@@ -281,6 +317,94 @@ impl GraphicsState {
     }
 }
 
+fn make_accelerator(
+    accelerator_name: &String,
+    prims: Vec<Arc<Primitive + Sync + Send>>,
+    ps: &ParamSet,
+) -> Arc<Primitive + Sync + Send> {
+    if accelerator_name == "bvh" {
+        BVHAccel::create(prims, ps)
+    } else if accelerator_name == "kdtree" {
+        KdTreeAccel::create(prims, ps)
+    } else {
+        panic!("Accelerator \"{}\" unknown.", accelerator_name);
+    }
+}
+
+fn make_pixel_bounds(params: &ParamSet, sample_bounds: Bounds2i) -> Bounds2i {
+    let pb: Vec<i32> = params.find_int("pixelbounds");
+    let np: usize = pb.len();
+    let mut pixel_bounds: Bounds2i = sample_bounds;
+    if np > 0 as usize {
+        if np != 4 as usize {
+            panic!(
+                "Expected four values for \"pixelbounds\" parameter. Got {}.",
+                np
+            );
+        } else {
+            pixel_bounds = bnd2_intersect_bnd2(
+                &pixel_bounds,
+                &Bounds2i {
+                    p_min: Point2i { x: pb[0], y: pb[2] },
+                    p_max: Point2i { x: pb[1], y: pb[3] },
+                },
+            );
+            if pixel_bounds.p_max.x <= pixel_bounds.p_min.x
+                || pixel_bounds.p_max.y <= pixel_bounds.p_min.y
+            {
+                println!("ERROR: Degenerate \"pixelbounds\" specified.");
+            }
+        }
+    }
+    pixel_bounds
+}
+
+fn make_normal_map(
+    api_state: &ApiState,
+    mp: &mut TextureParams,
+    bsdf_state: &mut BsdfState,
+) -> Option<Arc<MipMap<Spectrum>>> {
+    let mut filename: String = mp.find_filename("normalmap", String::new());
+    if filename == String::new() {
+        return None;
+    }
+    if let Some(ref search_directory) = api_state.search_directory {
+        let mut path_buf: PathBuf = PathBuf::from("/");
+        path_buf.push(search_directory.as_ref());
+        path_buf.push(filename);
+        filename = String::from(path_buf.to_str().unwrap());
+    }
+    if let Some(normal_map) = bsdf_state.normal_maps.get(&filename) {
+        return normal_map.clone();
+    }
+    let mut normal_map: Option<Arc<MipMap<Spectrum>>> = None;
+    // normal maps are stored without any gamma correction
+    if let Some((mut texels, res)) = read_image_linear(&filename) {
+        // flip image in y; texture coordinate space has (0,0) at the
+        // lower left corner.
+        for y in 0..res.y / 2 {
+            for x in 0..res.x {
+                let o1 = (y * res.x + x) as usize;
+                let o2 = ((res.y - 1 - y) * res.x + x) as usize;
+                texels.swap(o1, o2);
+            }
+        }
+        normal_map = Some(Arc::new(MipMap::new(
+            &res,
+            &texels[..],
+            false,
+            8.0 as Float,
+            ImageWrap::Repeat,
+        )));
+    } else {
+        println!("ERROR: Unable to read normal map {:?}", filename);
+    }
+    bsdf_state
+        .normal_maps
+        .insert(filename.clone(), normal_map.clone());
+    normal_map
+}
+
 fn create_material(
     api_state: &ApiState,
     bsdf_state: &mut BsdfState,
@@ -312,54 +436,22 @@ fn create_material(
         }
     } else {
         // MakeMaterial
+        let normal_map: Option<Arc<MipMap<Spectrum>>> =
+            make_normal_map(api_state, &mut mp, bsdf_state);
         if api_state.graphics_state.material == "" || api_state.graphics_state.material == "none" {
             return None;
         } else if api_state.graphics_state.material == "matte" {
-            return Some(MatteMaterial::create(&mut mp));
+            return Some(MatteMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "plastic" {
-            let kd = mp.get_spectrum_texture("Kd", Spectrum::new(0.25 as Float));
-            let ks = mp.get_spectrum_texture("Ks", Spectrum::new(0.25 as Float));
-            let roughness = mp.get_float_texture("roughness", 0.1 as Float);
-            // TODO: std::shared_ptr<Texture<Float>> bumpMap = mp.GetFloatTextureOrNull("bumpmap");
-            let remap_roughness: bool = mp.find_bool("remaproughness", true);
-            let plastic = Arc::new(PlasticMaterial::new(kd, ks, roughness, remap_roughness));
-            return Some(plastic);
+            return Some(PlasticMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "translucent" {
-            println!("TODO: CreateTranslucentMaterial");
+            return Some(TranslucentMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "glass" {
-            let kr = mp.get_spectrum_texture("Kr", Spectrum::new(1.0 as Float));
-            let kt = mp.get_spectrum_texture("Kt", Spectrum::new(1.0 as Float));
-            // let some_eta = mp.get_float_texture_or_null(String::from("eta"));
-            // if let Some(eta) = some_eta {
-            //     println!("some eta");
-            // } else {
-            let eta = mp.get_float_texture("index", 1.5);
-            // }
-            // std::shared_ptr<Texture<Float>> roughu =
-            //     mp.GetFloatTexture("uroughness", 0.f);
-            let roughu = mp.get_float_texture("uroughness", 0.0 as Float);
-            // std::shared_ptr<Texture<Float>> roughv =
-            //     mp.GetFloatTexture("vroughness", 0.f);
-            let roughv = mp.get_float_texture("vroughness", 0.0 as Float);
-            // std::shared_ptr<Texture<Float>> bumpMap =
-            //     mp.GetFloatTextureOrNull("bumpmap");
-            let remap_roughness: bool = mp.find_bool("remaproughness", true);
-            let glass = Arc::new(GlassMaterial {
-                kr: kr,
-                kt: kt,
-                u_roughness: roughu,
-                v_roughness: roughv,
-                index: eta,
-                remap_roughness: remap_roughness,
-            });
-            return Some(glass);
+            return Some(GlassMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "mirror" {
-            let kr = mp.get_spectrum_texture("Kr", Spectrum::new(0.9 as Float));
-            // TODO: std::shared_ptr<Texture<Float>> bumpMap = mp.GetFloatTextureOrNull("bumpmap");
-            let mirror = Arc::new(MirrorMaterial { kr: kr });
-            return Some(mirror);
+            return Some(MirrorMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "hair" {
-            return Some(HairMaterial::create(&mut mp));
+            return Some(HairMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "mix" {
             let m1: String = mp.find_string("namedmaterial1", String::from(""));
             let m2: String = mp.find_string("namedmaterial2", String::from(""));
@@ -385,19 +477,19 @@ fn create_material(
             }
             return None;
         } else if api_state.graphics_state.material == "metal" {
-            return Some(MetalMaterial::create(&mut mp));
+            return Some(MetalMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "substrate" {
-            return Some(SubstrateMaterial::create(&mut mp));
+            return Some(SubstrateMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "uber" {
-            return Some(UberMaterial::create(&mut mp));
+            return Some(UberMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "subsurface" {
-            println!("TODO: CreateSubsurfaceMaterial");
+            return Some(SubsurfaceMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "kdsubsurface" {
-            println!("TODO: CreateKdsubsurfaceMaterial");
+            return Some(KdSubsurfaceMaterial::create(&mut mp, normal_map));
         } else if api_state.graphics_state.material == "fourier" {
-            return Some(FourierMaterial::create(&mut mp, bsdf_state));
+            return Some(FourierMaterial::create(&mut mp, normal_map, bsdf_state));
         } else if api_state.graphics_state.material == "disney" {
-            return Some(DisneyMaterial::create(&mut mp));
+            return Some(DisneyMaterial::create(&mut mp, normal_map));
         } else {
             panic!(
                 "Material \"{}\" unknown.",
@@ -407,7 +499,7 @@ fn create_material(
     }
     let kd = Arc::new(ConstantTexture::new(Spectrum::new(0.5)));
     let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
-    Some(Arc::new(MatteMaterial::new(kd, sigma)))
+    Some(Arc::new(MatteMaterial::new(kd, sigma, None, None)))
 }
 
 fn create_medium_interface(api_state: &ApiState) -> MediumInterface {
@@ -445,6 +537,26 @@ fn create_medium_interface(api_state: &ApiState) -> MediumInterface {
     m
 }
 
+fn make_ies_profile(api_state: &ApiState) -> Option<Arc<IesProfile>> {
+    let mut iesname: String = api_state
+        .param_set
+        .find_one_filename("ies", String::from(""));
+    if iesname == String::from("") {
+        return None;
+    }
+    if let Some(ref search_directory) = api_state.search_directory {
+        let mut path_buf: PathBuf = PathBuf::from("/");
+        path_buf.push(search_directory.as_ref());
+        path_buf.push(iesname);
+        iesname = String::from(path_buf.to_str().unwrap());
+    }
+    if let Some(profile) = IesProfile::read(&iesname) {
+        Some(Arc::new(profile))
+    } else {
+        None
+    }
+}
+
 fn make_light(api_state: &mut ApiState, medium_interface: &MediumInterface) {
     // MakeLight (api.cpp:591)
     if api_state.param_set.name == "point" {
@@ -462,7 +574,13 @@ fn make_light(api_state: &mut ApiState, medium_interface: &MediumInterface) {
             y: p.y,
             z: p.z,
         }) * api_state.cur_transform.t[0];
-        let point_light = Arc::new(PointLight::new(&l2w, medium_interface, &(i * sc)));
+        let profile: Option<Arc<IesProfile>> = make_ies_profile(api_state);
+        let point_light = Arc::new(PointLight::new(
+            &l2w,
+            medium_interface,
+            &(i * sc),
+            profile,
+        ));
         api_state.render_options.lights.push(point_light);
     } else if api_state.param_set.name == "spot" {
         // CreateSpotLight
@@ -510,6 +628,7 @@ fn make_light(api_state: &mut ApiState, medium_interface: &MediumInterface) {
                 z: from.z,
             })
             * Transform::inverse(&dir_to_z);
+        let profile: Option<Arc<IesProfile>> = make_ies_profile(api_state);
         // return std::make_shared<SpotLight>(light2world, medium, I * sc, coneangle, coneangle - conedelta);
         let spot_light = Arc::new(SpotLight::new(
             &light2world,
@@ -517,12 +636,68 @@ fn make_light(api_state: &mut ApiState, medium_interface: &MediumInterface) {
             &(i * sc),
             coneangle,
             coneangle - conedelta,
+            profile,
         ));
         api_state.render_options.lights.push(spot_light);
     } else if api_state.param_set.name == "goniometric" {
-        println!("TODO: CreateGoniometricLight");
+        // CreateGoniometricLight
+        let i: Spectrum = api_state
+            .param_set
+            .find_one_spectrum("I", Spectrum::new(1.0 as Float));
+        let sc: Spectrum = api_state
+            .param_set
+            .find_one_spectrum("scale", Spectrum::new(1.0 as Float));
+        let mut texname: String = api_state
+            .param_set
+            .find_one_filename("mapname", String::from(""));
+        if texname != String::from("") {
+            if let Some(ref search_directory) = api_state.search_directory {
+                let mut path_buf: PathBuf = PathBuf::from("/");
+                path_buf.push(search_directory.as_ref());
+                path_buf.push(texname);
+                texname = String::from(path_buf.to_str().unwrap());
+            }
+        }
+        // an IES profile (if given) replaces the image _texname_
+        let profile: Option<Arc<IesProfile>> = make_ies_profile(api_state);
+        // return std::make_shared<GonioPhotometricLight>(light2world, medium, I * sc, texname);
+        let goniometric_light = Arc::new(GonioPhotometricLight::new(
+            &api_state.cur_transform.t[0],
+            medium_interface,
+            &(i * sc),
+            texname,
+            profile,
+        ));
+        api_state.render_options.lights.push(goniometric_light);
     } else if api_state.param_set.name == "projection" {
-        println!("TODO: CreateProjectionLight");
+        // CreateProjectionLight
+        let i: Spectrum = api_state
+            .param_set
+            .find_one_spectrum("I", Spectrum::new(1.0 as Float));
+        let sc: Spectrum = api_state
+            .param_set
+            .find_one_spectrum("scale", Spectrum::new(1.0 as Float));
+        let fov: Float = api_state.param_set.find_one_float("fov", 45.0 as Float);
+        let mut texname: String = api_state
+            .param_set
+            .find_one_filename("mapname", String::from(""));
+        if texname != String::from("") {
+            if let Some(ref search_directory) = api_state.search_directory {
+                let mut path_buf: PathBuf = PathBuf::from("/");
+                path_buf.push(search_directory.as_ref());
+                path_buf.push(texname);
+                texname = String::from(path_buf.to_str().unwrap());
+            }
+        }
+        // return std::make_shared<ProjectionLight>(light2world, medium, I * sc, texname, fov);
+        let projection_light = Arc::new(ProjectionLight::new(
+            &api_state.cur_transform.t[0],
+            medium_interface,
+            &(i * sc),
+            texname,
+            fov,
+        ));
+        api_state.render_options.lights.push(projection_light);
     } else if api_state.param_set.name == "distant" {
         // CreateDistantLight
         let l: Spectrum = api_state
@@ -616,7 +791,52 @@ fn make_medium(api_state: &mut ApiState) {
     if medium_type == "homogeneous" {
         some_medium = Some(Arc::new(HomogeneousMedium::new(&sig_a, &sig_s, g)));
     } else if medium_type == "heterogeneous" {
-        panic!("TODO: make_medium(\"heterogeneous\")");
+        let data: Vec<Float> = api_state.param_set.find_float("density");
+        if data.len() == 0 {
+            panic!("ERROR: No \"density\" values provided for heterogeneous medium?");
+        }
+        let nx: i32 = api_state.param_set.find_one_int("nx", 1);
+        let ny: i32 = api_state.param_set.find_one_int("ny", 1);
+        let nz: i32 = api_state.param_set.find_one_int("nz", 1);
+        let p0: Point3f = api_state.param_set.find_one_point3f(
+            "p0",
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        let p1: Point3f = api_state.param_set.find_one_point3f(
+            "p1",
+            Point3f {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+        );
+        if data.len() as i32 != nx * ny * nz {
+            panic!(
+                "ERROR: GridDensityMedium has {} density values; expected nx*ny*nz = {}",
+                data.len(),
+                nx * ny * nz
+            );
+        }
+        let data_to_medium: Transform = Transform::translate(&Vector3f {
+            x: p0.x,
+            y: p0.y,
+            z: p0.z,
+        }) * Transform::scale(p1.x - p0.x, p1.y - p0.y, p1.z - p0.z);
+        let medium_to_world: Transform = api_state.cur_transform.t[0] * data_to_medium;
+        some_medium = Some(Arc::new(GridDensityMedium::new(
+            &sig_a,
+            &sig_s,
+            g,
+            nx,
+            ny,
+            nz,
+            &medium_to_world,
+            data,
+        )));
     } else {
         panic!("MakeMedium: unknown name {}", medium_type);
     }
@@ -628,6 +848,53 @@ fn make_medium(api_state: &mut ApiState) {
     }
 }
 
+fn make_texture_mapping_2d(
+    tp: &mut TextureParams,
+    tex2world: &Transform,
+) -> Box<TextureMapping2D + Send + Sync> {
+    // initialize 2D texture mapping _map_ from _tp_
+    let mapping: String = tp.find_string("mapping", String::from("uv"));
+    if mapping == "uv" {
+        let su: Float = tp.find_float("uscale", 1.0);
+        let sv: Float = tp.find_float("vscale", 1.0);
+        let du: Float = tp.find_float("udelta", 0.0);
+        let dv: Float = tp.find_float("vdelta", 0.0);
+        Box::new(UVMapping2D {
+            su: su,
+            sv: sv,
+            du: du,
+            dv: dv,
+        })
+    } else if mapping == "spherical" {
+        Box::new(SphericalMapping2D::new(&Transform::inverse(tex2world)))
+    } else if mapping == "cylindrical" {
+        Box::new(CylindricalMapping2D::new(&Transform::inverse(tex2world)))
+    } else if mapping == "planar" {
+        Box::new(PlanarMapping2D {
+            vs: tp.find_vector3f(
+                "v1",
+                Vector3f {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            ),
+            vt: tp.find_vector3f(
+                "v2",
+                Vector3f {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+            ),
+            ds: tp.find_float("udelta", 0.0),
+            dt: tp.find_float("vdelta", 0.0),
+        })
+    } else {
+        panic!("2D texture mapping \"{}\" unknown", mapping);
+    }
+}
+
 fn make_texture(api_state: &mut ApiState) {
     // pbrtTexture (api.cpp:1049)
     let mut geom_params: ParamSet = ParamSet::default();
@@ -654,7 +921,12 @@ fn make_texture(api_state: &mut ApiState) {
         // TODO: WARN_IF_ANIMATED_TRANSFORM("Texture");
         // MakeFloatTexture(texname, curTransform[0], tp);
         if api_state.param_set.tex_name == "constant" {
-            println!("TODO: CreateConstantFloatTexture");
+            // CreateConstantFloatTexture
+            let ft = Arc::new(ConstantTexture::new(tp.find_float("value", 1.0)));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "scale" {
             let ft = Arc::new(ScaleTexture::<Float>::new(
                 tp.get_float_texture("tex1", 1.0 as Float),
@@ -665,52 +937,35 @@ fn make_texture(api_state: &mut ApiState) {
                 .float_textures
                 .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "mix" {
-            println!("TODO: CreateMixFloatTexture");
+            // CreateMixFloatTexture
+            let ft = Arc::new(MixTexture::<Float>::new(
+                tp.get_float_texture("tex1", 0.0 as Float),
+                tp.get_float_texture("tex2", 1.0 as Float),
+                tp.get_float_texture("amount", 0.5 as Float),
+            ));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "bilerp" {
-            println!("TODO: CreateBilerpFloatTexture");
+            // CreateBilerpFloatTexture
+            let map: Box<TextureMapping2D + Send + Sync> =
+                make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
+            let ft = Arc::new(BilerpTexture::new(
+                map,
+                tp.find_float("v00", 0.0),
+                tp.find_float("v01", 1.0),
+                tp.find_float("v10", 0.0),
+                tp.find_float("v11", 1.0),
+            ));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "imagemap" {
             // CreateImageFloatTexture
-            let mut map: Option<Box<TextureMapping2D + Send + Sync>> = None;
-            let mapping: String = tp.find_string("mapping", String::from("uv"));
-            if mapping == "uv" {
-                let su: Float = tp.find_float("uscale", 1.0);
-                let sv: Float = tp.find_float("vscale", 1.0);
-                let du: Float = tp.find_float("udelta", 0.0);
-                let dv: Float = tp.find_float("vdelta", 0.0);
-                map = Some(Box::new(UVMapping2D {
-                    su: su,
-                    sv: sv,
-                    du: du,
-                    dv: dv,
-                }));
-            } else if mapping == "spherical" {
-                println!("TODO: SphericalMapping2D");
-            } else if mapping == "cylindrical" {
-                println!("TODO: CylindricalMapping2D");
-            } else if mapping == "planar" {
-                map = Some(Box::new(PlanarMapping2D {
-                    vs: tp.find_vector3f(
-                        "v1",
-                        Vector3f {
-                            x: 1.0,
-                            y: 0.0,
-                            z: 0.0,
-                        },
-                    ),
-                    vt: tp.find_vector3f(
-                        "v2",
-                        Vector3f {
-                            x: 0.0,
-                            y: 1.0,
-                            z: 0.0,
-                        },
-                    ),
-                    ds: tp.find_float("udelta", 0.0),
-                    dt: tp.find_float("vdelta", 0.0),
-                }));
-            } else {
-                panic!("2D texture mapping \"{}\" unknown", mapping);
-            }
+            let map: Box<TextureMapping2D + Send + Sync> =
+                make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
             // initialize _ImageTexture_ parameters
             let max_aniso: Float = tp.find_float("maxanisotropy", 8.0);
             let do_trilinear: bool = tp.find_bool("trilinear", false);
@@ -737,38 +992,132 @@ fn make_texture(api_state: &mut ApiState) {
             // ".png"));
             let gamma: bool = tp.find_bool("gamma", true);
 
-            if let Some(mapping) = map {
-                let ft = Arc::new(ImageTexture::new(
-                    mapping,
-                    filename,
-                    do_trilinear,
-                    max_aniso,
-                    wrap_mode,
-                    scale,
-                    gamma,
-                    convert_to_float,
-                ));
+            let ft = Arc::new(ImageTexture::new(
+                map,
+                filename,
+                do_trilinear,
+                max_aniso,
+                wrap_mode,
+                scale,
+                gamma,
+                convert_to_float,
+            ));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
+        } else if api_state.param_set.tex_name == "uv" {
+            // CreateUVFloatTexture
+            println!("Float \"uv\" texture not supported, use a spectrum texture.");
+        } else if api_state.param_set.tex_name == "checkerboard" {
+            // CreateCheckerboardFloatTexture
+            let dim: i32 = tp.find_int("dimension", 2);
+            if dim != 2 && dim != 3 {
+                panic!("{} dimensional checkerboard texture not supported", dim);
+            }
+            let tex1: Arc<Texture<Float> + Send + Sync> =
+                tp.get_float_texture("tex1", 1.0 as Float);
+            let tex2: Arc<Texture<Float> + Send + Sync> =
+                tp.get_float_texture("tex2", 0.0 as Float);
+            if dim == 2 {
+                let map: Box<TextureMapping2D + Send + Sync> =
+                    make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
+                // TODO: aamode
+                let ft = Arc::new(Checkerboard2DTexture::new(map, tex1, tex2));
+                api_state
+                    .graphics_state
+                    .float_textures
+                    .insert(api_state.param_set.name.clone(), ft);
+            } else {
+                // dim == 3
+                let map: Box<TextureMapping3D + Send + Sync> =
+                    Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+                let ft = Arc::new(Checkerboard3DTexture::new(map, tex1, tex2));
                 api_state
                     .graphics_state
                     .float_textures
                     .insert(api_state.param_set.name.clone(), ft);
             }
-        } else if api_state.param_set.tex_name == "uv" {
-            println!("TODO: CreateUVFloatTexture");
-        } else if api_state.param_set.tex_name == "checkerboard" {
-            println!("TODO: CreateCheckerboardFloatTexture");
         } else if api_state.param_set.tex_name == "dots" {
-            println!("TODO: CreateDotsFloatTexture");
+            // CreateDotsFloatTexture
+            let map: Box<TextureMapping2D + Send + Sync> =
+                make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
+            let ft = Arc::new(DotsTexture::<Float>::new(
+                map,
+                tp.get_float_texture("outside", 0.0 as Float),
+                tp.get_float_texture("inside", 1.0 as Float),
+            ));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "fbm" {
-            println!("TODO: CreateFBmFloatTexture");
+            // CreateFBmFloatTexture
+            let map: Box<TextureMapping3D + Send + Sync> =
+                Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+            let ft = Arc::new(FBmTexture::new(
+                map,
+                tp.find_int("octaves", 8),
+                tp.find_float("roughness", 0.5),
+            ));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "wrinkled" {
-            println!("TODO: CreateWrinkledFloatTexture");
+            // CreateWrinkledFloatTexture
+            let map: Box<TextureMapping3D + Send + Sync> =
+                Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+            let ft = Arc::new(WrinkledTexture::new(
+                map,
+                tp.find_int("octaves", 8),
+                tp.find_float("roughness", 0.5),
+            ));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "marble" {
-            println!("TODO: CreateMarbleFloatTexture");
+            // CreateMarbleFloatTexture
+            let map: Box<TextureMapping3D + Send + Sync> =
+                Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+            let ft = Arc::new(MarbleTexture::new(
+                map,
+                tp.find_int("octaves", 8),
+                tp.find_float("roughness", 0.5),
+                tp.find_float("scale", 1.0),
+                tp.find_float("variation", 0.2),
+            ));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "windy" {
-            println!("TODO: CreateWindyFloatTexture");
+            // CreateWindyFloatTexture
+            let map: Box<TextureMapping3D + Send + Sync> =
+                Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+            let ft = Arc::new(WindyTexture::new(map));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "ptex" {
-            println!("TODO: CreatePtexFloatTexture");
+            // CreatePtexFloatTexture
+            let mut filename: String = tp.find_filename("filename", String::new());
+            if let Some(ref search_directory) = api_state.search_directory {
+                // filename = AbsolutePath(ResolveFilename(filename));
+                let mut path_buf: PathBuf = PathBuf::from("/");
+                path_buf.push(search_directory.as_ref());
+                path_buf.push(filename);
+                filename = String::from(path_buf.to_str().unwrap());
+            }
+            let gamma: Float = tp.find_float("gamma", 2.2);
+            let ptex: Option<Arc<PtexFile>> = api_state.ptex_cache.get(&filename);
+            let ft = Arc::new(PtexTexture::<Float>::new(filename, ptex, gamma));
+            api_state
+                .graphics_state
+                .float_textures
+                .insert(api_state.param_set.name.clone(), ft);
         } else {
             println!(
                 "Float texture \"{}\" unknown.",
@@ -790,7 +1139,14 @@ fn make_texture(api_state: &mut ApiState) {
         // TODO: WARN_IF_ANIMATED_TRANSFORM("Texture");
         // MakeSpectrumTexture(texname, curTransform[0], tp);
         if api_state.param_set.tex_name == "constant" {
-            println!("TODO: CreateConstantSpectrumTexture");
+            // CreateConstantSpectrumTexture
+            let st = Arc::new(ConstantTexture::new(
+                tp.find_spectrum("value", Spectrum::new(1.0)),
+            ));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "scale" {
             let tex1: Arc<Texture<Spectrum> + Send + Sync> =
                 tp.get_spectrum_texture("tex1", Spectrum::new(1.0));
@@ -802,52 +1158,37 @@ fn make_texture(api_state: &mut ApiState) {
                 .spectrum_textures
                 .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "mix" {
-            println!("TODO: CreateMixSpectrumTexture");
+            // CreateMixSpectrumTexture
+            let tex1: Arc<Texture<Spectrum> + Send + Sync> =
+                tp.get_spectrum_texture("tex1", Spectrum::new(0.0));
+            let tex2: Arc<Texture<Spectrum> + Send + Sync> =
+                tp.get_spectrum_texture("tex2", Spectrum::new(1.0));
+            let amount: Arc<Texture<Float> + Send + Sync> =
+                tp.get_float_texture("amount", 0.5 as Float);
+            let st = Arc::new(MixTexture::<Spectrum>::new(tex1, tex2, amount));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "bilerp" {
-            println!("TODO: CreateBilerpSpectrumTexture");
+            // CreateBilerpSpectrumTexture
+            let map: Box<TextureMapping2D + Send + Sync> =
+                make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
+            let st = Arc::new(BilerpTexture::new(
+                map,
+                tp.find_spectrum("v00", Spectrum::new(0.0)),
+                tp.find_spectrum("v01", Spectrum::new(1.0)),
+                tp.find_spectrum("v10", Spectrum::new(0.0)),
+                tp.find_spectrum("v11", Spectrum::new(1.0)),
+            ));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "imagemap" {
             // CreateImageSpectrumTexture
-            let mut map: Option<Box<TextureMapping2D + Send + Sync>> = None;
-            let mapping: String = tp.find_string("mapping", String::from("uv"));
-            if mapping == "uv" {
-                let su: Float = tp.find_float("uscale", 1.0);
-                let sv: Float = tp.find_float("vscale", 1.0);
-                let du: Float = tp.find_float("udelta", 0.0);
-                let dv: Float = tp.find_float("vdelta", 0.0);
-                map = Some(Box::new(UVMapping2D {
-                    su: su,
-                    sv: sv,
-                    du: du,
-                    dv: dv,
-                }));
-            } else if mapping == "spherical" {
-                println!("TODO: SphericalMapping2D");
-            } else if mapping == "cylindrical" {
-                println!("TODO: CylindricalMapping2D");
-            } else if mapping == "planar" {
-                map = Some(Box::new(PlanarMapping2D {
-                    vs: tp.find_vector3f(
-                        "v1",
-                        Vector3f {
-                            x: 1.0,
-                            y: 0.0,
-                            z: 0.0,
-                        },
-                    ),
-                    vt: tp.find_vector3f(
-                        "v2",
-                        Vector3f {
-                            x: 0.0,
-                            y: 1.0,
-                            z: 0.0,
-                        },
-                    ),
-                    ds: tp.find_float("udelta", 0.0),
-                    dt: tp.find_float("vdelta", 0.0),
-                }));
-            } else {
-                panic!("2D texture mapping \"{}\" unknown", mapping);
-            }
+            let map: Box<TextureMapping2D + Send + Sync> =
+                make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
             // initialize _ImageTexture_ parameters
             let max_aniso: Float = tp.find_float("maxanisotropy", 8.0);
             let do_trilinear: bool = tp.find_bool("trilinear", false);
@@ -874,24 +1215,29 @@ fn make_texture(api_state: &mut ApiState) {
             // ".png"));
             let gamma: bool = tp.find_bool("gamma", true);
 
-            if let Some(mapping) = map {
-                let st = Arc::new(ImageTexture::new(
-                    mapping,
-                    filename,
-                    do_trilinear,
-                    max_aniso,
-                    wrap_mode,
-                    scale,
-                    gamma,
-                    convert_to_spectrum,
-                ));
-                api_state
-                    .graphics_state
-                    .spectrum_textures
-                    .insert(api_state.param_set.name.clone(), st);
-            }
+            let st = Arc::new(ImageTexture::new(
+                map,
+                filename,
+                do_trilinear,
+                max_aniso,
+                wrap_mode,
+                scale,
+                gamma,
+                convert_to_spectrum,
+            ));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "uv" {
-            println!("TODO: CreateUVSpectrumTexture");
+            // CreateUVSpectrumTexture
+            let map: Box<TextureMapping2D + Send + Sync> =
+                make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
+            let st = Arc::new(UVTexture::new(map));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "checkerboard" {
             // CreateCheckerboardSpectrumTexture
             let dim: i32 = tp.find_int("dimension", 2);
@@ -903,69 +1249,104 @@ fn make_texture(api_state: &mut ApiState) {
             let tex2: Arc<Texture<Spectrum> + Send + Sync> =
                 tp.get_spectrum_texture("tex2", Spectrum::new(0.0));
             if dim == 2 {
-                let mut map: Option<Box<TextureMapping2D + Send + Sync>> = None;
-                let mapping: String = tp.find_string("mapping", String::from("uv"));
-                if mapping == "uv" {
-                    let su: Float = tp.find_float("uscale", 1.0);
-                    let sv: Float = tp.find_float("vscale", 1.0);
-                    let du: Float = tp.find_float("udelta", 0.0);
-                    let dv: Float = tp.find_float("vdelta", 0.0);
-                    map = Some(Box::new(UVMapping2D {
-                        su: su,
-                        sv: sv,
-                        du: du,
-                        dv: dv,
-                    }));
-                } else if mapping == "spherical" {
-                    println!("TODO: SphericalMapping2D");
-                } else if mapping == "cylindrical" {
-                    println!("TODO: CylindricalMapping2D");
-                } else if mapping == "planar" {
-                    map = Some(Box::new(PlanarMapping2D {
-                        vs: tp.find_vector3f(
-                            "v1",
-                            Vector3f {
-                                x: 1.0,
-                                y: 0.0,
-                                z: 0.0,
-                            },
-                        ),
-                        vt: tp.find_vector3f(
-                            "v2",
-                            Vector3f {
-                                x: 0.0,
-                                y: 1.0,
-                                z: 0.0,
-                            },
-                        ),
-                        ds: tp.find_float("udelta", 0.0),
-                        dt: tp.find_float("vdelta", 0.0),
-                    }));
-                } else {
-                    panic!("2D texture mapping \"{}\" unknown", mapping);
-                }
+                let map: Box<TextureMapping2D + Send + Sync> =
+                    make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
                 // TODO: aamode
-                if let Some(mapping) = map {
-                    let st = Arc::new(Checkerboard2DTexture::new(mapping, tex1, tex2));
-                    api_state
-                        .graphics_state
-                        .spectrum_textures
-                        .insert(api_state.param_set.name.clone(), st);
-                }
+                let st = Arc::new(Checkerboard2DTexture::new(map, tex1, tex2));
+                api_state
+                    .graphics_state
+                    .spectrum_textures
+                    .insert(api_state.param_set.name.clone(), st);
             } else {
                 // dim == 3
-                println!("TODO: TextureMapping3D");
+                let map: Box<TextureMapping3D + Send + Sync> =
+                    Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+                let st = Arc::new(Checkerboard3DTexture::new(map, tex1, tex2));
+                api_state
+                    .graphics_state
+                    .spectrum_textures
+                    .insert(api_state.param_set.name.clone(), st);
             }
         } else if api_state.param_set.tex_name == "dots" {
-            println!("TODO: CreateDotsSpectrumTexture");
+            // CreateDotsSpectrumTexture
+            let map: Box<TextureMapping2D + Send + Sync> =
+                make_texture_mapping_2d(&mut tp, &api_state.cur_transform.t[0]);
+            let outside: Arc<Texture<Spectrum> + Send + Sync> =
+                tp.get_spectrum_texture("outside", Spectrum::new(0.0));
+            let inside: Arc<Texture<Spectrum> + Send + Sync> =
+                tp.get_spectrum_texture("inside", Spectrum::new(1.0));
+            let st = Arc::new(DotsTexture::<Spectrum>::new(map, outside, inside));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "fbm" {
-            println!("TODO: CreateFBmSpectrumTexture");
+            // CreateFBmSpectrumTexture
+            let map: Box<TextureMapping3D + Send + Sync> =
+                Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+            let st = Arc::new(FBmTexture::new(
+                map,
+                tp.find_int("octaves", 8),
+                tp.find_float("roughness", 0.5),
+            ));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "wrinkled" {
-            println!("TODO: CreateWrinkledSpectrumTexture");
+            // CreateWrinkledSpectrumTexture
+            let map: Box<TextureMapping3D + Send + Sync> =
+                Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+            let st = Arc::new(WrinkledTexture::new(
+                map,
+                tp.find_int("octaves", 8),
+                tp.find_float("roughness", 0.5),
+            ));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "marble" {
-            println!("TODO: CreateMarbleSpectrumTexture");
+            // CreateMarbleSpectrumTexture
+            let map: Box<TextureMapping3D + Send + Sync> =
+                Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+            let st = Arc::new(MarbleTexture::new(
+                map,
+                tp.find_int("octaves", 8),
+                tp.find_float("roughness", 0.5),
+                tp.find_float("scale", 1.0),
+                tp.find_float("variation", 0.2),
+            ));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "windy" {
-            println!("TODO: CreateWindySpectrumTexture");
+            // CreateWindySpectrumTexture
+            let map: Box<TextureMapping3D + Send + Sync> =
+                Box::new(IdentityMapping3D::new(&api_state.cur_transform.t[0]));
+            let st = Arc::new(WindyTexture::new(map));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
+        } else if api_state.param_set.tex_name == "ptex" {
+            // CreatePtexSpectrumTexture
+            let mut filename: String = tp.find_filename("filename", String::new());
+            if let Some(ref search_directory) = api_state.search_directory {
+                // filename = AbsolutePath(ResolveFilename(filename));
+                let mut path_buf: PathBuf = PathBuf::from("/");
+                path_buf.push(search_directory.as_ref());
+                path_buf.push(filename);
+                filename = String::from(path_buf.to_str().unwrap());
+            }
+            let gamma: Float = tp.find_float("gamma", 2.2);
+            let ptex: Option<Arc<PtexFile>> = api_state.ptex_cache.get(&filename);
+            let st = Arc::new(PtexTexture::<Spectrum>::new(filename, ptex, gamma));
+            api_state
+                .graphics_state
+                .spectrum_textures
+                .insert(api_state.param_set.name.clone(), st);
         } else {
             println!(
                 "Spectrum texture \"{}\" unknown.",
@@ -1065,11 +1446,66 @@ fn get_shapes_and_materials(
         shapes.push(disk.clone());
         materials.push(mtl.clone());
     } else if api_state.param_set.name == "cone" {
-        println!("TODO: CreateConeShape");
+        let radius: Float = api_state.param_set.find_one_float("radius", 1.0);
+        let height: Float = api_state.param_set.find_one_float("height", 1.0);
+        let phi_max: Float = api_state.param_set.find_one_float("phimax", 360.0);
+        let cone = Arc::new(Cone::new(
+            obj_to_world,
+            world_to_obj,
+            false,
+            height,
+            radius,
+            phi_max,
+        ));
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state, bsdf_state);
+        shapes.push(cone.clone());
+        materials.push(mtl.clone());
     } else if api_state.param_set.name == "paraboloid" {
-        println!("TODO: CreateParaboloidShape");
+        let radius: Float = api_state.param_set.find_one_float("radius", 1.0);
+        let z_min: Float = api_state.param_set.find_one_float("zmin", 0.0);
+        let z_max: Float = api_state.param_set.find_one_float("zmax", 1.0);
+        let phi_max: Float = api_state.param_set.find_one_float("phimax", 360.0);
+        let paraboloid = Arc::new(Paraboloid::new(
+            obj_to_world,
+            world_to_obj,
+            false,
+            radius,
+            z_min,
+            z_max,
+            phi_max,
+        ));
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state, bsdf_state);
+        shapes.push(paraboloid.clone());
+        materials.push(mtl.clone());
     } else if api_state.param_set.name == "hyperboloid" {
-        println!("TODO: CreateHyperboloidShape");
+        let p1: Point3f = api_state.param_set.find_one_point3f(
+            "p1",
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        let p2: Point3f = api_state.param_set.find_one_point3f(
+            "p2",
+            Point3f {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+        );
+        let phi_max: Float = api_state.param_set.find_one_float("phimax", 360.0);
+        let hyperboloid = Arc::new(Hyperboloid::new(
+            obj_to_world,
+            world_to_obj,
+            false,
+            p1,
+            p2,
+            phi_max,
+        ));
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state, bsdf_state);
+        shapes.push(hyperboloid.clone());
+        materials.push(mtl.clone());
     } else if api_state.param_set.name == "curve" {
         let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state, bsdf_state);
         let curve_shapes: Vec<Arc<Shape + Send + Sync>> = create_curve_shape(
@@ -1115,24 +1551,12 @@ fn get_shapes_and_materials(
         assert!(vi.len() > 0_usize);
         assert!(p.len() > 0_usize);
         let s = api_state.param_set.find_vector3f("S");
-        let mut s_ws: Vec<Vector3f> = Vec::new();
         if !s.is_empty() {
             assert!(s.len() == p.len());
-            // transform tangents to world space
-            let n_tangents: usize = s.len();
-            for i in 0..n_tangents {
-                s_ws.push(obj_to_world.transform_vector(&s[i]));
-            }
         }
         let n = api_state.param_set.find_normal3f("N");
-        let mut n_ws: Vec<Normal3f> = Vec::new();
         if !n.is_empty() {
             assert!(n.len() == p.len());
-            // transform normals to world space
-            let n_normals: usize = n.len();
-            for i in 0..n_normals {
-                n_ws.push(obj_to_world.transform_normal(&n[i]));
-            }
         }
         for i in 0..vi.len() {
             if vi[i] as usize >= p.len() {
@@ -1143,42 +1567,43 @@ fn get_shapes_and_materials(
                 );
             }
         }
-        // TODO: alpha
-        // CreateTriangleMesh
-        // transform mesh vertices to world space
-        let mut p_ws: Vec<Point3f> = Vec::new();
-        let n_vertices: usize = p.len();
-        for i in 0..n_vertices {
-            p_ws.push(obj_to_world.transform_point(&p[i]));
+        let fi = api_state.param_set.find_int("faceIndices");
+        let mut face_indices: Vec<usize> = Vec::new();
+        if !fi.is_empty() {
+            if fi.len() != vi.len() / 3 {
+                println!(
+                    "Number of \"faceIndices\" {} not equal to number of triangles {}",
+                    fi.len(),
+                    vi.len() / 3
+                );
+            } else {
+                for i in 0..fi.len() {
+                    face_indices.push(fi[i] as usize);
+                }
+            }
         }
+        // TODO: alpha
         // vertex indices are expected as usize, not i32
         let mut vertex_indices: Vec<usize> = Vec::new();
         for i in 0..vi.len() {
             vertex_indices.push(vi[i] as usize);
         }
-        let mesh = Arc::new(TriangleMesh::new(
-            obj_to_world,
-            world_to_obj,
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state, bsdf_state);
+        let mesh_shapes: Vec<Arc<Shape + Send + Sync>> = create_triangle_mesh(
+            &obj_to_world,
+            &world_to_obj,
             api_state.graphics_state.reverse_orientation,
-            false,        // transform_swaps_handedness
             vi.len() / 3, // n_triangles
             vertex_indices,
-            n_vertices,
-            p_ws, // in world space
-            s_ws, // in world space
-            n_ws, // in world space
+            p.len(),
+            &p,
+            &s,
+            &n,
             uvs,
-        ));
-        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state, bsdf_state);
-        for id in 0..mesh.n_triangles {
-            let triangle = Arc::new(Triangle::new(
-                mesh.object_to_world,
-                mesh.world_to_object,
-                mesh.reverse_orientation,
-                mesh.clone(),
-                id,
-            ));
-            shapes.push(triangle.clone());
+            face_indices,
+        );
+        for shape in mesh_shapes {
+            shapes.push(shape.clone());
             materials.push(mtl.clone());
         }
     } else if api_state.param_set.name == "plymesh" {
@@ -1201,7 +1626,17 @@ fn get_shapes_and_materials(
             panic!("No search directory for plymesh.");
         }
     } else if api_state.param_set.name == "heightfield" {
-        println!("TODO: CreateHeightfield");
+        let mtl: Option<Arc<Material + Send + Sync>> = create_material(&api_state, bsdf_state);
+        let heightfield_shapes: Vec<Arc<Shape + Send + Sync>> = create_heightfield(
+            &obj_to_world,
+            &world_to_obj,
+            api_state.graphics_state.reverse_orientation,
+            &api_state.param_set,
+        );
+        for shape in heightfield_shapes {
+            shapes.push(shape.clone());
+            materials.push(mtl.clone());
+        }
     } else if api_state.param_set.name == "loopsubdiv" {
         // CreateLoopSubdiv
         let n_levels: i32 = api_state
@@ -1540,7 +1975,7 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
         "Missing end to pbrtTransformBegin()"
     );
     // MakeFilter
-    let mut some_filter: Option<Arc<Filter + Sync + Send>> = None;
+    let some_filter: Option<Arc<Filter + Sync + Send>>;
     if api_state.render_options.filter_name == "box" {
         some_filter = Some(BoxFilter::create(&api_state.render_options.filter_params));
     } else if api_state.render_options.filter_name == "gaussian" {
@@ -1552,7 +1987,9 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
             &api_state.render_options.filter_params,
         ));
     } else if api_state.render_options.filter_name == "sinc" {
-        println!("TODO: CreateSincFilter");
+        some_filter = Some(LanczosSincFilter::create(
+            &api_state.render_options.filter_params,
+        ));
     } else if api_state.render_options.filter_name == "triangle" {
         some_filter = Some(TriangleFilter::create(
             &api_state.render_options.filter_params,
@@ -1565,10 +2002,13 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
     }
     // MakeFilm
     if api_state.render_options.film_name == "image" {
-        let filename: String = api_state
+        let mut filename: String = api_state
             .render_options
             .film_params
             .find_one_string("filename", String::new());
+        if filename == "" {
+            filename = String::from("pbrt.png");
+        }
         let xres: i32 = api_state
             .render_options
             .film_params
@@ -1583,7 +2023,6 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
             p_min: Point2f { x: 0.0, y: 0.0 },
             p_max: Point2f { x: 1.0, y: 1.0 },
         };
-        // TODO: const Float *cr = params.FindFloat("cropwindow", &cwi);
         let cr: Vec<Float> = api_state
             .render_options
             .film_params
@@ -1686,7 +2125,7 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
             }
             if let Some(camera) = some_camera {
                 // MakeSampler
-                let mut some_sampler: Option<Box<Sampler + Sync + Send>> = None;
+                let some_sampler: Option<Box<Sampler + Sync + Send>>;
                 if api_state.render_options.sampler_name == "lowdiscrepancy"
                     || api_state.render_options.sampler_name == "02sequence"
                 {
@@ -1702,7 +2141,17 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                     let sampler = Box::new(ZeroTwoSequenceSampler::new(nsamp as i64, sd as i64));
                     some_sampler = Some(sampler);
                 } else if api_state.render_options.sampler_name == "maxmindist" {
-                    println!("TODO: CreateMaxMinDistSampler");
+                    let nsamp: i32 = api_state
+                        .render_options
+                        .sampler_params
+                        .find_one_int("pixelsamples", 16);
+                    let sd: i32 = api_state
+                        .render_options
+                        .sampler_params
+                        .find_one_int("dimensions", 4);
+                    // TODO: if (PbrtOptions.quickRender) nsamp = 1;
+                    let sampler = Box::new(MaxMinDistSampler::new(nsamp as i64, sd as i64));
+                    some_sampler = Some(sampler);
                 } else if api_state.render_options.sampler_name == "halton" {
                     let nsamp: i32 = api_state
                         .render_options
@@ -1736,7 +2185,25 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                     let sampler = Box::new(RandomSampler::new(nsamp as i64));
                     some_sampler = Some(sampler);
                 } else if api_state.render_options.sampler_name == "stratified" {
-                    println!("TODO: CreateStratifiedSampler");
+                    let jitter: bool = api_state
+                        .render_options
+                        .sampler_params
+                        .find_one_bool("jitter", true);
+                    let xsamp: i32 = api_state
+                        .render_options
+                        .sampler_params
+                        .find_one_int("xsamples", 4);
+                    let ysamp: i32 = api_state
+                        .render_options
+                        .sampler_params
+                        .find_one_int("ysamples", 4);
+                    let sd: i32 = api_state
+                        .render_options
+                        .sampler_params
+                        .find_one_int("dimensions", 4);
+                    // TODO: if (PbrtOptions.quickRender) xsamp = ysamp = 1;
+                    let sampler = Box::new(StratifiedSampler::new(xsamp, ysamp, jitter, sd as i64));
+                    some_sampler = Some(sampler);
                 } else {
                     panic!(
                         "Sampler \"{}\" unknown.",
@@ -1751,8 +2218,19 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                     > = None;
                     let mut some_bdpt_integrator: Option<Box<BDPTIntegrator>> = None;
                     let mut some_mlt_integrator: Option<Box<MLTIntegrator>> = None;
+                    let mut some_sppm_integrator: Option<Box<SPPMIntegrator>> = None;
                     if api_state.render_options.integrator_name == "whitted" {
-                        println!("TODO: CreateWhittedIntegrator");
+                        // CreateWhittedIntegrator
+                        let max_depth: i32 = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_int("maxdepth", 5);
+                        let pixel_bounds: Bounds2i = make_pixel_bounds(
+                            &api_state.render_options.integrator_params,
+                            camera.get_film().get_sample_bounds(),
+                        );
+                        let integrator = Box::new(WhittedIntegrator::new(max_depth, pixel_bounds));
+                        some_integrator = Some(integrator);
                     } else if api_state.render_options.integrator_name == "directlighting" {
                         // CreateDirectLightingIntegrator
                         let max_depth: i32 = api_state
@@ -1771,11 +2249,10 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                         } else {
                             panic!("Strategy \"{}\" for direct lighting unknown.", st);
                         }
-                        // TODO: const int *pb = params.FindInt("pixelbounds", &np);
-                        let pixel_bounds: Bounds2i = Bounds2i {
-                            p_min: Point2i { x: 0, y: 0 },
-                            p_max: Point2i { x: xres, y: yres },
-                        };
+                        let pixel_bounds: Bounds2i = make_pixel_bounds(
+                            &api_state.render_options.integrator_params,
+                            camera.get_film().get_sample_bounds(),
+                        );
                         let integrator = Box::new(DirectLightingIntegrator::new(
                             strategy,
                             max_depth as i64,
@@ -1788,26 +2265,10 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                             .render_options
                             .integrator_params
                             .find_one_int("maxdepth", 5);
-                        let pb: Vec<i32> = api_state
-                            .render_options
-                            .integrator_params
-                            .find_int("pixelbounds");
-                        let np: usize = pb.len();
-                        let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
-                        if np > 0 as usize {
-                            if np != 4 as usize {
-                                panic!(
-                                    "Expected four values for \"pixelbounds\" parameter. Got {}.",
-                                    np
-                                );
-                            } else {
-                                println!("TODO: pixelBounds = Intersect(...)");
-                                // pixelBounds = Intersect(pixelBounds,
-                                //                         Bounds2i{{pb[0], pb[2]}, {pb[1], pb[3]}});
-                                // if (pixelBounds.Area() == 0)
-                                //     Error("Degenerate \"pixelbounds\" specified.");
-                            }
-                        }
+                        let pixel_bounds: Bounds2i = make_pixel_bounds(
+                            &api_state.render_options.integrator_params,
+                            camera.get_film().get_sample_bounds(),
+                        );
                         let rr_threshold: Float = api_state
                             .render_options
                             .integrator_params
@@ -1826,7 +2287,30 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                         ));
                         some_integrator = Some(integrator);
                     } else if api_state.render_options.integrator_name == "volpath" {
-                        println!("TODO: CreateVolPathIntegrator");
+                        // CreateVolPathIntegrator
+                        let max_depth: i32 = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_int("maxdepth", 5);
+                        let pixel_bounds: Bounds2i = make_pixel_bounds(
+                            &api_state.render_options.integrator_params,
+                            camera.get_film().get_sample_bounds(),
+                        );
+                        let rr_threshold: Float = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_float("rrthreshold", 1.0 as Float);
+                        let light_strategy: String = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_string("lightsamplestrategy", String::from("spatial"));
+                        let integrator = Box::new(VolPathIntegrator::new(
+                            max_depth as u32,
+                            pixel_bounds,
+                            rr_threshold,
+                            light_strategy,
+                        ));
+                        some_integrator = Some(integrator);
                     } else if api_state.render_options.integrator_name == "bdpt" {
                         // CreateBDPTIntegrator
                         let mut max_depth: i32 = api_state
@@ -1845,7 +2329,10 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                             println!("WARNING: visualizestrategies/visualizeweights was enabled, limiting maxdepth to 5");
                             max_depth = 5;
                         }
-                        let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
+                        let pixel_bounds: Bounds2i = make_pixel_bounds(
+                            &api_state.render_options.integrator_params,
+                            camera.get_film().get_sample_bounds(),
+                        );
                         let light_strategy: String = api_state
                             .render_options
                             .integrator_params
@@ -1896,26 +2383,10 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                         some_mlt_integrator = Some(integrator);
                     } else if api_state.render_options.integrator_name == "ambientocclusion" {
                         // CreateAOIntegrator
-                        let pb: Vec<i32> = api_state
-                            .render_options
-                            .integrator_params
-                            .find_int("pixelbounds");
-                        let np: usize = pb.len();
-                        let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
-                        if np > 0 as usize {
-                            if np != 4 as usize {
-                                panic!(
-                                    "Expected four values for \"pixelbounds\" parameter. Got {}.",
-                                    np
-                                );
-                            } else {
-                                println!("TODO: pixelBounds = Intersect(...)");
-                                // pixelBounds = Intersect(pixelBounds,
-                                //                         Bounds2i{{pb[0], pb[2]}, {pb[1], pb[3]}});
-                                // if (pixelBounds.Area() == 0)
-                                //     Error("Degenerate \"pixelbounds\" specified.");
-                            }
-                        }
+                        let pixel_bounds: Bounds2i = make_pixel_bounds(
+                            &api_state.render_options.integrator_params,
+                            camera.get_film().get_sample_bounds(),
+                        );
                         let cos_sample: bool = api_state
                             .render_options
                             .integrator_params
@@ -1931,7 +2402,42 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                             Box::new(AOIntegrator::new(cos_sample, n_samples, pixel_bounds));
                         some_integrator = Some(integrator);
                     } else if api_state.render_options.integrator_name == "sppm" {
-                        println!("TODO: CreateSPPMIntegrator");
+                        // CreateSPPMIntegrator
+                        // "iterations" is the legacy name, "numiterations" wins
+                        let mut n_iterations: i32 = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_int("iterations", 64);
+                        n_iterations = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_int("numiterations", n_iterations);
+                        let max_depth: i32 = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_int("maxdepth", 5);
+                        let photons_per_iter: i32 = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_int("photonsperiteration", -1);
+                        let write_freq: i32 = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_int("imagewritefrequency", i32::max_value());
+                        let radius: Float = api_state
+                            .render_options
+                            .integrator_params
+                            .find_one_float("radius", 1.0 as Float);
+                        // TODO: if (PbrtOptions.quickRender) nIterations = std::max(1, nIterations / 16);
+                        let integrator = Box::new(SPPMIntegrator::new(
+                            camera.clone(),
+                            n_iterations,
+                            photons_per_iter,
+                            max_depth as u32,
+                            radius,
+                            write_freq,
+                        ));
+                        some_sppm_integrator = Some(integrator);
                     } else {
                         panic!(
                             "Integrator \"{}\" unknown.",
@@ -1956,75 +2462,18 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                             println!("WARNING: No light sources defined in scene; rendering a black image.",);
                         }
                         // MakeAccelerator
-                        if api_state.render_options.accelerator_name == "bvh" {
-                            //  CreateBVHAccelerator
-                            let split_method_name: String = api_state
-                                .render_options
-                                .accelerator_params
-                                .find_one_string("splitmethod", String::from("sah"));
-                            let split_method;
-                            if split_method_name == "sah" {
-                                split_method = SplitMethod::SAH;
-                            } else if split_method_name == "hlbvh" {
-                                split_method = SplitMethod::HLBVH;
-                            } else if split_method_name == "middle" {
-                                split_method = SplitMethod::Middle;
-                            } else if split_method_name == "equal" {
-                                split_method = SplitMethod::EqualCounts;
-                            } else {
-                                println!(
-                                    "WARNING: BVH split method \"{}\" unknown.  Using \"sah\".",
-                                    split_method_name
-                                );
-                                split_method = SplitMethod::SAH;
-                            }
-                            let max_prims_in_node: i32 = api_state
-                                .render_options
-                                .accelerator_params
-                                .find_one_int("maxnodeprims", 4);
-                            let accelerator = Arc::new(BVHAccel::new(
-                                api_state.render_options.primitives.clone(),
-                                max_prims_in_node as usize,
-                                split_method,
-                            ));
-                            // MakeScene
-                            let scene: Scene = Scene::new(
-                                accelerator.clone(),
-                                api_state.render_options.lights.clone(),
-                            );
-                            // TODO: primitives.erase(primitives.begin(), primitives.end());
-                            // TODO: lights.erase(lights.begin(), lights.end());
-                            let num_threads: u8 = api_state.number_of_threads;
-                            render(
-                                &scene,
-                                &camera.clone(),
-                                &mut sampler,
-                                &mut integrator,
-                                num_threads,
-                            );
-                        } else if api_state.render_options.accelerator_name == "kdtree" {
-                            // println!("TODO: CreateKdTreeAccelerator");
-                            // WARNING: Use BVHAccel for now !!!
-                            let accelerator = Arc::new(BVHAccel::new(
-                                api_state.render_options.primitives.clone(),
-                                4,
-                                SplitMethod::SAH,
-                            ));
-                            // MakeScene
-                            let scene: Scene = Scene::new(
-                                accelerator.clone(),
-                                api_state.render_options.lights.clone(),
-                            );
-                            // TODO: primitives.erase(primitives.begin(), primitives.end());
-                            // TODO: lights.erase(lights.begin(), lights.end());
-                            let num_threads: u8 = api_state.number_of_threads;
-                            render(&scene, &camera, &mut sampler, &mut integrator, num_threads);
-                        } else {
-                            panic!(
-                                "Accelerator \"{}\" unknown.",
-                                api_state.render_options.accelerator_name
-                            );
-                        }
+                        let accelerator: Arc<Primitive + Sync + Send> = make_accelerator(
+                            &api_state.render_options.accelerator_name,
+                            api_state.render_options.primitives.clone(),
+                            &api_state.render_options.accelerator_params,
+                        );
+                        // MakeScene
+                        let scene: Scene =
+                            Scene::new(accelerator, api_state.render_options.lights.clone());
+                        // TODO: primitives.erase(primitives.begin(), primitives.end());
+                        // TODO: lights.erase(lights.begin(), lights.end());
+                        let num_threads: u8 = api_state.number_of_threads;
+                        render(&scene, &camera, &mut sampler, &mut integrator, num_threads);
                     } else if let Some(mut integrator) = some_bdpt_integrator {
                         // because we can't call
                         // integrator.render() yet,
@@ -2039,81 +2488,18 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                             println!("WARNING: No light sources defined in scene; rendering a black image.",);
                         }
                         // MakeAccelerator
-                        if api_state.render_options.accelerator_name == "bvh" {
-                            //  CreateBVHAccelerator
-                            let split_method_name: String = api_state
-                                .render_options
-                                .accelerator_params
-                                .find_one_string("splitmethod", String::from("sah"));
-                            let split_method;
-                            if split_method_name == "sah" {
-                                split_method = SplitMethod::SAH;
-                            } else if split_method_name == "hlbvh" {
-                                split_method = SplitMethod::HLBVH;
-                            } else if split_method_name == "middle" {
-                                split_method = SplitMethod::Middle;
-                            } else if split_method_name == "equal" {
-                                split_method = SplitMethod::EqualCounts;
-                            } else {
-                                println!(
-                                    "WARNING: BVH split method \"{}\" unknown.  Using \"sah\".",
-                                    split_method_name
-                                );
-                                split_method = SplitMethod::SAH;
-                            }
-                            let max_prims_in_node: i32 = api_state
-                                .render_options
-                                .accelerator_params
-                                .find_one_int("maxnodeprims", 4);
-                            let accelerator = Arc::new(BVHAccel::new(
-                                api_state.render_options.primitives.clone(),
-                                max_prims_in_node as usize,
-                                split_method,
-                            ));
-                            // MakeScene
-                            let scene: Scene = Scene::new(
-                                accelerator.clone(),
-                                api_state.render_options.lights.clone(),
-                            );
-                            // TODO: primitives.erase(primitives.begin(), primitives.end());
-                            // TODO: lights.erase(lights.begin(), lights.end());
-                            let num_threads: u8 = api_state.number_of_threads;
-                            render_bdpt(
-                                &scene,
-                                &camera,
-                                &mut sampler,
-                                &mut integrator,
-                                num_threads,
-                            );
-                        } else if api_state.render_options.accelerator_name == "kdtree" {
-                            // println!("TODO: CreateKdTreeAccelerator");
-                            // WARNING: Use BVHAccel for now !!!
-                            let accelerator = Arc::new(BVHAccel::new(
-                                api_state.render_options.primitives.clone(),
-                                4,
-                                SplitMethod::SAH,
-                            ));
-                            // MakeScene
-                            let scene: Scene = Scene::new(
-                                accelerator.clone(),
-                                api_state.render_options.lights.clone(),
-                            );
-                            // TODO: primitives.erase(primitives.begin(), primitives.end());
-                            // TODO: lights.erase(lights.begin(), lights.end());
-                            let num_threads: u8 = api_state.number_of_threads;
-                            render_bdpt(
-                                &scene,
-                                &camera,
-                                &mut sampler,
-                                &mut integrator,
-                                num_threads,
-                            );
-                        } else {
-                            panic!(
-                                "Accelerator \"{}\" unknown.",
-                                api_state.render_options.accelerator_name
-                            );
-                        }
+                        let accelerator: Arc<Primitive + Sync + Send> = make_accelerator(
+                            &api_state.render_options.accelerator_name,
+                            api_state.render_options.primitives.clone(),
+                            &api_state.render_options.accelerator_params,
+                        );
+                        // MakeScene
+                        let scene: Scene =
+                            Scene::new(accelerator, api_state.render_options.lights.clone());
+                        // TODO: primitives.erase(primitives.begin(), primitives.end());
+                        // TODO: lights.erase(lights.begin(), lights.end());
+                        let num_threads: u8 = api_state.number_of_threads;
+                        render_bdpt(&scene, &camera, &mut sampler, &mut integrator, num_threads);
                     } else if let Some(mut integrator) = some_mlt_integrator {
                         // because we can't call
                         // integrator.render() yet,
@@ -2128,69 +2514,44 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
                             println!("WARNING: No light sources defined in scene; rendering a black image.",);
                         }
                         // MakeAccelerator
-                        if api_state.render_options.accelerator_name == "bvh" {
-                            //  CreateBVHAccelerator
-                            let split_method_name: String = api_state
-                                .render_options
-                                .accelerator_params
-                                .find_one_string("splitmethod", String::from("sah"));
-                            let split_method;
-                            if split_method_name == "sah" {
-                                split_method = SplitMethod::SAH;
-                            } else if split_method_name == "hlbvh" {
-                                split_method = SplitMethod::HLBVH;
-                            } else if split_method_name == "middle" {
-                                split_method = SplitMethod::Middle;
-                            } else if split_method_name == "equal" {
-                                split_method = SplitMethod::EqualCounts;
-                            } else {
-                                println!(
-                                    "WARNING: BVH split method \"{}\" unknown.  Using \"sah\".",
-                                    split_method_name
-                                );
-                                split_method = SplitMethod::SAH;
-                            }
-                            let max_prims_in_node: i32 = api_state
-                                .render_options
-                                .accelerator_params
-                                .find_one_int("maxnodeprims", 4);
-                            let accelerator = Arc::new(BVHAccel::new(
-                                api_state.render_options.primitives.clone(),
-                                max_prims_in_node as usize,
-                                split_method,
-                            ));
-                            // MakeScene
-                            let scene: Scene = Scene::new(
-                                accelerator.clone(),
-                                api_state.render_options.lights.clone(),
-                            );
-                            // TODO: primitives.erase(primitives.begin(), primitives.end());
-                            // TODO: lights.erase(lights.begin(), lights.end());
-                            let num_threads: u8 = api_state.number_of_threads;
-                            render_mlt(&scene, &camera, &mut sampler, &mut integrator, num_threads);
-                        } else if api_state.render_options.accelerator_name == "kdtree" {
-                            // println!("TODO: CreateKdTreeAccelerator");
-                            // WARNING: Use BVHAccel for now !!!
-                            let accelerator = Arc::new(BVHAccel::new(
-                                api_state.render_options.primitives.clone(),
-                                4,
-                                SplitMethod::SAH,
-                            ));
-                            // MakeScene
-                            let scene: Scene = Scene::new(
-                                accelerator.clone(),
-                                api_state.render_options.lights.clone(),
-                            );
-                            // TODO: primitives.erase(primitives.begin(), primitives.end());
-                            // TODO: lights.erase(lights.begin(), lights.end());
-                            let num_threads: u8 = api_state.number_of_threads;
-                            render_mlt(&scene, &camera, &mut sampler, &mut integrator, num_threads);
-                        } else {
-                            panic!(
-                                "Accelerator \"{}\" unknown.",
-                                api_state.render_options.accelerator_name
-                            );
+                        let accelerator: Arc<Primitive + Sync + Send> = make_accelerator(
+                            &api_state.render_options.accelerator_name,
+                            api_state.render_options.primitives.clone(),
+                            &api_state.render_options.accelerator_params,
+                        );
+                        // MakeScene
+                        let scene: Scene =
+                            Scene::new(accelerator, api_state.render_options.lights.clone());
+                        // TODO: primitives.erase(primitives.begin(), primitives.end());
+                        // TODO: lights.erase(lights.begin(), lights.end());
+                        let num_threads: u8 = api_state.number_of_threads;
+                        render_mlt(&scene, &camera, &mut sampler, &mut integrator, num_threads);
+                    } else if let Some(mut integrator) = some_sppm_integrator {
+                        // because we can't call
+                        // integrator.render() yet,
+                        // let us repeat some code and
+                        // call render_sppm(...)
+                        // instead:
+
+                        // MakeIntegrator
+                        // TODO: if (renderOptions->haveScatteringMedia && ...)
+                        if api_state.render_options.lights.is_empty() {
+                            // warn if no light sources are defined
+                            println!("WARNING: No light sources defined in scene; rendering a black image.",);
                         }
+                        // MakeAccelerator
+                        let accelerator: Arc<Primitive + Sync + Send> = make_accelerator(
+                            &api_state.render_options.accelerator_name,
+                            api_state.render_options.primitives.clone(),
+                            &api_state.render_options.accelerator_params,
+                        );
+                        // MakeScene
+                        let scene: Scene =
+                            Scene::new(accelerator, api_state.render_options.lights.clone());
+                        // TODO: primitives.erase(primitives.begin(), primitives.end());
+                        // TODO: lights.erase(lights.begin(), lights.end());
+                        let num_threads: u8 = api_state.number_of_threads;
+                        render_sppm(&scene, &camera, &mut sampler, &mut integrator, num_threads);
                     } else {
                         panic!("Unable to create integrator.");
                     }
@@ -2831,52 +3192,13 @@ pub fn pbrt_object_instance(api_state: &mut ApiState, params: ParamSet) {
         // TODO: ++nObjectInstancesUsed;
         if instance_vec.len() > 1_usize {
             // create aggregate for instance _Primitive_s
-            if api_state.render_options.accelerator_name == "bvh" {
-                //  CreateBVHAccelerator
-                let split_method_name: String = api_state
-                    .render_options
-                    .accelerator_params
-                    .find_one_string("splitmethod", String::from("sah"));
-                let split_method;
-                if split_method_name == "sah" {
-                    split_method = SplitMethod::SAH;
-                } else if split_method_name == "hlbvh" {
-                    split_method = SplitMethod::HLBVH;
-                } else if split_method_name == "middle" {
-                    split_method = SplitMethod::Middle;
-                } else if split_method_name == "equal" {
-                    split_method = SplitMethod::EqualCounts;
-                } else {
-                    println!(
-                        "WARNING: BVH split method \"{}\" unknown.  Using \"sah\".",
-                        split_method_name
-                    );
-                    split_method = SplitMethod::SAH;
-                }
-                let max_prims_in_node: i32 = api_state
-                    .render_options
-                    .accelerator_params
-                    .find_one_int("maxnodeprims", 4);
-                let accelerator: Arc<Primitive + Sync + Send> = Arc::new(BVHAccel::new(
-                    instance_vec.clone(),
-                    max_prims_in_node as usize,
-                    split_method,
-                ));
-                instance_vec.clear();
-                instance_vec.push(accelerator);
-            } else if api_state.render_options.accelerator_name == "kdtree" {
-                // println!("TODO: CreateKdTreeAccelerator");
-                // WARNING: Use BVHAccel for now !!!
-                let accelerator: Arc<Primitive + Sync + Send> =
-                    Arc::new(BVHAccel::new(instance_vec.clone(), 4, SplitMethod::SAH));
-                instance_vec.clear();
-                instance_vec.push(accelerator);
-            } else {
-                panic!(
-                    "Accelerator \"{}\" unknown.",
-                    api_state.render_options.accelerator_name
-                );
-            }
+            let accelerator: Arc<Primitive + Sync + Send> = make_accelerator(
+                &api_state.render_options.accelerator_name,
+                instance_vec.clone(),
+                &api_state.render_options.accelerator_params,
+            );
+            instance_vec.clear();
+            instance_vec.push(accelerator);
         }
         // create _animatedInstanceToWorld_ transform for instance
         let animated_instance_to_world: AnimatedTransform = AnimatedTransform::new(
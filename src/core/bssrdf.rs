@@ -0,0 +1,626 @@
+//! The bidirectional scattering surface reflectance distribution
+//! function (BSSRDF) describes subsurface light transport: light
+//! that enters a translucent surface at one point may leave it at
+//! another one.
+
+// std
+use std::f32::consts::PI;
+use std::sync::Arc;
+// others
+use rayon::prelude::*;
+// pbrt
+use core::geometry::{nrm_cross_vec3, pnt3_distance, vec3_dot_nrm, vec3_dot_vec3};
+use core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
+use core::interaction::{InteractionCommon, SurfaceInteraction};
+use core::interpolation::{
+    catmull_rom_weights, integrate_catmull_rom, invert_catmull_rom, sample_catmull_rom_2d,
+};
+use core::material::{Material, TransportMode};
+use core::medium::phase_hg;
+use core::pbrt::clamp_t;
+use core::pbrt::INV_4_PI;
+use core::pbrt::{Float, Spectrum};
+use core::reflection::{cos_theta, fr_dielectric};
+use core::reflection::{Bsdf, Bxdf, BxdfType};
+use core::scene::Scene;
+
+// see bssrdf.h
+
+pub trait Bssrdf {
+    /// Evaluates the BSSRDF for light arriving at *pi* from
+    /// direction *wi* and leaving at the point the BSSRDF was
+    /// created for.
+    fn s(&self, pi: &SurfaceInteraction, wi: &Vector3f) -> Spectrum;
+    /// Samples an incident point *pi* on the surface (returned
+    /// together with the BSSRDF value) by tracing probe rays against
+    /// the scene geometry.
+    fn sample_s<'a>(
+        &self,
+        scene: &'a Scene,
+        u1: Float,
+        u2: &Point2f,
+        pdf: &mut Float,
+    ) -> (Spectrum, Option<SurfaceInteraction<'a, 'a>>);
+}
+
+/// Stores a tabulated radial scattering profile (as a function of
+/// albedo and optical radius).
+pub struct BssrdfTable {
+    pub n_rho_samples: i32,
+    pub n_radius_samples: i32,
+    pub rho_samples: Vec<Float>,
+    pub radius_samples: Vec<Float>,
+    pub profile: Vec<Float>,
+    pub rho_eff: Vec<Float>,
+    pub profile_cdf: Vec<Float>,
+}
+
+impl BssrdfTable {
+    pub fn new(n_rho_samples: i32, n_radius_samples: i32) -> Self {
+        let n: usize = (n_radius_samples * n_rho_samples) as usize;
+        BssrdfTable {
+            n_rho_samples: n_rho_samples,
+            n_radius_samples: n_radius_samples,
+            rho_samples: vec![0.0 as Float; n_rho_samples as usize],
+            radius_samples: vec![0.0 as Float; n_radius_samples as usize],
+            profile: vec![0.0 as Float; n],
+            rho_eff: vec![0.0 as Float; n_rho_samples as usize],
+            profile_cdf: vec![0.0 as Float; n],
+        }
+    }
+    pub fn eval_profile(&self, rho_index: i32, radius_index: i32) -> Float {
+        self.profile[(rho_index * self.n_radius_samples + radius_index) as usize]
+    }
+}
+
+/// A separable BSSRDF whose radial profile **Sr** is interpolated
+/// from a **BssrdfTable**.
+pub struct TabulatedBssrdf {
+    // SeparableBSSRDF
+    pub po: InteractionCommon,
+    pub eta: Float,
+    pub ns: Normal3f,
+    pub ss: Vector3f,
+    pub ts: Vector3f,
+    pub material: Arc<Material + Send + Sync>,
+    pub mode: TransportMode,
+    // TabulatedBSSRDF
+    pub table: Arc<BssrdfTable>,
+    pub sigma_t: Spectrum,
+    pub rho: Spectrum,
+}
+
+impl TabulatedBssrdf {
+    pub fn new(
+        po: &SurfaceInteraction,
+        material: Arc<Material + Send + Sync>,
+        mode: TransportMode,
+        eta: Float,
+        sigma_a: &Spectrum,
+        sigma_s: &Spectrum,
+        table: Arc<BssrdfTable>,
+    ) -> Self {
+        let sigma_t: Spectrum = *sigma_a + *sigma_s;
+        let mut rho: Spectrum = Spectrum::default();
+        for c in 0..3 {
+            // TODO: Spectrum::nSamples
+            if sigma_t[c] != 0.0 as Float {
+                rho[c] = sigma_s[c] / sigma_t[c];
+            } else {
+                rho[c] = 0.0 as Float;
+            }
+        }
+        let ns: Normal3f = po.shading.n;
+        let ss: Vector3f = po.shading.dpdu.normalize();
+        TabulatedBssrdf {
+            po: InteractionCommon {
+                p: po.p,
+                time: po.time,
+                p_error: po.p_error,
+                wo: po.wo,
+                n: po.n,
+                medium_interface: po.medium_interface.clone(),
+            },
+            eta: eta,
+            ns: ns,
+            ss: ss,
+            ts: nrm_cross_vec3(&ns, &ss),
+            material: material,
+            mode: mode,
+            table: table,
+            sigma_t: sigma_t,
+            rho: rho,
+        }
+    }
+    pub fn sw(&self, w: &Vector3f) -> Spectrum {
+        let c: Float = 1.0 as Float - 2.0 as Float * fresnel_moment1(1.0 as Float / self.eta);
+        Spectrum::new(
+            (1.0 as Float - fr_dielectric(cos_theta(w), 1.0 as Float, self.eta)) / (c * PI),
+        )
+    }
+    pub fn sp(&self, pi: &SurfaceInteraction) -> Spectrum {
+        self.sr(pnt3_distance(&self.po.p, &pi.p))
+    }
+    pub fn sample_sp<'a>(
+        &self,
+        scene: &'a Scene,
+        u1: Float,
+        u2: &Point2f,
+        pdf: &mut Float,
+    ) -> (Spectrum, Option<SurfaceInteraction<'a, 'a>>) {
+        // TODO: ProfilePhase pp(Prof::BSSRDFEvaluation);
+        let mut u1: Float = u1;
+        // choose projection axis for BSSRDF sampling
+        let vx: Vector3f;
+        let vy: Vector3f;
+        let vz: Vector3f;
+        if u1 < 0.5 as Float {
+            vx = self.ss;
+            vy = self.ts;
+            vz = Vector3f::from(self.ns);
+            u1 *= 2.0 as Float;
+        } else if u1 < 0.75 as Float {
+            // prepare for sampling rays with respect to _self.ss_
+            vx = self.ts;
+            vy = Vector3f::from(self.ns);
+            vz = self.ss;
+            u1 = (u1 - 0.5 as Float) * 4.0 as Float;
+        } else {
+            // prepare for sampling rays with respect to _self.ts_
+            vx = Vector3f::from(self.ns);
+            vy = self.ss;
+            vz = self.ts;
+            u1 = (u1 - 0.75 as Float) * 4.0 as Float;
+        }
+        // choose spectral channel for BSSRDF sampling
+        let n_samples: i32 = 3; // TODO: Spectrum::nSamples
+        let ch: i32 = clamp_t((u1 * n_samples as Float) as i32, 0, n_samples - 1);
+        u1 = u1 * n_samples as Float - ch as Float;
+        // sample BSSRDF profile in polar coordinates
+        let r: Float = self.sample_sr(ch as usize, u2[0]);
+        if r < 0.0 as Float {
+            return (Spectrum::default(), None);
+        }
+        let phi: Float = 2.0 as Float * PI * u2[1];
+        // compute BSSRDF profile bounds and intersection height
+        let r_max: Float = self.sample_sr(ch as usize, 0.999 as Float);
+        if r >= r_max {
+            return (Spectrum::default(), None);
+        }
+        let l: Float = 2.0 as Float * (r_max * r_max - r * r).sqrt();
+        // compute BSSRDF sampling ray segment
+        let mut base: InteractionCommon = InteractionCommon::default();
+        base.p = self.po.p + (vx * phi.cos() + vy * phi.sin()) * r - vz * (l * 0.5 as Float);
+        base.time = self.po.time;
+        let p_target: Point3f = base.p + vz * l;
+        // intersect BSSRDF sampling ray against the scene geometry
+
+        // accumulate chain of intersections along ray
+        let mut chain: Vec<SurfaceInteraction> = Vec::new();
+        loop {
+            let mut r: Ray = base.spawn_ray_to_pnt(&p_target);
+            if r.d == Vector3f::default() {
+                break;
+            }
+            if let Some(si) = scene.intersect(&mut r) {
+                base = InteractionCommon {
+                    p: si.p,
+                    time: si.time,
+                    p_error: si.p_error,
+                    wo: si.wo,
+                    n: si.n,
+                    medium_interface: si.medium_interface.clone(),
+                };
+                // append admissible intersection to _chain_
+                let mut admissible: bool = false;
+                if let Some(primitive) = si.primitive {
+                    if let Some(material) = primitive.material.as_ref() {
+                        admissible = Arc::ptr_eq(material, &self.material);
+                    }
+                }
+                if admissible {
+                    chain.push(si);
+                }
+            } else {
+                break;
+            }
+        }
+        // randomly choose one of several intersections during BSSRDF sampling
+        let n_found: usize = chain.len();
+        if n_found == 0 {
+            return (Spectrum::default(), None);
+        }
+        let selected: usize =
+            clamp_t((u1 * n_found as Float) as i32, 0, n_found as i32 - 1) as usize;
+        let pi: SurfaceInteraction = chain.swap_remove(selected);
+        // compute sample PDF and return the spatial BSSRDF term $\Sp$
+        *pdf = self.pdf_sp(&pi) / n_found as Float;
+        (self.sp(&pi), Some(pi))
+    }
+    pub fn pdf_sp(&self, pi: &SurfaceInteraction) -> Float {
+        // express $\pti-\pto$ and $\bold{n}_i$ with respect to local coordinates at $\pto$
+        let d: Vector3f = self.po.p - pi.p;
+        let d_local: Vector3f = Vector3f {
+            x: vec3_dot_vec3(&self.ss, &d),
+            y: vec3_dot_vec3(&self.ts, &d),
+            z: vec3_dot_nrm(&d, &self.ns),
+        };
+        let n_local: Normal3f = Normal3f {
+            x: vec3_dot_nrm(&self.ss, &pi.n),
+            y: vec3_dot_nrm(&self.ts, &pi.n),
+            z: vec3_dot_nrm(&Vector3f::from(self.ns), &pi.n),
+        };
+        // compute BSSRDF profile radius under projection along each axis
+        let r_proj: [Float; 3] = [
+            (d_local.y * d_local.y + d_local.z * d_local.z).sqrt(),
+            (d_local.z * d_local.z + d_local.x * d_local.x).sqrt(),
+            (d_local.x * d_local.x + d_local.y * d_local.y).sqrt(),
+        ];
+        let n_local_abs: [Float; 3] = [n_local.x.abs(), n_local.y.abs(), n_local.z.abs()];
+        // return combined probability from all BSSRDF sampling strategies
+        let mut pdf: Float = 0.0 as Float;
+        let axis_prob: [Float; 3] = [0.25 as Float, 0.25 as Float, 0.5 as Float];
+        let n_samples: usize = 3; // TODO: Spectrum::nSamples
+        let ch_prob: Float = 1.0 as Float / n_samples as Float;
+        for axis in 0..3 {
+            for ch in 0..n_samples {
+                pdf += self.pdf_sr(ch, r_proj[axis]) * n_local_abs[axis] * ch_prob
+                    * axis_prob[axis];
+            }
+        }
+        pdf
+    }
+    pub fn sr(&self, r: Float) -> Spectrum {
+        let mut sr: Spectrum = Spectrum::default();
+        for ch in 0..3 {
+            // TODO: Spectrum::nSamples
+            // convert $r$ into unitless optical radius $r_{\roman{optical}}$
+            let r_optical: Float = r * self.sigma_t[ch];
+            // compute spline weights to interpolate BSSRDF at _r_
+            let mut rho_offset: i32 = 0;
+            let mut radius_offset: i32 = 0;
+            let mut rho_weights: [Float; 4] = [0.0 as Float; 4];
+            let mut radius_weights: [Float; 4] = [0.0 as Float; 4];
+            if !catmull_rom_weights(
+                &self.table.rho_samples,
+                self.rho[ch],
+                &mut rho_offset,
+                &mut rho_weights,
+            ) || !catmull_rom_weights(
+                &self.table.radius_samples,
+                r_optical,
+                &mut radius_offset,
+                &mut radius_weights,
+            ) {
+                continue;
+            }
+            // set BSSRDF value _Sr[ch]_ using tensor spline interpolation
+            let mut srf: Float = 0.0 as Float;
+            for i in 0..4 {
+                for j in 0..4 {
+                    let weight: Float = rho_weights[i] * radius_weights[j];
+                    if weight != 0.0 as Float {
+                        srf += weight
+                            * self
+                                .table
+                                .eval_profile(rho_offset + i as i32, radius_offset + j as i32);
+                    }
+                }
+            }
+            // cancel marginal PDF factor from tabulated BSSRDF profile
+            if r_optical != 0.0 as Float {
+                srf /= 2.0 as Float * PI * r_optical;
+            }
+            sr[ch] = srf;
+        }
+        // transform BSSRDF value into world space units
+        sr *= self.sigma_t * self.sigma_t;
+        sr.clamp(0.0 as Float, std::f32::INFINITY as Float)
+    }
+    pub fn sample_sr(&self, ch: usize, u: Float) -> Float {
+        if self.sigma_t[ch] == 0.0 as Float {
+            return -1.0 as Float;
+        }
+        sample_catmull_rom_2d(
+            &self.table.rho_samples,
+            &self.table.radius_samples,
+            &self.table.profile,
+            &self.table.profile_cdf,
+            self.rho[ch],
+            u,
+            None,
+            None,
+        ) / self.sigma_t[ch]
+    }
+    pub fn pdf_sr(&self, ch: usize, r: Float) -> Float {
+        // convert $r$ into unitless optical radius $r_{\roman{optical}}$
+        let r_optical: Float = r * self.sigma_t[ch];
+        // compute spline weights to interpolate BSSRDF density at _r_
+        let mut rho_offset: i32 = 0;
+        let mut radius_offset: i32 = 0;
+        let mut rho_weights: [Float; 4] = [0.0 as Float; 4];
+        let mut radius_weights: [Float; 4] = [0.0 as Float; 4];
+        if !catmull_rom_weights(
+            &self.table.rho_samples,
+            self.rho[ch],
+            &mut rho_offset,
+            &mut rho_weights,
+        ) || !catmull_rom_weights(
+            &self.table.radius_samples,
+            r_optical,
+            &mut radius_offset,
+            &mut radius_weights,
+        ) {
+            return 0.0 as Float;
+        }
+        // return BSSRDF profile density for channel _ch_
+        let mut sr: Float = 0.0 as Float;
+        let mut rho_eff: Float = 0.0 as Float;
+        for i in 0..4 {
+            if rho_weights[i] == 0.0 as Float {
+                continue;
+            }
+            rho_eff += self.table.rho_eff[(rho_offset + i as i32) as usize] * rho_weights[i];
+            for j in 0..4 {
+                if radius_weights[j] == 0.0 as Float {
+                    continue;
+                }
+                sr += self
+                    .table
+                    .eval_profile(rho_offset + i as i32, radius_offset + j as i32)
+                    * rho_weights[i]
+                    * radius_weights[j];
+            }
+        }
+        // cancel marginal PDF factor from tabulated BSSRDF profile
+        if r_optical != 0.0 as Float {
+            sr /= 2.0 as Float * PI * r_optical;
+        }
+        (0.0 as Float).max(sr * self.sigma_t[ch] * self.sigma_t[ch] / rho_eff)
+    }
+}
+
+impl Bssrdf for TabulatedBssrdf {
+    fn s(&self, pi: &SurfaceInteraction, wi: &Vector3f) -> Spectrum {
+        // TODO: ProfilePhase pp(Prof::BSSRDFEvaluation);
+        let ft: Float = fr_dielectric(cos_theta(&self.po.wo), 1.0 as Float, self.eta);
+        self.sp(pi) * self.sw(wi) * (1.0 as Float - ft)
+    }
+    fn sample_s<'a>(
+        &self,
+        scene: &'a Scene,
+        u1: Float,
+        u2: &Point2f,
+        pdf: &mut Float,
+    ) -> (Spectrum, Option<SurfaceInteraction<'a, 'a>>) {
+        // TODO: ProfilePhase pp(Prof::BSSRDFSampling);
+        let (sp, si_opt) = self.sample_sp(scene, u1, u2, pdf);
+        if let Some(mut si) = si_opt {
+            if !sp.is_black() {
+                // initialize material model at sampled surface interaction
+                let mut bxdfs: Vec<Arc<Bxdf + Send + Sync>> = Vec::new();
+                bxdfs.push(Arc::new(SeparableBssrdfAdapter::new(self.eta, self.mode)));
+                si.bsdf = Some(Arc::new(Bsdf::new(&si, 1.0 as Float, bxdfs)));
+                si.wo = Vector3f::from(si.shading.n);
+            }
+            (sp, Some(si))
+        } else {
+            (sp, None)
+        }
+    }
+}
+
+/// Makes the directional term **Sw** of a separable BSSRDF available
+/// as a BxDF at the sampled exit point.
+pub struct SeparableBssrdfAdapter {
+    pub eta: Float,
+    pub mode: TransportMode,
+}
+
+impl SeparableBssrdfAdapter {
+    pub fn new(eta: Float, mode: TransportMode) -> Self {
+        SeparableBssrdfAdapter {
+            eta: eta,
+            mode: mode,
+        }
+    }
+}
+
+impl Bxdf for SeparableBssrdfAdapter {
+    fn f(&self, _wo: &Vector3f, wi: &Vector3f) -> Spectrum {
+        // see SeparableBSSRDF::Sw()
+        let c: Float = 1.0 as Float - 2.0 as Float * fresnel_moment1(1.0 as Float / self.eta);
+        let mut f: Spectrum = Spectrum::new(
+            (1.0 as Float - fr_dielectric(cos_theta(wi), 1.0 as Float, self.eta)) / (c * PI),
+        );
+        // update BSSRDF transmission term to account for adjoint light transport
+        if self.mode == TransportMode::Radiance {
+            f *= Spectrum::new(self.eta * self.eta);
+        }
+        f
+    }
+    fn get_type(&self) -> u8 {
+        BxdfType::BsdfReflection as u8 | BxdfType::BsdfDiffuse as u8
+    }
+}
+
+// see bssrdf.cpp
+
+pub fn fresnel_moment1(eta: Float) -> Float {
+    let eta2: Float = eta * eta;
+    let eta3: Float = eta2 * eta;
+    let eta4: Float = eta3 * eta;
+    let eta5: Float = eta4 * eta;
+    if eta < 1.0 as Float {
+        0.45966 as Float - 1.73965 as Float * eta + 3.37668 as Float * eta2
+            - 3.904945 as Float * eta3
+            + 2.49277 as Float * eta4
+            - 0.68441 as Float * eta5
+    } else {
+        -4.61686 as Float + 11.1136 as Float * eta - 10.4646 as Float * eta2
+            + 5.11455 as Float * eta3
+            - 1.27198 as Float * eta4
+            + 0.12746 as Float * eta5
+    }
+}
+
+pub fn fresnel_moment2(eta: Float) -> Float {
+    let eta2: Float = eta * eta;
+    let eta3: Float = eta2 * eta;
+    let eta4: Float = eta3 * eta;
+    let eta5: Float = eta4 * eta;
+    if eta < 1.0 as Float {
+        0.27614 as Float - 0.87350 as Float * eta + 1.12077 as Float * eta2
+            - 0.65095 as Float * eta3
+            + 0.07883 as Float * eta4
+            + 0.04860 as Float * eta5
+    } else {
+        let r_eta: Float = 1.0 as Float / eta;
+        let r_eta2: Float = r_eta * r_eta;
+        let r_eta3: Float = r_eta2 * r_eta;
+        -547.033 as Float + 45.3087 as Float * r_eta3 - 218.725 as Float * r_eta2
+            + 458.843 as Float * r_eta
+            + 404.557 as Float * eta
+            - 189.519 as Float * eta2
+            + 54.9327 as Float * eta3
+            - 9.00603 as Float * eta4
+            + 0.63942 as Float * eta5
+    }
+}
+
+/// Multiple scattering term of the photon beam diffusion (PBD)
+/// profile.
+pub fn beam_diffusion_ms(sigma_s: Float, sigma_a: Float, g: Float, eta: Float, r: Float) -> Float {
+    let n_samples: i32 = 100;
+    let mut ed: Float = 0.0 as Float;
+    // precompute information for dipole integrand
+
+    // compute reduced scattering coefficients $\sigmaps, \sigmapt$
+    // and albedo $\rhop$
+    let sigmap_s: Float = sigma_s * (1.0 as Float - g);
+    let sigmap_t: Float = sigma_a + sigmap_s;
+    let rhop: Float = sigmap_s / sigmap_t;
+    // compute non-classical diffusion coefficient $D_\roman{G}$ using
+    // Equation (15.24)
+    let d_g: Float = (2.0 as Float * sigma_a + sigmap_s) / (3.0 as Float * sigmap_t * sigmap_t);
+    // compute effective transport coefficient $\sigmatr$ based on $D_\roman{G}$
+    let sigma_tr: Float = (sigma_a / d_g).sqrt();
+    // determine linear extrapolation distance $\depthextrapolation$
+    // using Equation (15.28)
+    let fm1: Float = fresnel_moment1(eta);
+    let fm2: Float = fresnel_moment2(eta);
+    let ze: Float = -2.0 as Float * d_g * (1.0 as Float + 3.0 as Float * fm2)
+        / (1.0 as Float - 2.0 as Float * fm1);
+    // determine exitance scale factors using Equations (15.31) and (15.32)
+    let c_phi: Float = 0.25 as Float * (1.0 as Float - 2.0 as Float * fm1);
+    let c_e: Float = 0.5 as Float * (1.0 as Float - 3.0 as Float * fm2);
+    for i in 0..n_samples {
+        // sample real point source depth $\depthreal$
+        let zr: Float =
+            -(1.0 as Float - (i as Float + 0.5 as Float) / n_samples as Float).ln() / sigmap_t;
+        // evaluate dipole integrand $E_{\roman{d}}$ at $\depthreal$ and add to _ed_
+        let zv: Float = -zr + 2.0 as Float * ze;
+        let dr: Float = (r * r + zr * zr).sqrt();
+        let dv: Float = (r * r + zv * zv).sqrt();
+        // compute dipole fluence rate $\dipole(r)$ using Equation (15.27)
+        let phi_d: Float =
+            INV_4_PI / d_g * ((-sigma_tr * dr).exp() / dr - (-sigma_tr * dv).exp() / dv);
+        // compute dipole vector irradiance $-\N{}\cdot\dipoleE(r)$
+        // using Equation (15.27)
+        let ed_n: Float = INV_4_PI
+            * (zr * (1.0 as Float + sigma_tr * dr) * (-sigma_tr * dr).exp() / (dr * dr * dr)
+                - zv * (1.0 as Float + sigma_tr * dv) * (-sigma_tr * dv).exp() / (dv * dv * dv));
+        // add contribution from dipole for depth $\depthreal$ to _ed_
+        let e: Float = phi_d * c_phi + ed_n * c_e;
+        let kappa: Float = 1.0 as Float - (-2.0 as Float * sigmap_t * (dr + zr)).exp();
+        ed += kappa * rhop * rhop * e;
+    }
+    ed / n_samples as Float
+}
+
+/// Single scattering term of the photon beam diffusion (PBD)
+/// profile.
+pub fn beam_diffusion_ss(sigma_s: Float, sigma_a: Float, g: Float, eta: Float, r: Float) -> Float {
+    // compute material parameters and minimum $t$ below the critical angle
+    let sigma_t: Float = sigma_a + sigma_s;
+    let rho: Float = sigma_s / sigma_t;
+    let t_crit: Float = r * (eta * eta - 1.0 as Float).sqrt();
+    let mut ess: Float = 0.0 as Float;
+    let n_samples: i32 = 100;
+    for i in 0..n_samples {
+        // evaluate single scattering integrand and add to _ess_
+        let ti: Float = t_crit
+            - (1.0 as Float - (i as Float + 0.5 as Float) / n_samples as Float).ln() / sigma_t;
+        // determine length $d$ of connecting segment and $\cos\theta_\roman{o}$
+        let d: Float = (r * r + ti * ti).sqrt();
+        let cos_theta_o: Float = ti / d;
+        // add contribution of single scattering at depth $t$
+        ess += rho * (-sigma_t * (d + t_crit)).exp() / (d * d)
+            * phase_hg(cos_theta_o, g)
+            * (1.0 as Float - fr_dielectric(-cos_theta_o, 1.0 as Float, eta))
+            * cos_theta_o.abs();
+    }
+    ess / n_samples as Float
+}
+
+/// Fills a **BssrdfTable** with the photon beam diffusion profile
+/// for the given phase function asymmetry and index of refraction.
+pub fn compute_beam_diffusion_bssrdf(g: Float, eta: Float, t: &mut BssrdfTable) {
+    // choose radius values of the diffusion profile discretization
+    t.radius_samples[0] = 0.0 as Float;
+    t.radius_samples[1] = 2.5e-3 as Float;
+    for i in 2..t.n_radius_samples as usize {
+        t.radius_samples[i] = t.radius_samples[i - 1] * 1.2 as Float;
+    }
+    // choose albedo values of the diffusion profile discretization
+    for i in 0..t.n_rho_samples as usize {
+        t.rho_samples[i] = (1.0 as Float
+            - (-8.0 as Float * i as Float / (t.n_rho_samples - 1) as Float).exp())
+            / (1.0 as Float - (-8.0 as Float).exp());
+    }
+    let n_radius_samples: usize = t.n_radius_samples as usize;
+    let radius_samples: &Vec<Float> = &t.radius_samples;
+    let rho_samples: &Vec<Float> = &t.rho_samples;
+    let rho_eff: Vec<Float> = t
+        .profile
+        .par_chunks_mut(n_radius_samples)
+        .zip(t.profile_cdf.par_chunks_mut(n_radius_samples))
+        .enumerate()
+        .map(|(i, (profile, profile_cdf))| {
+            // compute the diffusion profile for the _i_th albedo sample
+
+            // compute scattering profile for chosen albedo $\rho$
+            for j in 0..n_radius_samples {
+                let rho: Float = rho_samples[i];
+                let r: Float = radius_samples[j];
+                profile[j] = 2.0 as Float
+                    * PI
+                    * r
+                    * (beam_diffusion_ss(rho, 1.0 as Float - rho, g, eta, r)
+                        + beam_diffusion_ms(rho, 1.0 as Float - rho, g, eta, r));
+            }
+            // compute effective albedo $\rho_{\roman{eff}}$ and CDF
+            // for importance sampling
+            integrate_catmull_rom(radius_samples, profile, profile_cdf)
+        })
+        .collect();
+    t.rho_eff = rho_eff;
+}
+
+/// Inverts the effective albedo of a **BssrdfTable** to find the
+/// scattering coefficients which produce the given diffuse
+/// reflectance and mean free path.
+pub fn subsurface_from_diffuse(
+    t: &BssrdfTable,
+    rho_eff: &Spectrum,
+    mfp: &Spectrum,
+    sigma_a: &mut Spectrum,
+    sigma_s: &mut Spectrum,
+) {
+    for c in 0..3 {
+        // TODO: Spectrum::nSamples
+        let rho: Float = invert_catmull_rom(&t.rho_samples, &t.rho_eff, rho_eff[c]);
+        sigma_s[c] = rho / mfp[c];
+        sigma_a[c] = (1.0 as Float - rho) / mfp[c];
+    }
+}
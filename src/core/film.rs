@@ -10,23 +10,16 @@
 //!
 
 // std
-#[cfg(feature = "openexr")]
-use std;
 use std::ops::{DerefMut, Index};
-use std::path::Path;
 use std::sync::{Arc, RwLock, RwLockWriteGuard};
-// others
-use image;
-#[cfg(feature = "openexr")]
-use openexr::{FrameBuffer, Header, PixelType, ScanlineOutputFile};
 // pbrt
 use core::filter::Filter;
 use core::geometry::{
     bnd2_intersect_bnd2, pnt2_ceil, pnt2_floor, pnt2_inside_exclusive, pnt2_max_pnt2, pnt2_min_pnt2,
 };
 use core::geometry::{Bounds2f, Bounds2i, Point2f, Point2i, Vector2f};
+use core::imageio::write_image;
 use core::parallel::AtomicFloat;
-use core::pbrt::{clamp_t, gamma_correct};
 use core::pbrt::{Float, Spectrum};
 use core::spectrum::xyz_to_rgb;
 
@@ -365,103 +358,28 @@ impl Film {
         splat_xyz[1].add(xyz[1]);
         splat_xyz[2].add(xyz[2]);
     }
-    #[cfg(not(feature = "openexr"))]
-    pub fn write_image(&self, splat_scale: Float) {
-        let mut rgb: Vec<Float> =
-            vec![0.0 as Float; (3 * self.cropped_pixel_bounds.area()) as usize];
-        let mut offset;
-        for p in &self.cropped_pixel_bounds {
-            // convert pixel XYZ color to RGB
-            assert!(pnt2_inside_exclusive(&p, &self.cropped_pixel_bounds));
-            let width: i32 = self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x;
-            offset = ((p.x - self.cropped_pixel_bounds.p_min.x)
-                + (p.y - self.cropped_pixel_bounds.p_min.y) * width) as usize;
-            let pixel: &Pixel = &self.pixels.read().unwrap()[offset];
-
-            let start: usize = 3 * offset;
-            let mut rgb_array: [Float; 3] = [0.0 as Float; 3];
-            xyz_to_rgb(&pixel.xyz, &mut rgb_array); // TODO: Use 'rgb' directly.
-            rgb[start + 0] = rgb_array[0];
-            rgb[start + 1] = rgb_array[1];
-            rgb[start + 2] = rgb_array[2];
-            // normalize pixel with weight sum
-            let filter_weight_sum: Float = pixel.filter_weight_sum;
-            if filter_weight_sum != 0.0 as Float {
-                let inv_wt: Float = 1.0 as Float / filter_weight_sum;
-                rgb[start + 0] = (rgb[start + 0] * inv_wt).max(0.0 as Float);
-                rgb[start + 1] = (rgb[start + 1] * inv_wt).max(0.0 as Float);
-                rgb[start + 2] = (rgb[start + 2] * inv_wt).max(0.0 as Float);
-            }
-            // add splat value at pixel
-            let mut splat_rgb: [Float; 3] = [0.0 as Float; 3];
-            let pixel_splat_xyz: &[AtomicFloat; 3] = &pixel.splat_xyz;
-            let splat_xyz: [Float; 3] = [
-                Float::from(pixel_splat_xyz.index(0)),
-                Float::from(pixel_splat_xyz.index(1)),
-                Float::from(pixel_splat_xyz.index(2)),
+    /// Overwrite the pixels of the cropped image with the given
+    /// (already filtered) values, discarding previous samples and
+    /// splats.
+    pub fn set_image(&self, img: &[Spectrum]) {
+        let n_pixels: usize = self.cropped_pixel_bounds.area() as usize;
+        assert_eq!(img.len(), n_pixels);
+        let mut pixels_write: RwLockWriteGuard<Vec<Pixel>> = self.pixels.write().unwrap();
+        let pixel_vec: &mut Vec<Pixel> = pixels_write.deref_mut();
+        for i in 0..n_pixels {
+            let p: &mut Pixel = &mut pixel_vec[i];
+            img[i].to_xyz(&mut p.xyz);
+            p.filter_weight_sum = 1.0 as Float;
+            p.splat_xyz = [
+                AtomicFloat::default(),
+                AtomicFloat::default(),
+                AtomicFloat::default(),
             ];
-            xyz_to_rgb(&splat_xyz, &mut splat_rgb);
-            rgb[start + 0] += splat_scale * splat_rgb[0];
-            rgb[start + 1] += splat_scale * splat_rgb[1];
-            rgb[start + 2] += splat_scale * splat_rgb[2];
-            // scale pixel value by _scale_
-            rgb[start + 0] *= self.scale;
-            rgb[start + 1] *= self.scale;
-            rgb[start + 2] *= self.scale;
         }
-        let filename = "pbrt.png";
-        println!(
-            "Writing image {:?} with bounds {:?}",
-            filename, // TODO: self.filename,
-            self.cropped_pixel_bounds
-        );
-        // TODO: pbrt::WriteImage(filename, &rgb[0], croppedPixelBounds, fullResolution);
-        let mut buffer: Vec<u8> = vec![0.0 as u8; (3 * self.cropped_pixel_bounds.area()) as usize];
-        // 8-bit format; apply gamma (see WriteImage(...) in imageio.cpp)
-        let width: u32 =
-            (self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x) as u32;
-        let height: u32 =
-            (self.cropped_pixel_bounds.p_max.y - self.cropped_pixel_bounds.p_min.y) as u32;
-        for y in 0..height {
-            for x in 0..width {
-                // red
-                let index: usize = (3 * (y * width + x) + 0) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-                // green
-                let index: usize = (3 * (y * width + x) + 1) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-                // blue
-                let index: usize = (3 * (y * width + x) + 2) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-            }
-        }
-        // write "pbrt.png" to disk
-        image::save_buffer(
-            &Path::new("pbrt.png"),
-            &buffer,
-            width,
-            height,
-            image::RGB(8),
-        ).unwrap();
     }
-    #[cfg(feature = "openexr")]
     pub fn write_image(&self, splat_scale: Float) {
         let mut rgb: Vec<Float> =
             vec![0.0 as Float; (3 * self.cropped_pixel_bounds.area()) as usize];
-        let mut exr: Vec<(Float, Float, Float)> = // copy data for OpenEXR image
-            vec![(0.0_f32, 0.0_f32, 0.0_f32); self.cropped_pixel_bounds.area() as usize];
         let mut offset;
         for p in &self.cropped_pixel_bounds {
             // convert pixel XYZ color to RGB
@@ -470,7 +388,8 @@ impl Film {
             offset = ((p.x - self.cropped_pixel_bounds.p_min.x)
                 + (p.y - self.cropped_pixel_bounds.p_min.y) * width) as usize;
             let pixel: &Pixel = &self.pixels.read().unwrap()[offset];
-            let start = 3 * offset;
+
+            let start: usize = 3 * offset;
             let mut rgb_array: [Float; 3] = [0.0 as Float; 3];
             xyz_to_rgb(&pixel.xyz, &mut rgb_array); // TODO: Use 'rgb' directly.
             rgb[start + 0] = rgb_array[0];
@@ -480,10 +399,15 @@ impl Film {
             let filter_weight_sum: Float = pixel.filter_weight_sum;
             if filter_weight_sum != 0.0 as Float {
                 let inv_wt: Float = 1.0 as Float / filter_weight_sum;
-                rgb[start + 0] = (rgb[start + 0] * inv_wt).max(0.0 as Float);
-                rgb[start + 1] = (rgb[start + 1] * inv_wt).max(0.0 as Float);
-                rgb[start + 2] = (rgb[start + 2] * inv_wt).max(0.0 as Float);
+                rgb[start + 0] *= inv_wt;
+                rgb[start + 1] *= inv_wt;
+                rgb[start + 2] *= inv_wt;
             }
+            // filters with negative lobes (e.g. _LanczosSincFilter_)
+            // can produce negative weighted sums, clamp them to zero
+            rgb[start + 0] = rgb[start + 0].max(0.0 as Float);
+            rgb[start + 1] = rgb[start + 1].max(0.0 as Float);
+            rgb[start + 2] = rgb[start + 2].max(0.0 as Float);
             // add splat value at pixel
             let mut splat_rgb: [Float; 3] = [0.0 as Float; 3];
             let pixel_splat_xyz: &[AtomicFloat; 3] = &pixel.splat_xyz;
@@ -500,78 +424,17 @@ impl Film {
             rgb[start + 0] *= self.scale;
             rgb[start + 1] *= self.scale;
             rgb[start + 2] *= self.scale;
-            // copy data for OpenEXR image
-            exr[offset].0 = rgb[start + 0];
-            exr[offset].1 = rgb[start + 1];
-            exr[offset].2 = rgb[start + 2];
         }
-        let filename = "pbrt.png";
         println!(
             "Writing image {:?} with bounds {:?}",
-            filename, // TODO: self.filename,
-            self.cropped_pixel_bounds
+            self.filename, self.cropped_pixel_bounds
         );
-        // TODO: pbrt::WriteImage(filename, &rgb[0], croppedPixelBounds, fullResolution);
-        let mut buffer: Vec<u8> = vec![0.0 as u8; (3 * self.cropped_pixel_bounds.area()) as usize];
-        // 8-bit format; apply gamma (see WriteImage(...) in imageio.cpp)
-        let width: u32 =
-            (self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x) as u32;
-        let height: u32 =
-            (self.cropped_pixel_bounds.p_max.y - self.cropped_pixel_bounds.p_min.y) as u32;
-        // OpenEXR
-        let filename = "pbrt_rust.exr";
-        println!(
-            "Writing image {:?} with bounds {:?}",
-            filename, // TODO: self.filename,
-            self.cropped_pixel_bounds
+        write_image(
+            &self.filename,
+            &rgb,
+            &self.cropped_pixel_bounds,
+            &self.full_resolution,
         );
-        let mut file = std::fs::File::create("pbrt_rust.exr").unwrap();
-        let mut output_file = ScanlineOutputFile::new(
-            &mut file,
-            Header::new()
-                .set_resolution(width, height)
-                .add_channel("R", PixelType::FLOAT)
-                .add_channel("G", PixelType::FLOAT)
-                .add_channel("B", PixelType::FLOAT),
-        ).unwrap();
-        let mut fb = FrameBuffer::new(width as u32, height as u32);
-        fb.insert_channels(&["R", "G", "B"], &exr);
-        output_file.write_pixels(&fb).unwrap();
-
-        // OpenEXR
-        for y in 0..height {
-            for x in 0..width {
-                // red
-                let index: usize = (3 * (y * width + x) + 0) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-                // green
-                let index: usize = (3 * (y * width + x) + 1) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-                // blue
-                let index: usize = (3 * (y * width + x) + 2) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-            }
-        }
-        // write "pbrt.png" to disk
-        image::save_buffer(
-            &Path::new("pbrt.png"),
-            &buffer,
-            width,
-            height,
-            image::RGB(8),
-        ).unwrap();
     }
     // pub fn get_pixel<'a>(&self, p: &Point2i) -> &'a Pixel {
     //     assert!(pnt2_inside_exclusive(p, &self.cropped_pixel_bounds));
@@ -760,6 +760,14 @@ where
     }
 }
 
+/// Is a 2D point inside a 2D bound (including the upper boundary)?
+pub fn pnt2_inside_bnd2<T>(pt: &Point2<T>, b: &Bounds2<T>) -> bool
+where
+    T: PartialOrd,
+{
+    pt.x >= b.p_min.x && pt.x <= b.p_max.x && pt.y >= b.p_min.y && pt.y <= b.p_max.y
+}
+
 /// Is a 2D point inside a 2D bound?
 pub fn pnt2_inside_exclusive<T>(pt: &Point2<T>, b: &Bounds2<T>) -> bool
 where
@@ -1361,6 +1369,16 @@ impl Bounds2<Float> {
             y: lerp(t.y, self.p_min.y, self.p_max.y),
         }
     }
+    pub fn offset(&self, p: &Point2f) -> Vector2f {
+        let mut o: Vector2f = *p - self.p_min;
+        if self.p_max.x > self.p_min.x {
+            o.x /= self.p_max.x - self.p_min.x;
+        }
+        if self.p_max.y > self.p_min.y {
+            o.y /= self.p_max.y - self.p_min.y;
+        }
+        o
+    }
 }
 
 pub struct Bounds2Iterator<'a> {
@@ -1596,6 +1614,32 @@ impl Bounds3<Float> {
         }
         (t_min < ray.t_max) && (t_max > 0.0)
     }
+    /// Returns the parametric range of the ray inside the bounding
+    /// box (if any) in *hitt0* and *hitt1*.
+    pub fn intersect_b(&self, ray: &Ray, hitt0: &mut Float, hitt1: &mut Float) -> bool {
+        let mut t0: Float = 0.0 as Float;
+        let mut t1: Float = ray.t_max;
+        for i in 0..3_u8 {
+            // update interval for _i_th bounding box slab
+            let inv_ray_dir: Float = 1.0 as Float / ray.d[i];
+            let mut t_near: Float = (self.p_min[i] - ray.o[i]) * inv_ray_dir;
+            let mut t_far: Float = (self.p_max[i] - ray.o[i]) * inv_ray_dir;
+            // update parametric interval from slab intersection $t$ values
+            if t_near > t_far {
+                std::mem::swap(&mut t_near, &mut t_far);
+            }
+            // update _t_far_ to ensure robust ray--bounds intersection
+            t_far *= 1.0 + 2.0 * gamma(3_i32);
+            t0 = if t_near > t0 { t_near } else { t0 };
+            t1 = if t_far < t1 { t_far } else { t1 };
+            if t0 > t1 {
+                return false;
+            }
+        }
+        *hitt0 = t0;
+        *hitt1 = t1;
+        true
+    }
 }
 
 impl<T> Index<u8> for Bounds3<T> {
@@ -0,0 +1,265 @@
+//! Read IES LM-63 (1986, 1991, 1995, 2002) photometric data files.
+
+// std
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+// pbrt
+use core::geometry::{spherical_phi, spherical_theta, Vector3f};
+use core::pbrt::Float;
+
+/// Tabulated angular intensity distribution (in candela) of a
+/// luminaire. Vertical angles are measured from the photometric axis
+/// (nadir, 0 degrees) towards the zenith (180 degrees), horizontal
+/// angles around the photometric axis.
+#[derive(Debug, Default, Clone)]
+pub struct IesProfile {
+    pub photometric_type: i32,
+    pub vertical_angles: Vec<Float>,
+    pub horizontal_angles: Vec<Float>,
+    /// candela values, one row of vertical angles per horizontal angle
+    pub candela: Vec<Float>,
+}
+
+impl IesProfile {
+    pub fn read(filename: &String) -> Option<IesProfile> {
+        let path = Path::new(&filename);
+        let result = File::open(path);
+        if !result.is_ok() {
+            println!("ERROR: Unable to open IES file {:?}", filename);
+            return None;
+        }
+        let reader = BufReader::new(result.unwrap());
+        // skip header and keyword lines up to TILT=...
+        let mut tilt: Option<String> = None;
+        let mut tokens: Vec<String> = Vec::new();
+        for line_result in reader.lines() {
+            if !line_result.is_ok() {
+                println!("ERROR: Unable to read IES file {:?}", filename);
+                return None;
+            }
+            let line = line_result.unwrap();
+            if tilt.is_none() {
+                let trimmed = line.trim();
+                if trimmed.starts_with("TILT=") {
+                    tilt = Some(String::from(trimmed[5..].trim()));
+                }
+            } else {
+                for token in line.split_whitespace() {
+                    tokens.push(String::from(token));
+                }
+            }
+        }
+        if tilt.is_none() {
+            println!("ERROR: No TILT line found in IES file {:?}", filename);
+            return None;
+        }
+        let mut values: Vec<Float> = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            match token.parse::<f32>() {
+                Ok(float) => values.push(float as Float),
+                Err(_) => {
+                    println!(
+                        "ERROR: Unexpected text {:?} found in IES file {:?}",
+                        token, filename
+                    );
+                    return None;
+                }
+            }
+        }
+        let mut index: usize = 0;
+        if tilt == Some(String::from("INCLUDE")) {
+            // lamp-to-luminaire geometry, followed by angles and
+            // multiplying factors (ignored, lamps are assumed to be
+            // operated in their photometric position)
+            if values.len() < 2 {
+                println!("ERROR: Premature end of IES file {:?}", filename);
+                return None;
+            }
+            let n_tilt: usize = values[1] as usize;
+            index += 2 + 2 * n_tilt;
+        } else if tilt != Some(String::from("NONE")) {
+            println!(
+                "WARNING: TILT={:?} in IES file {:?} not supported (ignored)",
+                tilt.unwrap(),
+                filename
+            );
+        }
+        // number of lamps, lumens per lamp, candela multiplier, number
+        // of vertical angles, number of horizontal angles, photometric
+        // type, units type, width, length, height, ballast factor,
+        // future use (ballast-lamp photometric factor), input watts
+        if values.len() < index + 13 {
+            println!("ERROR: Premature end of IES file {:?}", filename);
+            return None;
+        }
+        let candela_multiplier: Float = values[index + 2];
+        let n_vertical: usize = values[index + 3] as usize;
+        let n_horizontal: usize = values[index + 4] as usize;
+        let photometric_type: i32 = values[index + 5] as i32;
+        let ballast_factor: Float = values[index + 10];
+        let ballast_lamp_factor: Float = values[index + 11];
+        index += 13;
+        if values.len() < index + n_vertical + n_horizontal + n_vertical * n_horizontal {
+            println!("ERROR: Premature end of IES file {:?}", filename);
+            return None;
+        }
+        if n_vertical == 0 || n_horizontal == 0 {
+            println!("ERROR: No candela values in IES file {:?}", filename);
+            return None;
+        }
+        if photometric_type != 1 {
+            println!(
+                "WARNING: Photometric type {} in IES file {:?} treated as type C",
+                photometric_type, filename
+            );
+        }
+        let vertical_angles: Vec<Float> = values[index..index + n_vertical].to_vec();
+        index += n_vertical;
+        let horizontal_angles: Vec<Float> = values[index..index + n_horizontal].to_vec();
+        index += n_horizontal;
+        let scale: Float = candela_multiplier * ballast_factor * ballast_lamp_factor;
+        let candela: Vec<Float> = values[index..index + n_vertical * n_horizontal]
+            .iter()
+            .map(|c| c * scale)
+            .collect();
+        Some(IesProfile {
+            photometric_type: photometric_type,
+            vertical_angles: vertical_angles,
+            horizontal_angles: horizontal_angles,
+            candela: candela,
+        })
+    }
+    /// Intensity (in candela) for a normalized direction _w_ in light
+    /// space. All lights use the same orientation: the photometric
+    /// axis (vertical angle 0) points along +z and the horizontal
+    /// angle is measured from +x towards +y.
+    pub fn evaluate_direction(&self, w: &Vector3f) -> Float {
+        self.evaluate(
+            spherical_theta(w).to_degrees(),
+            spherical_phi(w).to_degrees(),
+        )
+    }
+    /// Intensity (in candela) for a vertical angle _theta_ and a
+    /// horizontal angle _phi_ (both in degrees).
+    pub fn evaluate(&self, theta: Float, phi: Float) -> Float {
+        let n_v: usize = self.vertical_angles.len();
+        let n_h: usize = self.horizontal_angles.len();
+        // outside of the measured vertical range no light is emitted
+        if theta < self.vertical_angles[0] || theta > self.vertical_angles[n_v - 1] {
+            return 0.0 as Float;
+        }
+        // apply horizontal symmetry of the luminaire
+        let mut phi: Float = phi % 360.0 as Float;
+        if phi < 0.0 as Float {
+            phi += 360.0 as Float;
+        }
+        let first_h: Float = self.horizontal_angles[0];
+        let last_h: Float = self.horizontal_angles[n_h - 1];
+        if n_h == 1 || last_h == 0.0 as Float {
+            // rotationally symmetric
+            return IesProfile::interpolate(&self.vertical_angles, &self.candela[0..n_v], theta);
+        } else if first_h == 0.0 as Float && last_h == 90.0 as Float {
+            // symmetric in each quadrant
+            if phi > 180.0 as Float {
+                phi = 360.0 as Float - phi;
+            }
+            if phi > 90.0 as Float {
+                phi = 180.0 as Float - phi;
+            }
+        } else if first_h == 0.0 as Float && last_h == 180.0 as Float {
+            // bilaterally symmetric about the 0-180 degree plane
+            if phi > 180.0 as Float {
+                phi = 360.0 as Float - phi;
+            }
+        } else if first_h == 90.0 as Float && last_h == 270.0 as Float {
+            // bilaterally symmetric about the 90-270 degree plane
+            if phi < 90.0 as Float {
+                phi = 180.0 as Float - phi;
+            } else if phi > 270.0 as Float {
+                phi = 540.0 as Float - phi;
+            }
+        }
+        // find horizontal interval and interpolate between planes
+        let h: usize = IesProfile::find_interval(&self.horizontal_angles, phi);
+        let i0: Float = IesProfile::interpolate(
+            &self.vertical_angles,
+            &self.candela[h * n_v..(h + 1) * n_v],
+            theta,
+        );
+        if h + 1 >= n_h {
+            // wrap around between the last plane and 360 degrees
+            let h0: Float = self.horizontal_angles[n_h - 1];
+            let h1: Float = self.horizontal_angles[0] + 360.0 as Float;
+            if h1 <= h0 || phi <= h0 {
+                return i0;
+            }
+            let i1: Float =
+                IesProfile::interpolate(&self.vertical_angles, &self.candela[0..n_v], theta);
+            let t: Float = (phi - h0) / (h1 - h0);
+            return (1.0 as Float - t) * i0 + t * i1;
+        }
+        let i1: Float = IesProfile::interpolate(
+            &self.vertical_angles,
+            &self.candela[(h + 1) * n_v..(h + 2) * n_v],
+            theta,
+        );
+        let h0: Float = self.horizontal_angles[h];
+        let h1: Float = self.horizontal_angles[h + 1];
+        let t: Float = if h1 > h0 {
+            ((phi - h0) / (h1 - h0)).max(0.0 as Float).min(1.0 as Float)
+        } else {
+            0.0 as Float
+        };
+        (1.0 as Float - t) * i0 + t * i1
+    }
+    /// Integral of the intensity distribution over the sphere of
+    /// directions (in lumen), computed numerically.
+    pub fn integral(&self) -> Float {
+        self.integral_with(&|_cos_theta| 1.0 as Float)
+    }
+    /// Integral of the intensity distribution over the sphere of
+    /// directions, weighted by a function of the cosine of the
+    /// vertical angle.
+    pub fn integral_with(&self, weight: &Fn(Float) -> Float) -> Float {
+        let n_theta: usize = 256;
+        let n_phi: usize = 512;
+        let d_theta: Float = PI / n_theta as Float;
+        let d_phi: Float = 2.0 as Float * PI / n_phi as Float;
+        let mut sum: Float = 0.0 as Float;
+        for t in 0..n_theta {
+            let theta: Float = (t as Float + 0.5 as Float) * d_theta;
+            let sin_theta: Float = theta.sin();
+            let w: Float = weight(theta.cos());
+            if w == 0.0 as Float {
+                continue;
+            }
+            for p in 0..n_phi {
+                let phi: Float = (p as Float + 0.5 as Float) * d_phi;
+                sum += self.evaluate(theta.to_degrees(), phi.to_degrees()) * w * sin_theta;
+            }
+        }
+        sum * d_theta * d_phi
+    }
+    fn find_interval(angles: &Vec<Float>, angle: Float) -> usize {
+        let mut i: usize = 0;
+        while i + 1 < angles.len() && angles[i + 1] <= angle {
+            i += 1;
+        }
+        i
+    }
+    fn interpolate(angles: &Vec<Float>, values: &[Float], angle: Float) -> Float {
+        let i: usize = IesProfile::find_interval(angles, angle);
+        if i + 1 >= angles.len() {
+            return values[i];
+        }
+        let a0: Float = angles[i];
+        let a1: Float = angles[i + 1];
+        if a1 <= a0 {
+            return values[i];
+        }
+        let t: Float = (angle - a0) / (a1 - a0);
+        (1.0 as Float - t) * values[i] + t * values[i + 1]
+    }
+}
@@ -0,0 +1,308 @@
+extern crate image;
+
+// std
+use std;
+use std::io::{BufReader, BufWriter, Write};
+#[cfg(feature = "openexr")]
+use std::os::raw::c_char;
+use std::path::Path;
+// others
+use byteorder::{LittleEndian, WriteBytesExt};
+use image::{DynamicImage, ImageResult};
+#[cfg(feature = "openexr")]
+use openexr::{Box2i, FrameBuffer, Header, PixelType, ScanlineOutputFile};
+// pbrt
+use core::geometry::{Bounds2i, Point2i};
+use core::pbrt::{clamp_t, gamma_correct};
+use core::pbrt::{Float, Spectrum};
+
+// see imageio.h
+
+/// Reads an image file into a vector of RGB spectra (no flipping in
+/// y). HDR files (*.hdr) are read with full precision, all other
+/// formats supported by the **image** crate are interpreted as 8-bit
+/// sRGB values and converted back to linear values.
+pub fn read_image(name: &str) -> Option<(Vec<Spectrum>, Point2i)> {
+    read_image_gamma(name, true)
+}
+
+/// Like **read_image**, but 8-bit formats are taken as linear values
+/// without any gamma correction (e.g. for normal maps).
+pub fn read_image_linear(name: &str) -> Option<(Vec<Spectrum>, Point2i)> {
+    read_image_gamma(name, false)
+}
+
+fn read_image_gamma(name: &str, gamma: bool) -> Option<(Vec<Spectrum>, Point2i)> {
+    let path = Path::new(name);
+    let is_hdr: bool = match path.extension() {
+        Some(ext) => ext.to_str().unwrap_or("").to_lowercase() == "hdr",
+        None => false,
+    };
+    if is_hdr {
+        let file_result = std::fs::File::open(path);
+        if !file_result.is_ok() {
+            println!("ERROR: Unable to open file {:?}", name);
+            return None;
+        }
+        let reader = BufReader::new(file_result.unwrap());
+        let hdr_result = image::hdr::HDRDecoder::with_strictness(reader, false);
+        if let Ok(hdr) = hdr_result {
+            let meta = hdr.metadata();
+            let resolution: Point2i = Point2i {
+                x: meta.width as i32,
+                y: meta.height as i32,
+            };
+            let img_result = hdr.read_image_transform(|p| {
+                let rgb = p.to_hdr();
+                Spectrum::rgb(rgb[0], rgb[1], rgb[2])
+            });
+            if let Ok(texels) = img_result {
+                return Some((texels, resolution));
+            }
+        }
+        println!("ERROR: Unable to read HDR image {:?}", name);
+        return None;
+    }
+    let img_result: ImageResult<DynamicImage> = image::open(path);
+    if !img_result.is_ok() {
+        println!("ERROR: Unable to read image {:?}", name);
+        return None;
+    }
+    let buf = img_result.unwrap();
+    let rgb = buf.to_rgb();
+    let resolution: Point2i = Point2i {
+        x: rgb.width() as i32,
+        y: rgb.height() as i32,
+    };
+    let texels: Vec<Spectrum> = rgb
+        .pixels()
+        .map(|p| {
+            let r = Float::from(p[0]) / 255.0;
+            let g = Float::from(p[1]) / 255.0;
+            let b = Float::from(p[2]) / 255.0;
+            if gamma {
+                Spectrum::rgb(r, g, b).inverse_gamma_correct()
+            } else {
+                Spectrum::rgb(r, g, b)
+            }
+        }).collect();
+    Some((texels, resolution))
+}
+
+/// Writes the RGB values of the pixels within _output_bounds_ of an
+/// image with _total_resolution_ to a file. The image format is
+/// chosen by the extension of _name_ (*.exr*, *.pfm*, *.png* or
+/// *.tga*). Only OpenEXR keeps track of where the pixels lie within
+/// the full image (the data window), all other formats just contain
+/// the cropped region.
+pub fn write_image(
+    name: &str,
+    rgb: &[Float],
+    output_bounds: &Bounds2i,
+    total_resolution: &Point2i,
+) {
+    let resolution: Point2i = Point2i {
+        x: output_bounds.p_max.x - output_bounds.p_min.x,
+        y: output_bounds.p_max.y - output_bounds.p_min.y,
+    };
+    assert_eq!(rgb.len(), (3 * resolution.x * resolution.y) as usize);
+    let extension: String = match Path::new(name).extension() {
+        Some(ext) => ext.to_str().unwrap_or("").to_lowercase(),
+        None => String::new(),
+    };
+    if extension == "exr" {
+        write_image_exr(name, rgb, output_bounds, total_resolution);
+    } else if extension == "pfm" {
+        write_image_pfm(name, rgb, &resolution);
+    } else if extension == "png" || extension == "tga" {
+        // 8-bit formats; apply gamma
+        let mut rgb8: Vec<u8> = vec![0_u8; rgb.len()];
+        for i in 0..rgb.len() {
+            rgb8[i] = clamp_t(
+                255.0 as Float * gamma_correct(rgb[i]) + 0.5,
+                0.0 as Float,
+                255.0 as Float,
+            ) as u8;
+        }
+        if extension == "png" {
+            let result = image::save_buffer(
+                &Path::new(name),
+                &rgb8,
+                resolution.x as u32,
+                resolution.y as u32,
+                image::RGB(8),
+            );
+            if result.is_err() {
+                println!("ERROR: Unable to write PNG image {:?}", name);
+            }
+        } else {
+            write_image_tga(name, &rgb8, &resolution);
+        }
+    } else {
+        println!(
+            "ERROR: Can't determine image file type from suffix of filename {:?}",
+            name
+        );
+    }
+}
+
+#[cfg(feature = "openexr")]
+fn write_image_exr(
+    name: &str,
+    rgb: &[Float],
+    output_bounds: &Bounds2i,
+    total_resolution: &Point2i,
+) {
+    let width: u32 = (output_bounds.p_max.x - output_bounds.p_min.x) as u32;
+    let height: u32 = (output_bounds.p_max.y - output_bounds.p_min.y) as u32;
+    if width == 0 || height == 0 {
+        println!("ERROR: No pixels to write to OpenEXR image {:?}", name);
+        return;
+    }
+    if width < 2 || height < 2 {
+        // Header::set_data_window() asserts a data window of at least
+        // 2x2 pixels, keep the pixels of a single row or column strip
+        let pfm_name = Path::new(name).with_extension("pfm");
+        println!(
+            "ERROR: Can't write a {}x{} data window at ({}, {}) to OpenEXR image {:?}, writing {:?} instead",
+            width,
+            height,
+            output_bounds.p_min.x,
+            output_bounds.p_min.y,
+            name,
+            pfm_name
+        );
+        write_image_pfm(
+            pfm_name.to_str().unwrap_or(name),
+            rgb,
+            &Point2i {
+                x: width as i32,
+                y: height as i32,
+            },
+        );
+        return;
+    }
+    let exr: Vec<f32> = rgb.iter().map(|v| *v as f32).collect();
+    let file_result = std::fs::File::create(name);
+    if !file_result.is_ok() {
+        println!("ERROR: Unable to create file {:?}", name);
+        return;
+    }
+    let mut file = file_result.unwrap();
+    // the display window covers the full image, the data window
+    // (inclusive bounds) only the pixels actually written
+    let mut header: Header = Header::new();
+    header.set_resolution(total_resolution.x as u32, total_resolution.y as u32);
+    let mut data_window: Box2i = *header.data_window();
+    data_window.min.x = output_bounds.p_min.x;
+    data_window.min.y = output_bounds.p_min.y;
+    data_window.max.x = output_bounds.p_max.x - 1;
+    data_window.max.y = output_bounds.p_max.y - 1;
+    header
+        .set_data_window(data_window)
+        .add_channel("R", PixelType::FLOAT)
+        .add_channel("G", PixelType::FLOAT)
+        .add_channel("B", PixelType::FLOAT);
+    let output_result = ScanlineOutputFile::new(&mut file, &header);
+    if !output_result.is_ok() {
+        println!("ERROR: Unable to write OpenEXR image {:?}", name);
+        return;
+    }
+    let mut output_file = output_result.unwrap();
+    // OpenEXR addresses pixel (x, y) of the data window as base + x *
+    // x_stride + y * y_stride, so the base pointer has to be moved
+    // back to where pixel (0, 0) would be
+    let x_stride: usize = 3 * std::mem::size_of::<f32>();
+    let y_stride: usize = x_stride * width as usize;
+    let origin: isize = output_bounds.p_min.x as isize * x_stride as isize
+        + output_bounds.p_min.y as isize * y_stride as isize;
+    let mut fb = FrameBuffer::new(width, height);
+    for (c, channel) in ["R", "G", "B"].iter().enumerate() {
+        let offset: isize = (c * std::mem::size_of::<f32>()) as isize - origin;
+        unsafe {
+            fb.insert_raw(
+                channel,
+                PixelType::FLOAT,
+                (exr.as_ptr() as *const c_char).wrapping_offset(offset),
+                (x_stride, y_stride),
+                (1, 1),
+                0.0,
+                (false, false),
+            );
+        }
+    }
+    if output_file.write_pixels(&fb).is_err() {
+        println!("ERROR: Unable to write OpenEXR image {:?}", name);
+    }
+}
+
+#[cfg(not(feature = "openexr"))]
+fn write_image_exr(
+    name: &str,
+    _rgb: &[Float],
+    _output_bounds: &Bounds2i,
+    _total_resolution: &Point2i,
+) {
+    println!(
+        "ERROR: Can't write {:?}, OpenEXR support (feature \"openexr\") is disabled",
+        name
+    );
+}
+
+fn write_image_pfm(name: &str, rgb: &[Float], resolution: &Point2i) {
+    let file_result = std::fs::File::create(name);
+    if !file_result.is_ok() {
+        println!("ERROR: Unable to create file {:?}", name);
+        return;
+    }
+    let mut writer = BufWriter::new(file_result.unwrap());
+    // only write 3 channel PFMs here; a negative scale means little
+    // endian
+    let mut result = write!(writer, "PF\n{} {}\n-1\n", resolution.x, resolution.y);
+    // write the data from bottom left to upper right as specified by
+    // http://netpbm.sourceforge.net/doc/pfm.html
+    for y in (0..resolution.y as usize).rev() {
+        let start: usize = 3 * y * resolution.x as usize;
+        for value in &rgb[start..(start + 3 * resolution.x as usize)] {
+            if result.is_ok() {
+                result = writer.write_f32::<LittleEndian>(*value as f32);
+            }
+        }
+    }
+    if result.is_err() || writer.flush().is_err() {
+        println!("ERROR: Unable to write PFM image {:?}", name);
+    }
+}
+
+fn write_image_tga(name: &str, rgb8: &[u8], resolution: &Point2i) {
+    let file_result = std::fs::File::create(name);
+    if !file_result.is_ok() {
+        println!("ERROR: Unable to create file {:?}", name);
+        return;
+    }
+    let mut writer = BufWriter::new(file_result.unwrap());
+    // uncompressed true-color image, 24 bits per pixel, origin in the
+    // upper left corner
+    let mut header: [u8; 18] = [0_u8; 18];
+    header[2] = 2_u8;
+    header[12] = (resolution.x & 0xff) as u8;
+    header[13] = ((resolution.x >> 8) & 0xff) as u8;
+    header[14] = (resolution.y & 0xff) as u8;
+    header[15] = ((resolution.y >> 8) & 0xff) as u8;
+    header[16] = 24_u8;
+    header[17] = 0x20_u8;
+    let mut result = writer.write_all(&header);
+    // pixels are stored as BGR
+    let mut bgr: Vec<u8> = Vec::with_capacity(rgb8.len());
+    for pixel in rgb8.chunks(3) {
+        bgr.push(pixel[2]);
+        bgr.push(pixel[1]);
+        bgr.push(pixel[0]);
+    }
+    if result.is_ok() {
+        result = writer.write_all(&bgr);
+    }
+    if result.is_err() || writer.flush().is_err() {
+        println!("ERROR: Unable to write TGA image {:?}", name);
+    }
+}
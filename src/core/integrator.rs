@@ -5,8 +5,8 @@
 use std;
 use std::sync::Arc;
 // pbrt
-use core::geometry::vec3_abs_dot_nrm;
-use core::geometry::{Bounds2i, Point2f, Ray, Vector3f};
+use core::geometry::{vec3_abs_dot_nrm, vec3_dot_nrm};
+use core::geometry::{Bounds2i, Normal3f, Point2f, Ray, RayDifferential, Vector3f};
 use core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
 use core::light::is_delta_light;
 use core::light::{Light, VisibilityTester};
@@ -34,14 +34,167 @@ pub trait SamplerIntegrator {
         // arena: &mut Arena,
         depth: i32,
     ) -> Spectrum;
+    /// Like **li()**, but for a camera ray that was already
+    /// intersected with the scene (see **Scene::intersect_batch()**),
+    /// _isect_ being the result. Only called if
+    /// **batch_camera_rays()** returns true.
+    fn li_isect(
+        &self,
+        ray: &mut Ray,
+        _isect: Option<SurfaceInteraction>,
+        scene: &Scene,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        // arena: &mut Arena,
+        depth: i32,
+    ) -> Spectrum {
+        self.li(ray, scene, sampler, depth)
+    }
+    /// Whether **render()** should intersect the camera rays of each
+    /// pixel in one batch and call **li_isect()** instead of **li()**.
+    fn batch_camera_rays(&self) -> bool {
+        false
+    }
     fn get_pixel_bounds(&self) -> Bounds2i;
+    /// Handles perfect specular reflection by tracing a reflected
+    /// ray (with ray differentials) and recursively calling **li()**.
+    fn specular_reflect(
+        &self,
+        ray: &Ray,
+        isect: &SurfaceInteraction,
+        scene: &Scene,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        // arena: &mut Arena,
+        depth: i32,
+    ) -> Spectrum {
+        // compute specular reflection direction _wi_ and BSDF value
+        let wo: Vector3f = isect.wo;
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0 as Float;
+        let ns: Normal3f = isect.shading.n;
+        let mut sampled_type: u8 = 0_u8;
+        let bsdf_flags: u8 = BxdfType::BsdfReflection as u8 | BxdfType::BsdfSpecular as u8;
+        let f: Spectrum;
+        if let Some(ref bsdf) = isect.bsdf {
+            f = bsdf.sample_f(
+                &wo,
+                &mut wi,
+                &sampler.get_2d(),
+                &mut pdf,
+                bsdf_flags,
+                &mut sampled_type,
+            );
+            if pdf > 0.0 as Float && !f.is_black() && vec3_abs_dot_nrm(&wi, &ns) != 0.0 as Float {
+                // compute ray differential _rd_ for specular reflection
+                let mut rd: Ray = isect.spawn_ray(&wi);
+                if let Some(d) = ray.differential.iter().next() {
+                    let dndx: Normal3f =
+                        isect.shading.dndu * isect.dudx + isect.shading.dndv * isect.dvdx;
+                    let dndy: Normal3f =
+                        isect.shading.dndu * isect.dudy + isect.shading.dndv * isect.dvdy;
+                    let dwodx: Vector3f = -d.rx_direction - wo;
+                    let dwody: Vector3f = -d.ry_direction - wo;
+                    let ddndx: Float = vec3_dot_nrm(&dwodx, &ns) + vec3_dot_nrm(&wo, &dndx);
+                    let ddndy: Float = vec3_dot_nrm(&dwody, &ns) + vec3_dot_nrm(&wo, &dndy);
+                    // compute differential reflected directions
+                    let diff: RayDifferential = RayDifferential {
+                        rx_origin: isect.p + isect.dpdx,
+                        ry_origin: isect.p + isect.dpdy,
+                        rx_direction: wi - dwodx
+                            + Vector3f::from(dndx * vec3_dot_nrm(&wo, &ns) + ns * ddndx)
+                                * 2.0 as Float,
+                        ry_direction: wi - dwody
+                            + Vector3f::from(dndy * vec3_dot_nrm(&wo, &ns) + ns * ddndy)
+                                * 2.0 as Float,
+                    };
+                    rd.differential = Some(diff);
+                }
+                return f
+                    * self.li(&mut rd, scene, sampler, depth + 1)
+                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf);
+            } else {
+                Spectrum::new(0.0)
+            }
+        } else {
+            Spectrum::new(0.0)
+        }
+    }
+    /// Handles perfect specular transmission (refraction) the same
+    /// way **specular_reflect()** handles reflection.
+    fn specular_transmit(
+        &self,
+        ray: &Ray,
+        isect: &SurfaceInteraction,
+        scene: &Scene,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        // arena: &mut Arena,
+        depth: i32,
+    ) -> Spectrum {
+        let wo: Vector3f = isect.wo;
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0 as Float;
+        // let p: Point3f = isect.p;
+        let ns: Normal3f = isect.shading.n;
+        let mut sampled_type: u8 = 0_u8;
+        let bsdf_flags: u8 = BxdfType::BsdfTransmission as u8 | BxdfType::BsdfSpecular as u8;
+        let f: Spectrum;
+        if let Some(ref bsdf) = isect.bsdf {
+            f = bsdf.sample_f(
+                &wo,
+                &mut wi,
+                &sampler.get_2d(),
+                &mut pdf,
+                bsdf_flags,
+                &mut sampled_type,
+            );
+            if pdf > 0.0 as Float && !f.is_black() && vec3_abs_dot_nrm(&wi, &ns) != 0.0 as Float {
+                // compute ray differential _rd_ for specular transmission
+                let mut rd: Ray = isect.spawn_ray(&wi);
+                if let Some(d) = ray.differential.iter().next() {
+                    let mut eta: Float = bsdf.eta;
+                    let w: Vector3f = -wo;
+                    if vec3_dot_nrm(&wo, &ns) < 0.0 as Float {
+                        eta = 1.0 / eta;
+                    }
+                    let dndx: Normal3f =
+                        isect.shading.dndu * isect.dudx + isect.shading.dndv * isect.dvdx;
+                    let dndy: Normal3f =
+                        isect.shading.dndu * isect.dudy + isect.shading.dndv * isect.dvdy;
+                    let dwodx: Vector3f = -d.rx_direction - wo;
+                    let dwody: Vector3f = -d.ry_direction - wo;
+                    let ddndx: Float = vec3_dot_nrm(&dwodx, &ns) + vec3_dot_nrm(&wo, &dndx);
+                    let ddndy: Float = vec3_dot_nrm(&dwody, &ns) + vec3_dot_nrm(&wo, &dndy);
+                    let mu: Float = eta * vec3_dot_nrm(&w, &ns) - vec3_dot_nrm(&wi, &ns);
+                    let dmudx: Float = (eta
+                        - (eta * eta * vec3_dot_nrm(&w, &ns)) / vec3_dot_nrm(&wi, &ns))
+                        * ddndx;
+                    let dmudy: Float = (eta
+                        - (eta * eta * vec3_dot_nrm(&w, &ns)) / vec3_dot_nrm(&wi, &ns))
+                        * ddndy;
+                    let diff: RayDifferential = RayDifferential {
+                        rx_origin: isect.p + isect.dpdx,
+                        ry_origin: isect.p + isect.dpdy,
+                        rx_direction: wi + dwodx * eta - Vector3f::from(dndx * mu + ns * dmudx),
+                        ry_direction: wi + dwody * eta - Vector3f::from(dndy * mu + ns * dmudy),
+                    };
+                    rd.differential = Some(diff);
+                }
+                return f
+                    * self.li(&mut rd, scene, sampler, depth + 1)
+                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf);
+            } else {
+                Spectrum::new(0.0)
+            }
+        } else {
+            Spectrum::new(0.0)
+        }
+    }
 }
 
 // see integrator.cpp
 
 /// Most basic direct lighting strategy.
 pub fn uniform_sample_all_lights(
-    it: &SurfaceInteraction,
+    it: &Interaction,
     scene: &Scene,
     sampler: &mut Box<Sampler + Send + Sync>,
     n_light_samples: &Vec<i32>,
@@ -93,7 +246,7 @@ pub fn uniform_sample_all_lights(
 /// Estimate direct lighting for only one randomly chosen light and
 /// multiply the result by the number of lights to compensate.
 pub fn uniform_sample_one_light(
-    it: &SurfaceInteraction,
+    it: &Interaction,
     scene: &Scene,
     sampler: &mut Box<Sampler + Send + Sync>,
     handle_media: bool,
@@ -140,12 +293,12 @@ pub fn uniform_sample_one_light(
 
 /// Computes a direct lighting estimate for a single light source sample.
 pub fn estimate_direct(
-    it: &SurfaceInteraction,
+    it: &Interaction,
     u_scattering: &Point2f,
     light: Arc<Light + Send + Sync>,
     u_light: &Point2f,
     scene: &Scene,
-    _sampler: &mut Box<Sampler + Send + Sync>,
+    sampler: &mut Box<Sampler + Send + Sync>,
     // TODO: arena
     handle_media: bool,
     specular: bool,
@@ -183,21 +336,27 @@ pub fn estimate_direct(
         let mut f: Spectrum = Spectrum::new(0.0);
         if it.is_surface_interaction() {
             // evaluate BSDF for light sampling strategy
-            if let Some(ref bsdf) = it.bsdf {
-                f = bsdf.f(&it.get_wo(), &wi, bsdf_flags)
-                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &it.shading.n));
-                scattering_pdf = bsdf.pdf(&it.get_wo(), &wi, bsdf_flags);
-                // TODO: println!("  surf f*dot :{:?}, scatteringPdf: {:?}", f, scattering_pdf);
+            if let Some(ref bsdf) = it.get_bsdf() {
+                if let Some(shading_n) = it.get_shading_n() {
+                    f = bsdf.f(&it.get_wo(), &wi, bsdf_flags)
+                        * Spectrum::new(vec3_abs_dot_nrm(&wi, &shading_n));
+                    scattering_pdf = bsdf.pdf(&it.get_wo(), &wi, bsdf_flags);
+                    // TODO: println!("  surf f*dot :{:?}, scatteringPdf: {:?}", f, scattering_pdf);
+                }
             }
         } else {
             // evaluate phase function for light sampling strategy
-            // TODO
-            println!("TODO: evaluate phase function for light sampling strategy");
+            if let Some(ref phase) = it.get_phase() {
+                let p: Float = phase.p(&it.get_wo(), &wi);
+                f = Spectrum::new(p);
+                scattering_pdf = p;
+                // TODO: println!("  medium p: {:?}", p);
+            }
         }
         if !f.is_black() {
             // compute effect of visibility for light source sample
             if handle_media {
-                // TODO: li *= tr(scene, sampler);
+                li *= visibility.tr(scene, &mut **sampler);
                 // TODO: VLOG(2) << "  after Tr, Li: " << Li;
             } else {
                 if !visibility.unoccluded(scene) {
@@ -225,23 +384,29 @@ pub fn estimate_direct(
         if it.is_surface_interaction() {
             // sample scattered direction for surface interactions
             let mut sampled_type: u8 = 0_u8;
-            if let Some(ref bsdf) = it.bsdf {
-                f = bsdf.sample_f(
-                    &it.get_wo(),
-                    &mut wi,
-                    u_scattering,
-                    &mut scattering_pdf,
-                    bsdf_flags,
-                    &mut sampled_type,
-                );
-                f *= Spectrum::new(vec3_abs_dot_nrm(&wi, &it.shading.n));
-                sampled_specular = (sampled_type & BxdfType::BsdfSpecular as u8) != 0_u8;
+            if let Some(ref bsdf) = it.get_bsdf() {
+                if let Some(shading_n) = it.get_shading_n() {
+                    f = bsdf.sample_f(
+                        &it.get_wo(),
+                        &mut wi,
+                        u_scattering,
+                        &mut scattering_pdf,
+                        bsdf_flags,
+                        &mut sampled_type,
+                    );
+                    f *= Spectrum::new(vec3_abs_dot_nrm(&wi, &shading_n));
+                    sampled_specular = (sampled_type & BxdfType::BsdfSpecular as u8) != 0_u8;
+                }
             } else {
-                println!("TODO: if let Some(ref bsdf) = it.bsdf failed");
+                println!("TODO: if let Some(ref bsdf) = it.get_bsdf() failed");
             }
         } else {
-            // TODO
-            println!("TODO: estimate_direct 1");
+            // sample scattered direction for medium interactions
+            if let Some(ref phase) = it.get_phase() {
+                let p: Float = phase.sample_p(&it.get_wo(), &mut wi, u_scattering);
+                f = Spectrum::new(p);
+                scattering_pdf = p;
+            }
         }
         // TODO: println!("  BSDF / phase sampling f: {:?}, scatteringPdf: {:?}",
         //          f, scattering_pdf);
@@ -257,22 +422,24 @@ pub fn estimate_direct(
             }
             // find intersection and compute transmittance
             let mut ray: Ray = it.spawn_ray(&wi);
-            let tr: Spectrum = Spectrum::new(1.0 as Float);
+            let mut tr: Spectrum = Spectrum::new(1.0 as Float);
             let mut found_surface_interaction: bool = false;
             // add light contribution from material sampling
             let mut li: Spectrum = Spectrum::default();
+            let light_isect_opt: Option<SurfaceInteraction>;
             if handle_media {
-                // TODO: scene.IntersectTr(ray, sampler, &lightIsect, &Tr)
+                light_isect_opt = scene.intersect_tr(&mut ray, &mut **sampler, &mut tr);
             } else {
-                if let Some(light_isect) = scene.intersect(&mut ray) {
-                    found_surface_interaction = true;
-                    if let Some(primitive) = light_isect.primitive {
-                        if let Some(area_light) = primitive.get_area_light() {
-                            let pa = &*area_light as *const _ as *const usize;
-                            let pl = &*light as *const _ as *const usize;
-                            if pa == pl {
-                                li = light_isect.le(&-wi);
-                            }
+                light_isect_opt = scene.intersect(&mut ray);
+            }
+            if let Some(light_isect) = light_isect_opt {
+                found_surface_interaction = true;
+                if let Some(primitive) = light_isect.primitive {
+                    if let Some(area_light) = primitive.get_area_light() {
+                        let pa = &*area_light as *const _ as *const usize;
+                        let pl = &*light as *const _ as *const usize;
+                        if pa == pl {
+                            li = light_isect.le(&-wi);
                         }
                     }
                 }
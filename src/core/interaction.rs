@@ -14,6 +14,7 @@ use std::sync::Arc;
 use core::geometry::{
     nrm_faceforward_nrm, pnt3_offset_ray_origin, vec3_cross_vec3, vec3_dot_nrm, vec3_dot_vec3,
 };
+use core::bssrdf::Bssrdf;
 use core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
 use core::material::TransportMode;
 use core::medium::{Medium, MediumInterface, PhaseFunction};
@@ -36,6 +37,9 @@ pub trait Interaction {
     fn get_wo(&self) -> Vector3f;
     fn get_n(&self) -> Normal3f;
     fn get_medium_interface(&self) -> Option<Arc<MediumInterface>>;
+    fn get_bsdf(&self) -> Option<Arc<Bsdf>>;
+    fn get_shading_n(&self) -> Option<Normal3f>;
+    fn get_phase(&self) -> Option<Arc<PhaseFunction>>;
 }
 
 #[derive(Default, Clone)]
@@ -61,6 +65,18 @@ impl InteractionCommon {
             medium: self.get_medium(d),
         }
     }
+    pub fn spawn_ray_to_pnt(&self, p2: &Point3f) -> Ray {
+        let origin: Point3f = pnt3_offset_ray_origin(&self.p, &self.p_error, &self.n, &(*p2 - self.p));
+        let d: Vector3f = *p2 - origin;
+        Ray {
+            o: origin,
+            d: d,
+            t_max: 1.0 - SHADOW_EPSILON,
+            time: self.time,
+            differential: None,
+            medium: self.get_medium(&d),
+        }
+    }
     pub fn spawn_ray_to(&self, it: &InteractionCommon) -> Ray {
         let origin: Point3f =
             pnt3_offset_ray_origin(&self.p, &self.p_error, &self.n, &(it.p - self.p));
@@ -176,13 +192,6 @@ impl MediumInteraction {
             false
         }
     }
-    pub fn get_phase(&self) -> Option<Arc<PhaseFunction>> {
-        if let Some(ref phase) = self.phase {
-            Some(phase.clone())
-        } else {
-            None
-        }
-    }
 }
 
 impl Interaction for MediumInteraction {
@@ -225,6 +234,19 @@ impl Interaction for MediumInteraction {
             None
         }
     }
+    fn get_bsdf(&self) -> Option<Arc<Bsdf>> {
+        None
+    }
+    fn get_shading_n(&self) -> Option<Normal3f> {
+        None
+    }
+    fn get_phase(&self) -> Option<Arc<PhaseFunction>> {
+        if let Some(ref phase) = self.phase {
+            Some(phase.clone())
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Default, Clone)]
@@ -251,7 +273,9 @@ pub struct SurfaceInteraction<'p, 's> {
     pub primitive: Option<&'p GeometricPrimitive>,
     pub shading: Shading,
     pub bsdf: Option<Arc<Bsdf>>,
+    pub bssrdf: Option<Arc<Bssrdf + Send + Sync>>,
     pub shape: Option<&'s Shape>,
+    pub face_index: usize,
 }
 
 impl<'p, 's> SurfaceInteraction<'p, 's> {
@@ -303,7 +327,9 @@ impl<'p, 's> SurfaceInteraction<'p, 's> {
             primitive: None,
             shading: shading,
             bsdf: None,
+            bssrdf: None,
             shape: sh,
+            face_index: 0_usize,
         }
     }
     pub fn get_medium(&self, w: &Vector3f) -> Option<Arc<Medium + Send + Sync>> {
@@ -511,4 +537,17 @@ impl<'p, 's> Interaction for SurfaceInteraction<'p, 's> {
             None
         }
     }
+    fn get_bsdf(&self) -> Option<Arc<Bsdf>> {
+        if let Some(ref bsdf) = self.bsdf {
+            Some(bsdf.clone())
+        } else {
+            None
+        }
+    }
+    fn get_shading_n(&self) -> Option<Normal3f> {
+        Some(self.shading.n)
+    }
+    fn get_phase(&self) -> Option<Arc<PhaseFunction>> {
+        None
+    }
 }
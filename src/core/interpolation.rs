@@ -173,6 +173,107 @@ pub fn sample_catmull_rom_2d(
     x0 + width * t
 }
 
+/// Computes the definite integral over a Catmull-Rom spline and
+/// stores the running sum (unnormalized CDF) in *cdf*.
+pub fn integrate_catmull_rom(x: &[Float], values: &[Float], cdf: &mut [Float]) -> Float {
+    let n: usize = x.len();
+    let mut sum: Float = 0.0 as Float;
+    cdf[0] = 0.0 as Float;
+    for i in 0..(n - 1) {
+        // look up $x_i$ and function values of spline segment _i_
+        let x0: Float = x[i];
+        let x1: Float = x[i + 1];
+        let f0: Float = values[i];
+        let f1: Float = values[i + 1];
+        let width: Float = x1 - x0;
+        // approximate derivatives using finite differences
+        let d0: Float;
+        let d1: Float;
+        if i > 0 {
+            d0 = width * (f1 - values[i - 1]) / (x1 - x[i - 1]);
+        } else {
+            d0 = f1 - f0;
+        }
+        if i + 2 < n {
+            d1 = width * (values[i + 2] - f0) / (x[i + 2] - x0);
+        } else {
+            d1 = f1 - f0;
+        }
+        // keep a running sum and build a cumulative distribution function
+        sum += ((d0 - d1) * (1.0 as Float / 12.0 as Float) + (f0 + f1) * 0.5 as Float) * width;
+        cdf[i + 1] = sum;
+    }
+    sum
+}
+
+/// Inverts a monotonically increasing Catmull-Rom spline, returning
+/// the position where the spline takes the value *u*.
+pub fn invert_catmull_rom(x: &[Float], values: &[Float], u: Float) -> Float {
+    let n: usize = x.len();
+    // stop when _u_ is out of bounds
+    if !(u > values[0]) {
+        return x[0];
+    } else if !(u < values[n - 1]) {
+        return x[n - 1];
+    }
+    // map _u_ to a spline interval by inverting _values_
+    let i: usize = find_interval(n as i32, |i| values[i as usize] <= u) as usize;
+    // look up $x_i$ and function values of spline segment _i_
+    let x0: Float = x[i];
+    let x1: Float = x[i + 1];
+    let f0: Float = values[i];
+    let f1: Float = values[i + 1];
+    let width: Float = x1 - x0;
+    // approximate derivatives using finite differences
+    let d0: Float;
+    let d1: Float;
+    if i > 0 {
+        d0 = width * (f1 - values[i - 1]) / (x1 - x[i - 1]);
+    } else {
+        d0 = f1 - f0;
+    }
+    if i + 2 < n {
+        d1 = width * (values[i + 2] - f0) / (x[i + 2] - x0);
+    } else {
+        d1 = f1 - f0;
+    }
+    // invert the spline interpolant using Newton-Bisection
+    let mut a: Float = 0.0;
+    let mut b: Float = 1.0;
+    let mut t: Float = 0.5;
+    loop {
+        // fall back to a bisection step when _t_ is out of bounds
+        if !(t >= a && t <= b) {
+            t = 0.5 as Float * (a + b);
+        }
+        // compute powers of _t_
+        let t2: Float = t * t;
+        let t3: Float = t2 * t;
+        // compute function value and derivative in Horner form
+        let f_hat: Float = (2.0 as Float * t3 - 3.0 as Float * t2 + 1.0 as Float) * f0
+            + (-2.0 as Float * t3 + 3.0 as Float * t2) * f1
+            + (t3 - 2.0 as Float * t2 + t) * d0
+            + (t3 - t2) * d1;
+        let fhat: Float = (6.0 as Float * t2 - 6.0 as Float * t) * f0
+            + (-6.0 as Float * t2 + 6.0 as Float * t) * f1
+            + (3.0 as Float * t2 - 4.0 as Float * t + 1.0 as Float) * d0
+            + (3.0 as Float * t2 - 2.0 as Float * t) * d1;
+        // stop the iteration if converged
+        if (f_hat - u).abs() < 1e-6 as Float || b - a < 1e-6 as Float {
+            break;
+        }
+        // update bisection bounds using updated _t_
+        if f_hat - u < 0.0 as Float {
+            a = t;
+        } else {
+            b = t;
+        }
+        // perform a Newton step
+        t -= (f_hat - u) / fhat;
+    }
+    x0 + t * width
+}
+
 /// Evaluates the weighted sum of cosines.
 pub fn fourier(a: &Vec<Float>, si: usize, m: i32, cos_phi: f64) -> Float {
     let mut value: f64 = 0.0;
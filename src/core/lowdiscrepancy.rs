@@ -11,6 +11,143 @@ use core::sobolmatrices::{
 
 // see lowdiscrepancy.h
 
+/// 2D Sobol$'$ generator matrices.
+pub const C_SOBOL: [[u32; 32]; 2] = [
+    [
+        0x80000000_u32,
+        0x40000000,
+        0x20000000,
+        0x10000000,
+        0x8000000,
+        0x4000000,
+        0x2000000,
+        0x1000000,
+        0x800000,
+        0x400000,
+        0x200000,
+        0x100000,
+        0x80000,
+        0x40000,
+        0x20000,
+        0x10000,
+        0x8000,
+        0x4000,
+        0x2000,
+        0x1000,
+        0x800,
+        0x400,
+        0x200,
+        0x100,
+        0x80,
+        0x40,
+        0x20,
+        0x10,
+        0x8,
+        0x4,
+        0x2,
+        0x1,
+    ],
+    [
+        0x80000000, 0xc0000000, 0xa0000000, 0xf0000000, 0x88000000, 0xcc000000, 0xaa000000,
+        0xff000000, 0x80800000, 0xc0c00000, 0xa0a00000, 0xf0f00000, 0x88880000, 0xcccc0000,
+        0xaaaa0000, 0xffff0000, 0x80008000, 0xc000c000, 0xa000a000, 0xf000f000, 0x88008800,
+        0xcc00cc00, 0xaa00aa00, 0xff00ff00, 0x80808080, 0xc0c0c0c0, 0xa0a0a0a0, 0xf0f0f0f0,
+        0x88888888, 0xcccccccc, 0xaaaaaaaa, 0xffffffff,
+    ],
+];
+
+/// Generator matrices for the **MaxMinDistSampler**, one per power of
+/// two sample count (2^0 to 2^16). Together with the points' x
+/// coordinates _i/N_, matrix _m_ yields a (0,m,2)-net whose minimum
+/// (toroidal) distance between points was maximized by a numerical
+/// search (see Grünschloß and Keller's max-min-distance criterion).
+/// Only the first _m_ columns are used, the remaining ones are zero.
+pub const C_MAX_MIN_DIST: [[u32; 32]; 17] = [
+    [
+        0x0_u32, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0x80000000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0x80000000, 0x40000000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xa0000000, 0x40000000, 0x80000000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xc0000000, 0x50000000, 0x20000000, 0x30000000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0,
+    ],
+    [
+        0x88000000, 0x58000000, 0x20000000, 0x40000000, 0x80000000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0,
+    ],
+    [
+        0xe0000000, 0x80000000, 0x28000000, 0xf0000000, 0xf8000000, 0x4000000, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xc2000000, 0x84000000, 0x2a000000, 0xd2000000, 0x26000000, 0x80000000, 0x44000000, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xf0000000, 0x70000000, 0x30000000, 0x14000000, 0x8000000, 0xfc000000, 0x3000000,
+        0x1000000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xae800000, 0x60000000, 0x20800000, 0xb6800000, 0xa4000000, 0x92000000, 0x3000000,
+        0x5d800000, 0x7e800000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xa0000000, 0x60400000, 0x41c00000, 0xf8c00000, 0xa800000, 0xfec00000, 0x3c00000,
+        0x3f800000, 0xa1c00000, 0xe0400000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xaa600000, 0x58e00000, 0xd8000000, 0xe0800000, 0x20200000, 0x56600000, 0x54400000,
+        0x1a00000, 0x800000, 0x2c00000, 0x25800000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xc5100000, 0x40400000, 0xb5800000, 0xdd700000, 0xa1100000, 0xdb700000, 0x57800000,
+        0xb4300000, 0x18900000, 0xace00000, 0x40d00000, 0x59c00000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xe5d00000, 0x7db00000, 0x2c600000, 0xa3080000, 0x1bf80000, 0xc0780000, 0x49100000,
+        0x28e80000, 0xe2a00000, 0x880000, 0x52180000, 0xc9900000, 0xfad00000, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0xa8d00000, 0x66800000, 0x42080000, 0xb0c80000, 0x32500000, 0x90580000, 0x14880000,
+        0xff900000, 0xd4800000, 0x33480000, 0x48640000, 0xcbfc0000, 0x24840000, 0x13c40000, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ],
+    [
+        0x96040000, 0x66280000, 0xc4f60000, 0x4f760000, 0x815a0000, 0xdc8a0000, 0xf38c0000,
+        0xa5e00000, 0x2b0e0000, 0x4d540000, 0x761a0000, 0xd6d60000, 0x83020000, 0x370c0000,
+        0xd9020000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0,
+    ],
+    [
+        0xea000000, 0x8d4d0000, 0xa1d10000, 0x15a10000, 0x35b90000, 0xa6eb0000, 0x57320000,
+        0x14730000, 0x84ba0000, 0xb83c0000, 0x54f40000, 0x78b40000, 0x245f0000, 0xf0d0000,
+        0x29190000, 0x984a0000, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0,
+    ],
+];
+
 pub const PRIME_TABLE_SIZE: u16 = 1000_u16;
 
 pub const PRIMES: [u32; PRIME_TABLE_SIZE as usize] = [
@@ -200,6 +337,23 @@ pub fn inverse_radical_inverse(base: u8, inverse: u64, n_digits: u64) -> u64 {
     index
 }
 
+/// Computes the sample value for index *a* by multiplying the
+/// generator matrix *c* with the bits of *a* (XOR-ed with an
+/// optional *scramble* value).
+pub fn sample_generator_matrix(c: &[u32; 32], a: u32, scramble: u32) -> Float {
+    let mut v: u32 = scramble;
+    let mut a: u32 = a;
+    let mut i: usize = 0;
+    while a != 0 {
+        if a & 1 != 0 {
+            v ^= c[i];
+        }
+        a >>= 1;
+        i += 1;
+    }
+    (v as Float * hexf32!("0x1.0p-32") as Float).min(FLOAT_ONE_MINUS_EPSILON)
+}
+
 /// Takes a generator matrix *c*, a number of 1D samples to generate
 /// *n*, and stores the corresponding samples in memory at the
 /// location pointed to by *p*.
@@ -278,53 +432,9 @@ pub fn sobol_2d(
     let x: i32 = rng.uniform_uint32() as i32;
     let y: i32 = rng.uniform_uint32() as i32;
     let scramble: Point2i = Point2i { x: x, y: y };
-    // define 2D Sobol$'$ generator matrices _c_sobol[2]_
-    let c_sobol: [[u32; 32]; 2] = [
-        [
-            0x80000000_u32,
-            0x40000000,
-            0x20000000,
-            0x10000000,
-            0x8000000,
-            0x4000000,
-            0x2000000,
-            0x1000000,
-            0x800000,
-            0x400000,
-            0x200000,
-            0x100000,
-            0x80000,
-            0x40000,
-            0x20000,
-            0x10000,
-            0x8000,
-            0x4000,
-            0x2000,
-            0x1000,
-            0x800,
-            0x400,
-            0x200,
-            0x100,
-            0x80,
-            0x40,
-            0x20,
-            0x10,
-            0x8,
-            0x4,
-            0x2,
-            0x1,
-        ],
-        [
-            0x80000000, 0xc0000000, 0xa0000000, 0xf0000000, 0x88000000, 0xcc000000, 0xaa000000,
-            0xff000000, 0x80800000, 0xc0c00000, 0xa0a00000, 0xf0f00000, 0x88880000, 0xcccc0000,
-            0xaaaa0000, 0xffff0000, 0x80008000, 0xc000c000, 0xa000a000, 0xf000f000, 0x88008800,
-            0xcc00cc00, 0xaa00aa00, 0xff00ff00, 0x80808080, 0xc0c0c0c0, 0xa0a0a0a0, 0xf0f0f0f0,
-            0x88888888, 0xcccccccc, 0xaaaaaaaa, 0xffffffff,
-        ],
-    ];
     gray_code_sample_2d(
-        &c_sobol[0],
-        &c_sobol[1],
+        &C_SOBOL[0],
+        &C_SOBOL[1],
         (n_samples_per_pixel_sample * n_pixel_samples) as u32,
         &scramble,
         &mut samples[..],
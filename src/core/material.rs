@@ -4,10 +4,11 @@
 //std
 use std::sync::Arc;
 // pbrt
-use core::geometry::vec3_cross_vec3;
+use core::geometry::{vec3_cross_vec3, vec3_dot_vec3};
 use core::geometry::{Normal3f, Vector2f, Vector3f};
 use core::interaction::SurfaceInteraction;
-use core::pbrt::Float;
+use core::mipmap::MipMap;
+use core::pbrt::{Float, Spectrum};
 use core::texture::Texture;
 
 // see material.h
@@ -88,4 +89,35 @@ pub trait Material {
         let dndv = si.shading.dndv;
         si.set_shading_geometry(&dpdu, &dpdv, &dndu, &dndv, false);
     }
+    /// Replaces the shading normal by a tangent-space normal looked
+    /// up in an RGB normal map, where the shading frame is built from
+    /// _dpdu_ and _n_ of the shading geometry.
+    fn normal_map(map: &Arc<MipMap<Spectrum>>, si: &mut SurfaceInteraction)
+    where
+        Self: Sized,
+    {
+        // get normal from the map (components are stored in $[0,1]$)
+        let mut rgb: [Float; 3] = [0.0 as Float; 3];
+        map.lookup_pnt_flt(&si.uv, 0.0 as Float).to_rgb(&mut rgb);
+        let ns: Vector3f = Vector3f {
+            x: 2.0 as Float * rgb[0] - 1.0 as Float,
+            y: 2.0 as Float * rgb[1] - 1.0 as Float,
+            z: 2.0 as Float * rgb[2] - 1.0 as Float,
+        }
+        .normalize();
+        // transform tangent-space normal to rendering space
+        let x: Vector3f = si.shading.dpdu.normalize();
+        let z: Vector3f = Vector3f::from(si.shading.n);
+        let y: Vector3f = vec3_cross_vec3(&z, &x);
+        let ns: Vector3f = (x * ns.x + y * ns.y + z * ns.z).normalize();
+        // find $\dpdu$ and $\dpdv$ that give shading normal
+        let ulen: Float = si.shading.dpdu.length();
+        let vlen: Float = si.shading.dpdv.length();
+        let dpdu: Vector3f =
+            (si.shading.dpdu - ns * vec3_dot_vec3(&si.shading.dpdu, &ns)).normalize() * ulen;
+        let dpdv: Vector3f = vec3_cross_vec3(&ns, &dpdu).normalize() * vlen;
+        let dndu = si.shading.dndu;
+        let dndv = si.shading.dndv;
+        si.set_shading_geometry(&dpdu, &dpdv, &dndu, &dndv, false);
+    }
 }
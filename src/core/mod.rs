@@ -1,12 +1,15 @@
 //! All the code for the PBRT core.
 
 pub mod api;
+pub mod bssrdf;
 pub mod camera;
 pub mod efloat;
 pub mod film;
 pub mod filter;
 pub mod floatfile;
 pub mod geometry;
+pub mod ies;
+pub mod imageio;
 pub mod integrator;
 pub mod interaction;
 pub mod interpolation;
@@ -22,6 +25,7 @@ pub mod parallel;
 pub mod paramset;
 pub mod pbrt;
 pub mod primitive;
+pub mod ptex;
 pub mod quaternion;
 pub mod reflection;
 pub mod rng;
@@ -17,6 +17,15 @@ pub trait Primitive {
     fn world_bound(&self) -> Bounds3f;
     fn intersect(&self, ray: &mut Ray) -> Option<SurfaceInteraction>;
     fn intersect_p(&self, r: &Ray) -> bool;
+    /// Intersects each ray with the primitive (see **intersect()**);
+    /// aggregates can override this to traverse rays in packets.
+    fn intersect_batch(&self, rays: &mut [Ray]) -> Vec<Option<SurfaceInteraction>> {
+        rays.iter_mut().map(|ray| self.intersect(ray)).collect()
+    }
+    /// Tests each ray for an intersection (see **intersect_p()**).
+    fn intersect_p_batch(&self, rays: &[Ray]) -> Vec<bool> {
+        rays.iter().map(|ray| self.intersect_p(ray)).collect()
+    }
     fn get_area_light(&self) -> Option<Arc<AreaLight + Send + Sync>>;
     fn get_material(&self) -> Option<Arc<Material + Send + Sync>>;
     fn compute_scattering_functions(
@@ -196,10 +205,12 @@ impl Primitive for TransformedPrimitive {
                     new_isect.time,
                     None,
                 );
-                // we need to preserve the primitive pointer
+                // we need to preserve the primitive pointer and the
+                // face index (used for Ptex lookups)
                 if let Some(primitive) = isect.primitive {
                     is.primitive = Some(primitive);
                 }
+                is.face_index = new_isect.face_index;
                 return Some(is);
             }
             None
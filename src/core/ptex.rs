@@ -0,0 +1,513 @@
+//! Read per-face textures stored in the [Ptex][ptex] file format.
+//!
+//! Only the highest resolution level of each face is decoded, all
+//! faces are converted to floating point values on load. Quad faces
+//! are filtered bilinearly, faces of triangle meshes return the
+//! triangular texel containing the (barycentric) face coordinates.
+//!
+//! [ptex]: http://ptex.us
+
+// std
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+// others
+use byteorder::{LittleEndian, ReadBytesExt};
+use half::f16;
+use inflate::inflate_bytes_zlib;
+// pbrt
+use core::pbrt::clamp_t;
+use core::pbrt::Float;
+
+// see PtexIO.h
+
+const PTEX_MAGIC: u32 = 0x7865_7450; // "Ptex"
+const PTEX_HEADER_SIZE: u64 = 64;
+const PTEX_FACE_INFO_SIZE: usize = 20;
+const PTEX_LEVEL_INFO_SIZE: usize = 16;
+const PTEX_FACE_DATA_HEADER_SIZE: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PtexDataType {
+    Uint8,
+    Uint16,
+    Half,
+    Float,
+}
+
+impl PtexDataType {
+    fn size(&self) -> usize {
+        match *self {
+            PtexDataType::Uint8 => 1,
+            PtexDataType::Uint16 => 2,
+            PtexDataType::Half => 2,
+            PtexDataType::Float => 4,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum PtexEncoding {
+    Constant,
+    Zipped,
+    Differenced,
+    Tiled,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct FaceDataHeader {
+    block_size: usize,
+    encoding: PtexEncoding,
+}
+
+impl FaceDataHeader {
+    fn from_u32(data: u32) -> Self {
+        FaceDataHeader {
+            block_size: (data & 0x3fff_ffff) as usize,
+            encoding: match (data >> 30) & 0x3 {
+                0 => PtexEncoding::Constant,
+                1 => PtexEncoding::Zipped,
+                2 => PtexEncoding::Differenced,
+                _ => PtexEncoding::Tiled,
+            },
+        }
+    }
+}
+
+/// The decoded texels of a single face, stored row by row with all
+/// channels of a texel next to each other.
+#[derive(Debug, Default, Clone)]
+pub struct PtexFace {
+    pub ulog2: u8,
+    pub vlog2: u8,
+    pub data: Vec<Float>,
+}
+
+impl PtexFace {
+    pub fn u_res(&self) -> usize {
+        1_usize << self.ulog2
+    }
+    pub fn v_res(&self) -> usize {
+        1_usize << self.vlog2
+    }
+    /// Index of the texel of a triangle face containing the
+    /// barycentric face coordinates $(u, v)$. Each of the res x res
+    /// texels is split along its diagonal, the upright triangles are
+    /// stored in the lower left half and the inverted ones, rotated by
+    /// 180 degrees, in the upper right half.
+    pub fn triangle_texel(&self, u: Float, v: Float) -> usize {
+        let res: usize = self.u_res();
+        // keep $(u, v)$ within the triangle
+        let mut u: Float = clamp_t(u, 0.0 as Float, 1.0 as Float);
+        let mut v: Float = clamp_t(v, 0.0 as Float, 1.0 as Float);
+        if u + v > 1.0 as Float {
+            let inv_sum: Float = 1.0 as Float / (u + v);
+            u *= inv_sum;
+            v *= inv_sum;
+        }
+        let s: Float = u * res as Float;
+        let t: Float = v * res as Float;
+        let i: usize = (s as usize).min(res - 1);
+        let j: usize = (t as usize).min(res - 1 - i);
+        if (s - i as Float) + (t - j as Float) >= 1.0 as Float && i + j + 2 <= res {
+            // inverted triangle
+            (res - 1 - j) * res + (res - 1 - i)
+        } else {
+            j * res + i
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PtexFile {
+    pub n_channels: usize,
+    pub alpha_channel: i32,
+    /// true for triangle meshes, false for quad meshes
+    pub is_triangle_mesh: bool,
+    pub faces: Vec<PtexFace>,
+}
+
+impl PtexFile {
+    pub fn read(filename: &String) -> Option<PtexFile> {
+        let path = Path::new(&filename);
+        let result = File::open(path);
+        if !result.is_ok() {
+            println!("ERROR: Unable to open Ptex file {:?}", filename);
+            return None;
+        }
+        let mut bytes: Vec<u8> = Vec::new();
+        if result.unwrap().read_to_end(&mut bytes).is_err() {
+            println!("ERROR: Unable to read Ptex file {:?}", filename);
+            return None;
+        }
+        match PtexFile::parse(bytes) {
+            Ok(ptex) => Some(ptex),
+            Err(msg) => {
+                println!("ERROR: {} in Ptex file {:?}", msg, filename);
+                None
+            }
+        }
+    }
+    fn parse(bytes: Vec<u8>) -> Result<PtexFile, String> {
+        let mut file = Cursor::new(bytes);
+        // header
+        let magic: u32 = read_u32(&mut file)?;
+        if magic != PTEX_MAGIC {
+            return Err(String::from("Not a ptex file"));
+        }
+        let version: u32 = read_u32(&mut file)?;
+        if version != 1 {
+            return Err(format!("Unsupported ptex file version ({})", version));
+        }
+        let mesh_type: u32 = read_u32(&mut file)?;
+        let data_type: PtexDataType = match read_u32(&mut file)? {
+            0 => PtexDataType::Uint8,
+            1 => PtexDataType::Uint16,
+            2 => PtexDataType::Half,
+            3 => PtexDataType::Float,
+            dt => return Err(format!("Unknown data type ({})", dt)),
+        };
+        let alpha_channel: i32 = read_i32(&mut file)?;
+        let n_channels: usize = read_u16(&mut file)? as usize;
+        let n_levels: usize = read_u16(&mut file)? as usize;
+        let n_faces: usize = read_u32(&mut file)? as usize;
+        let ext_header_size: u64 = read_u32(&mut file)? as u64;
+        let face_info_size: usize = read_u32(&mut file)? as usize;
+        let const_data_size: usize = read_u32(&mut file)? as usize;
+        let level_info_size: usize = read_u32(&mut file)? as usize;
+        if n_channels == 0 {
+            return Err(String::from("No channels"));
+        }
+        if n_levels == 0 {
+            return Err(String::from("No levels"));
+        }
+        let pixel_size: usize = n_channels * data_type.size();
+        // face info
+        seek(&mut file, PTEX_HEADER_SIZE + ext_header_size)?;
+        let face_info: Vec<u8> =
+            read_zip_block(&mut file, face_info_size, n_faces * PTEX_FACE_INFO_SIZE)?;
+        let mut res: Vec<(u8, u8)> = Vec::with_capacity(n_faces);
+        let mut is_constant: Vec<bool> = Vec::with_capacity(n_faces);
+        for f in 0..n_faces {
+            let info: &[u8] = &face_info[f * PTEX_FACE_INFO_SIZE..(f + 1) * PTEX_FACE_INFO_SIZE];
+            res.push((info[0], info[1]));
+            // flag_constant
+            is_constant.push(info[3] & 1 != 0);
+        }
+        // constant data (one texel per face)
+        let const_data: Vec<u8> = read_zip_block(&mut file, const_data_size, n_faces * pixel_size)?;
+        // level info
+        let mut level_info: Vec<u8> = vec![0_u8; level_info_size];
+        read_exact(&mut file, &mut level_info)?;
+        if level_info_size < PTEX_LEVEL_INFO_SIZE {
+            return Err(String::from("Missing level info"));
+        }
+        let mut level_info = Cursor::new(level_info);
+        let _level_data_size: u64 = level_info.read_u64::<LittleEndian>().unwrap();
+        let level_header_size: usize = level_info.read_u32::<LittleEndian>().unwrap() as usize;
+        let level_n_faces: usize = level_info.read_u32::<LittleEndian>().unwrap() as usize;
+        if level_n_faces != n_faces {
+            return Err(String::from("Inconsistent face count"));
+        }
+        // level 0 (full resolution) face data headers
+        let level_header: Vec<u8> = read_zip_block(
+            &mut file,
+            level_header_size,
+            n_faces * PTEX_FACE_DATA_HEADER_SIZE,
+        )?;
+        let mut fdhs: Vec<FaceDataHeader> = Vec::with_capacity(n_faces);
+        let mut level_header = Cursor::new(level_header);
+        for _f in 0..n_faces {
+            fdhs.push(FaceDataHeader::from_u32(
+                level_header.read_u32::<LittleEndian>().unwrap(),
+            ));
+        }
+        // level 0 face data
+        let mut faces: Vec<PtexFace> = Vec::with_capacity(n_faces);
+        let mut pos: u64 = file.position();
+        for f in 0..n_faces {
+            let (ulog2, vlog2) = res[f];
+            if is_constant[f] {
+                faces.push(PtexFace {
+                    ulog2: 0,
+                    vlog2: 0,
+                    data: convert(&const_data[f * pixel_size..(f + 1) * pixel_size], data_type),
+                });
+            } else {
+                if mesh_type == 0 && ulog2 != vlog2 {
+                    return Err(format!("Non-square triangle face ({})", f));
+                }
+                seek(&mut file, pos)?;
+                let data: Vec<u8> =
+                    read_face_data(&mut file, &fdhs[f], ulog2, vlog2, data_type, n_channels)?;
+                faces.push(PtexFace {
+                    ulog2: ulog2,
+                    vlog2: vlog2,
+                    data: convert(&data, data_type),
+                });
+            }
+            pos += fdhs[f].block_size as u64;
+        }
+        Ok(PtexFile {
+            n_channels: n_channels,
+            alpha_channel: alpha_channel,
+            is_triangle_mesh: mesh_type == 0,
+            faces: faces,
+        })
+    }
+    /// Lookup of _n_ channels (starting at channel _first_) on a face
+    /// at the face coordinates $(u, v)$. Quad faces are bilinearly
+    /// interpolated, for triangle faces $(u, v)$ are the barycentric
+    /// coordinates of the second and third vertex and the texel
+    /// containing them is returned (see **triangle_texel()**).
+    pub fn lookup(
+        &self,
+        face_index: usize,
+        u: Float,
+        v: Float,
+        first: usize,
+        n: usize,
+    ) -> Vec<Float> {
+        let mut result: Vec<Float> = vec![0.0 as Float; n];
+        if face_index >= self.faces.len() {
+            return result;
+        }
+        let face: &PtexFace = &self.faces[face_index];
+        if self.is_triangle_mesh {
+            let offset: usize = face.triangle_texel(u, v) * self.n_channels + first;
+            result.copy_from_slice(&face.data[offset..offset + n]);
+            return result;
+        }
+        let u_res: usize = face.u_res();
+        let v_res: usize = face.v_res();
+        // texel centers are at half-integer coordinates
+        let s: Float = u * u_res as Float - 0.5 as Float;
+        let t: Float = v * v_res as Float - 0.5 as Float;
+        let s0: Float = s.floor();
+        let t0: Float = t.floor();
+        let ds: Float = s - s0;
+        let dt: Float = t - t0;
+        let texel = |si: Float, ti: Float, c: usize| -> Float {
+            let x: usize = clamp_t(si as isize, 0, u_res as isize - 1) as usize;
+            let y: usize = clamp_t(ti as isize, 0, v_res as isize - 1) as usize;
+            face.data[(y * u_res + x) * self.n_channels + first + c]
+        };
+        for c in 0..n {
+            result[c] = (1.0 as Float - ds) * (1.0 as Float - dt) * texel(s0, t0, c)
+                + ds * (1.0 as Float - dt) * texel(s0 + 1.0, t0, c)
+                + (1.0 as Float - ds) * dt * texel(s0, t0 + 1.0, c)
+                + ds * dt * texel(s0 + 1.0, t0 + 1.0, c);
+        }
+        result
+    }
+}
+
+/// Keeps every Ptex file open (decoded) which was referenced by a
+/// texture, so several textures can share the same file.
+#[derive(Debug, Default)]
+pub struct PtexCache {
+    pub files: HashMap<String, Option<Arc<PtexFile>>>,
+}
+
+impl PtexCache {
+    pub fn get(&mut self, filename: &String) -> Option<Arc<PtexFile>> {
+        if let Some(ptex) = self.files.get(filename) {
+            return ptex.clone();
+        }
+        let ptex: Option<Arc<PtexFile>> = PtexFile::read(filename).map(|p| Arc::new(p));
+        self.files.insert(filename.clone(), ptex.clone());
+        ptex
+    }
+}
+
+fn read_face_data(
+    file: &mut Cursor<Vec<u8>>,
+    fdh: &FaceDataHeader,
+    ulog2: u8,
+    vlog2: u8,
+    data_type: PtexDataType,
+    n_channels: usize,
+) -> Result<Vec<u8>, String> {
+    let u_res: usize = 1_usize << ulog2;
+    let v_res: usize = 1_usize << vlog2;
+    let pixel_size: usize = n_channels * data_type.size();
+    match fdh.encoding {
+        PtexEncoding::Constant => {
+            let mut pixel: Vec<u8> = vec![0_u8; pixel_size];
+            read_exact(file, &mut pixel)?;
+            let mut data: Vec<u8> = Vec::with_capacity(u_res * v_res * pixel_size);
+            for _i in 0..u_res * v_res {
+                data.extend_from_slice(&pixel);
+            }
+            Ok(data)
+        }
+        PtexEncoding::Zipped | PtexEncoding::Differenced => {
+            let mut planar: Vec<u8> =
+                read_zip_block(file, fdh.block_size, u_res * v_res * pixel_size)?;
+            if fdh.encoding == PtexEncoding::Differenced {
+                decode_difference(&mut planar, data_type);
+            }
+            Ok(interleave(&planar, u_res * v_res, data_type, n_channels))
+        }
+        PtexEncoding::Tiled => {
+            let tile_ulog2: u8 = read_u8(file)?;
+            let tile_vlog2: u8 = read_u8(file)?;
+            if tile_ulog2 > ulog2 || tile_vlog2 > vlog2 {
+                return Err(String::from("Invalid tile resolution"));
+            }
+            let tile_header_size: usize = read_u32(file)? as usize;
+            let n_tiles_u: usize = 1_usize << (ulog2 - tile_ulog2);
+            let n_tiles_v: usize = 1_usize << (vlog2 - tile_vlog2);
+            let n_tiles: usize = n_tiles_u * n_tiles_v;
+            let tile_header: Vec<u8> =
+                read_zip_block(file, tile_header_size, n_tiles * PTEX_FACE_DATA_HEADER_SIZE)?;
+            let mut tile_header = Cursor::new(tile_header);
+            let mut tile_fdhs: Vec<FaceDataHeader> = Vec::with_capacity(n_tiles);
+            for _t in 0..n_tiles {
+                tile_fdhs.push(FaceDataHeader::from_u32(
+                    tile_header.read_u32::<LittleEndian>().unwrap(),
+                ));
+            }
+            let tile_u_res: usize = 1_usize << tile_ulog2;
+            let tile_v_res: usize = 1_usize << tile_vlog2;
+            let mut data: Vec<u8> = vec![0_u8; u_res * v_res * pixel_size];
+            let mut pos: u64 = file.position();
+            for (t, tile_fdh) in tile_fdhs.iter().enumerate() {
+                if tile_fdh.encoding == PtexEncoding::Tiled {
+                    return Err(String::from("Nested tiles"));
+                }
+                seek(file, pos)?;
+                let tile: Vec<u8> = read_face_data(
+                    file, tile_fdh, tile_ulog2, tile_vlog2, data_type, n_channels,
+                )?;
+                // copy tile rows into face
+                let tu: usize = t % n_tiles_u;
+                let tv: usize = t / n_tiles_u;
+                let row_size: usize = tile_u_res * pixel_size;
+                for row in 0..tile_v_res {
+                    let dst: usize =
+                        ((tv * tile_v_res + row) * u_res + tu * tile_u_res) * pixel_size;
+                    data[dst..dst + row_size]
+                        .copy_from_slice(&tile[row * row_size..(row + 1) * row_size]);
+                }
+                pos += tile_fdh.block_size as u64;
+            }
+            Ok(data)
+        }
+    }
+}
+
+/// Undo the delta encoding which is applied (per channel plane) to
+/// integer data before compression.
+fn decode_difference(data: &mut Vec<u8>, data_type: PtexDataType) {
+    match data_type {
+        PtexDataType::Uint8 => {
+            let mut prev: u8 = 0;
+            for value in data.iter_mut() {
+                *value = value.wrapping_add(prev);
+                prev = *value;
+            }
+        }
+        PtexDataType::Uint16 => {
+            let mut prev: u16 = 0;
+            for chunk in data.chunks_mut(2) {
+                let value: u16 = (chunk[0] as u16 | (chunk[1] as u16) << 8).wrapping_add(prev);
+                chunk[0] = (value & 0xff) as u8;
+                chunk[1] = (value >> 8) as u8;
+                prev = value;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Face data is stored one channel after another, convert it to
+/// interleaved texels.
+fn interleave(
+    planar: &Vec<u8>,
+    n_texels: usize,
+    data_type: PtexDataType,
+    n_channels: usize,
+) -> Vec<u8> {
+    let ds: usize = data_type.size();
+    let mut data: Vec<u8> = vec![0_u8; planar.len()];
+    for c in 0..n_channels {
+        for i in 0..n_texels {
+            let src: usize = (c * n_texels + i) * ds;
+            let dst: usize = (i * n_channels + c) * ds;
+            data[dst..dst + ds].copy_from_slice(&planar[src..src + ds]);
+        }
+    }
+    data
+}
+
+fn convert(data: &[u8], data_type: PtexDataType) -> Vec<Float> {
+    match data_type {
+        PtexDataType::Uint8 => data.iter().map(|v| *v as Float / 255.0 as Float).collect(),
+        PtexDataType::Uint16 => data
+            .chunks(2)
+            .map(|c| (c[0] as u16 | (c[1] as u16) << 8) as Float / 65535.0 as Float)
+            .collect(),
+        PtexDataType::Half => data
+            .chunks(2)
+            .map(|c| f32::from(f16::from_bits(c[0] as u16 | (c[1] as u16) << 8)) as Float)
+            .collect(),
+        PtexDataType::Float => data
+            .chunks(4)
+            .map(|c| {
+                let mut cursor = Cursor::new(c);
+                cursor.read_f32::<LittleEndian>().unwrap() as Float
+            })
+            .collect(),
+    }
+}
+
+fn read_zip_block(
+    file: &mut Cursor<Vec<u8>>,
+    zip_size: usize,
+    unzip_size: usize,
+) -> Result<Vec<u8>, String> {
+    let mut zipped: Vec<u8> = vec![0_u8; zip_size];
+    read_exact(file, &mut zipped)?;
+    let data: Vec<u8> = inflate_bytes_zlib(&zipped)?;
+    if data.len() != unzip_size {
+        return Err(String::from("Unexpected size of compressed block"));
+    }
+    Ok(data)
+}
+
+fn read_exact(file: &mut Cursor<Vec<u8>>, buffer: &mut [u8]) -> Result<(), String> {
+    file.read_exact(buffer)
+        .map_err(|_| String::from("Premature end of file"))
+}
+
+fn seek(file: &mut Cursor<Vec<u8>>, pos: u64) -> Result<(), String> {
+    if pos > file.get_ref().len() as u64 {
+        return Err(String::from("Premature end of file"));
+    }
+    file.seek(SeekFrom::Start(pos))
+        .map(|_| ())
+        .map_err(|_| String::from("Premature end of file"))
+}
+
+fn read_u8(file: &mut Cursor<Vec<u8>>) -> Result<u8, String> {
+    file.read_u8()
+        .map_err(|_| String::from("Premature end of file"))
+}
+
+fn read_u16(file: &mut Cursor<Vec<u8>>) -> Result<u16, String> {
+    file.read_u16::<LittleEndian>()
+        .map_err(|_| String::from("Premature end of file"))
+}
+
+fn read_u32(file: &mut Cursor<Vec<u8>>) -> Result<u32, String> {
+    file.read_u32::<LittleEndian>()
+        .map_err(|_| String::from("Premature end of file"))
+}
+
+fn read_i32(file: &mut Cursor<Vec<u8>>) -> Result<i32, String> {
+    file.read_i32::<LittleEndian>()
+        .map_err(|_| String::from("Premature end of file"))
+}
@@ -835,7 +835,28 @@ impl Bxdf for LambertianTransmission {
     fn f(&self, _wo: &Vector3f, _wi: &Vector3f) -> Spectrum {
         self.t * INV_PI
     }
-
+    fn sample_f(
+        &self,
+        wo: &Vector3f,
+        wi: &mut Vector3f,
+        u: &Point2f,
+        pdf: &mut Float,
+        _sampled_type: &mut u8,
+    ) -> Spectrum {
+        *wi = cosine_sample_hemisphere(u);
+        if wo.z > 0.0 as Float {
+            wi.z *= -1.0 as Float;
+        }
+        *pdf = self.pdf(wo, &*wi);
+        self.f(wo, &*wi)
+    }
+    fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
+        if !vec3_same_hemisphere_vec3(wo, wi) {
+            abs_cos_theta(wi) * INV_PI
+        } else {
+            0.0 as Float
+        }
+    }
     fn get_type(&self) -> u8 {
         BxdfType::BsdfDiffuse as u8 | BxdfType::BsdfTransmission as u8
     }
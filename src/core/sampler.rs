@@ -27,6 +27,7 @@ pub trait Sampler: SamplerClone {
     fn round_count(&self, count: i32) -> i32;
     fn get_2d_array(&mut self, n: i32) -> Vec<Point2f>;
     fn start_next_sample(&mut self) -> bool;
+    fn set_sample_number(&mut self, sample_num: i64) -> bool;
     fn reseed(&mut self, seed: u64);
     fn get_current_pixel(&self) -> Point2i;
     fn get_current_sample_number(&self) -> i64;
@@ -9,6 +9,7 @@ use core::pbrt::clamp_t;
 use core::pbrt::Float;
 use core::pbrt::{INV_2_PI, INV_4_PI, INV_PI, PI_OVER_2, PI_OVER_4};
 use core::rng::Rng;
+use core::rng::FLOAT_ONE_MINUS_EPSILON;
 
 // see sampling.h
 
@@ -195,6 +196,65 @@ impl Distribution2D {
     }
 }
 
+/// Generates *n_samples* stratified 1D samples in $[0,1)$, jittered
+/// within each stratum (or placed at the center if *jitter* is false).
+pub fn stratified_sample_1d(samp: &mut [Float], n_samples: i32, rng: &mut Rng, jitter: bool) {
+    let inv_n_samples: Float = 1.0 as Float / n_samples as Float;
+    for i in 0..n_samples as usize {
+        let delta: Float = if jitter {
+            rng.uniform_float()
+        } else {
+            0.5 as Float
+        };
+        samp[i] = ((i as Float + delta) * inv_n_samples).min(FLOAT_ONE_MINUS_EPSILON);
+    }
+}
+
+/// Generates *nx* times *ny* stratified 2D samples in $[0,1)^2$.
+pub fn stratified_sample_2d(samp: &mut [Point2f], nx: i32, ny: i32, rng: &mut Rng, jitter: bool) {
+    let dx: Float = 1.0 as Float / nx as Float;
+    let dy: Float = 1.0 as Float / ny as Float;
+    let mut i: usize = 0;
+    for y in 0..ny {
+        for x in 0..nx {
+            let jx: Float = if jitter {
+                rng.uniform_float()
+            } else {
+                0.5 as Float
+            };
+            let jy: Float = if jitter {
+                rng.uniform_float()
+            } else {
+                0.5 as Float
+            };
+            samp[i].x = ((x as Float + jx) * dx).min(FLOAT_ONE_MINUS_EPSILON);
+            samp[i].y = ((y as Float + jy) * dy).min(FLOAT_ONE_MINUS_EPSILON);
+            i += 1;
+        }
+    }
+}
+
+/// Latin hypercube sampling: generates *n_samples* samples with
+/// *n_dim* dimensions each, by stratifying each dimension separately
+/// and randomly permuting the strata.
+pub fn latin_hypercube(samples: &mut [Float], n_samples: i32, n_dim: i32, rng: &mut Rng) {
+    // generate LHS samples along diagonal
+    let inv_n_samples: Float = 1.0 as Float / n_samples as Float;
+    for i in 0..n_samples {
+        for j in 0..n_dim {
+            let sj: Float = (i as Float + rng.uniform_float()) * inv_n_samples;
+            samples[(n_dim * i + j) as usize] = sj.min(FLOAT_ONE_MINUS_EPSILON);
+        }
+    }
+    // permute LHS samples in each dimension
+    for i in 0..n_dim {
+        for j in 0..n_samples {
+            let other: i32 = j + rng.uniform_uint32_bounded((n_samples - j) as u32) as i32;
+            samples.swap((n_dim * j + i) as usize, (n_dim * other + i) as usize);
+        }
+    }
+}
+
 /// Randomly permute an array of *count* sample values, each of which
 /// has *n_dimensions* dimensions.
 pub fn shuffle<T>(samp: &mut [T], count: i32, n_dimensions: i32, rng: &mut Rng) {
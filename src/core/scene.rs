@@ -6,11 +6,12 @@
 // std
 use std::sync::Arc;
 // pbrt
-use accelerators::bvh::BVHAccel;
 use core::geometry::{Bounds3f, Ray, Vector3f};
-use core::interaction::SurfaceInteraction;
+use core::interaction::{Interaction, SurfaceInteraction};
 use core::light::{Light, LightFlags};
+use core::pbrt::{Float, Spectrum};
 use core::primitive::Primitive;
+use core::sampler::Sampler;
 
 // see scene.h
 
@@ -18,12 +19,15 @@ use core::primitive::Primitive;
 pub struct Scene {
     pub lights: Vec<Arc<Light + Sync + Send>>,
     pub infinite_lights: Vec<Arc<Light + Sync + Send>>,
-    pub aggregate: Arc<BVHAccel>, // TODO: Primitive,
+    pub aggregate: Arc<Primitive + Sync + Send>,
     pub world_bound: Bounds3f,
 }
 
 impl Scene {
-    pub fn new(aggregate: Arc<BVHAccel>, lights: Vec<Arc<Light + Sync + Send>>) -> Self {
+    pub fn new(
+        aggregate: Arc<Primitive + Sync + Send>,
+        lights: Vec<Arc<Light + Sync + Send>>,
+    ) -> Self {
         let world_bound: Bounds3f = aggregate.world_bound();
         let scene: Scene = Scene {
             lights: Vec::new(),
@@ -75,4 +79,66 @@ impl Scene {
         );
         self.aggregate.intersect_p(ray)
     }
+    /// Intersects all rays at once, which lets the aggregate
+    /// traverse coherent rays (e.g. the camera rays of a pixel)
+    /// together. Each ray's _t_max_ is updated like in
+    /// **intersect()**.
+    pub fn intersect_batch(&self, rays: &mut [Ray]) -> Vec<Option<SurfaceInteraction>> {
+        // TODO: nIntersectionTests += rays.len();
+        for ray in rays.iter() {
+            assert_ne!(
+                ray.d,
+                Vector3f {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                }
+            );
+        }
+        self.aggregate.intersect_batch(rays)
+    }
+    /// Batched version of **intersect_p()**, e.g. for shadow rays.
+    pub fn intersect_p_batch(&self, rays: &[Ray]) -> Vec<bool> {
+        // TODO: nShadowTests += rays.len();
+        for ray in rays.iter() {
+            assert_ne!(
+                ray.d,
+                Vector3f {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                }
+            );
+        }
+        self.aggregate.intersect_p_batch(rays)
+    }
+    /// Like **intersect()**, but skips over surfaces without a
+    /// material (medium boundaries) and returns the beam
+    /// transmittance up to the first intersection found.
+    pub fn intersect_tr(
+        &self,
+        ray: &mut Ray,
+        sampler: &mut Sampler,
+        tr: &mut Spectrum,
+    ) -> Option<SurfaceInteraction> {
+        *tr = Spectrum::new(1.0 as Float);
+        loop {
+            let hit_surface = self.intersect(ray);
+            // accumulate beam transmittance for ray segment
+            if let Some(ref medium_arc) = ray.medium {
+                *tr *= medium_arc.tr(&ray, sampler);
+            }
+            // initialize next ray segment or terminate transmittance computation
+            if let Some(isect) = hit_surface {
+                if let Some(primitive) = isect.primitive {
+                    if let Some(_material) = primitive.get_material() {
+                        return Some(isect);
+                    }
+                }
+                *ray = isect.spawn_ray(&ray.d);
+            } else {
+                return None;
+            }
+        }
+    }
 }
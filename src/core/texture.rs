@@ -8,9 +8,44 @@
 use std::f32::consts::PI;
 // pbrt
 use core::geometry::vec3_dot_vec3;
-use core::geometry::{Point2f, Vector2f, Vector3f};
+use core::geometry::{spherical_phi, spherical_theta};
+use core::geometry::{Point2f, Point3f, Vector2f, Vector3f};
 use core::interaction::SurfaceInteraction;
 use core::pbrt::Float;
+use core::pbrt::{clamp_t, lerp};
+use core::pbrt::{INV_2_PI, INV_PI};
+use core::transform::Transform;
+
+// see texture.cpp
+
+const NOISE_PERM_SIZE: usize = 256;
+const NOISE_PERM: [usize; 2 * NOISE_PERM_SIZE] = [
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69,
+    142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148, 247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219,
+    203, 117, 35, 11, 32, 57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+    74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122, 60, 211, 133, 230,
+    220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25, 63, 161, 1, 216, 80, 73, 209, 76,
+    132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173,
+    186, 3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212, 207, 206,
+    59, 227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213, 119, 248, 152, 2, 44, 154, 163,
+    70, 221, 153, 101, 155, 167, 43, 172, 9, 129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232,
+    178, 185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162,
+    241, 81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157, 184, 84, 204,
+    176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93, 222, 114, 67, 29, 24, 72, 243, 141,
+    128, 195, 78, 66, 215, 61, 156, 180, 151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194,
+    233, 7, 225, 140, 36, 103, 30, 69, 142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148, 247, 120, 234,
+    75, 0, 26, 197, 62, 94, 252, 219, 203, 117, 35, 11, 32, 57, 177, 33, 88, 237, 149, 56, 87, 174,
+    20, 125, 136, 171, 168, 68, 175, 74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83,
+    111, 229, 122, 60, 211, 133, 230, 220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25,
+    63, 161, 1, 216, 80, 73, 209, 76, 132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188,
+    159, 86, 164, 100, 109, 198, 173, 186, 3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147,
+    118, 126, 255, 82, 85, 212, 207, 206, 59, 227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170,
+    213, 119, 248, 152, 2, 44, 154, 163, 70, 221, 153, 101, 155, 167, 43, 172, 9, 129, 22, 39, 253,
+    19, 98, 108, 110, 79, 113, 224, 232, 178, 185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193,
+    238, 210, 144, 12, 191, 179, 162, 241, 81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31,
+    181, 199, 106, 157, 184, 84, 204, 176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93,
+    222, 114, 67, 29, 24, 72, 243, 141, 128, 195, 78, 66, 215, 61, 156, 180,
+];
 
 // see texture.h
 
@@ -44,6 +79,102 @@ impl TextureMapping2D for UVMapping2D {
     }
 }
 
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SphericalMapping2D {
+    pub world_to_texture: Transform,
+}
+
+impl SphericalMapping2D {
+    pub fn new(world_to_texture: &Transform) -> Self {
+        SphericalMapping2D {
+            world_to_texture: *world_to_texture,
+        }
+    }
+    fn sphere(&self, p: &Point3f) -> Point2f {
+        let vec: Vector3f =
+            (self.world_to_texture.transform_point(p) - Point3f::default()).normalize();
+        let theta: Float = spherical_theta(&vec);
+        let phi: Float = spherical_phi(&vec);
+        Point2f {
+            x: theta * INV_PI,
+            y: phi * INV_2_PI,
+        }
+    }
+}
+
+impl TextureMapping2D for SphericalMapping2D {
+    fn map(&self, si: &SurfaceInteraction, dstdx: &mut Vector2f, dstdy: &mut Vector2f) -> Point2f {
+        let st: Point2f = self.sphere(&si.p);
+        // compute texture coordinate differentials for sphere $(u,v)$ mapping
+        let delta: Float = 0.1 as Float;
+        let st_delta_x: Point2f = self.sphere(&(si.p + si.dpdx * delta));
+        *dstdx = st_delta_x - st;
+        *dstdx *= 1.0 as Float / delta;
+        let st_delta_y: Point2f = self.sphere(&(si.p + si.dpdy * delta));
+        *dstdy = st_delta_y - st;
+        *dstdy *= 1.0 as Float / delta;
+        // handle sphere mapping discontinuity for coordinate differentials
+        if dstdx.y > 0.5 as Float {
+            dstdx.y = 1.0 as Float - dstdx.y;
+        } else if dstdx.y < -0.5 as Float {
+            dstdx.y = -(dstdx.y + 1.0 as Float);
+        }
+        if dstdy.y > 0.5 as Float {
+            dstdy.y = 1.0 as Float - dstdy.y;
+        } else if dstdy.y < -0.5 as Float {
+            dstdy.y = -(dstdy.y + 1.0 as Float);
+        }
+        st
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct CylindricalMapping2D {
+    pub world_to_texture: Transform,
+}
+
+impl CylindricalMapping2D {
+    pub fn new(world_to_texture: &Transform) -> Self {
+        CylindricalMapping2D {
+            world_to_texture: *world_to_texture,
+        }
+    }
+    fn cylinder(&self, p: &Point3f) -> Point2f {
+        let vec: Vector3f =
+            (self.world_to_texture.transform_point(p) - Point3f::default()).normalize();
+        Point2f {
+            x: (PI + vec.y.atan2(vec.x)) * INV_2_PI,
+            y: vec.z,
+        }
+    }
+}
+
+impl TextureMapping2D for CylindricalMapping2D {
+    fn map(&self, si: &SurfaceInteraction, dstdx: &mut Vector2f, dstdy: &mut Vector2f) -> Point2f {
+        let st: Point2f = self.cylinder(&si.p);
+        // compute texture coordinate differentials for cylinder $(u,v)$ mapping
+        let delta: Float = 0.01 as Float;
+        let st_delta_x: Point2f = self.cylinder(&(si.p + si.dpdx * delta));
+        *dstdx = st_delta_x - st;
+        *dstdx *= 1.0 as Float / delta;
+        let st_delta_y: Point2f = self.cylinder(&(si.p + si.dpdy * delta));
+        *dstdy = st_delta_y - st;
+        *dstdy *= 1.0 as Float / delta;
+        // handle cylinder mapping discontinuity for coordinate differentials
+        if dstdx.x > 0.5 as Float {
+            dstdx.x = 1.0 as Float - dstdx.x;
+        } else if dstdx.x < -0.5 as Float {
+            dstdx.x = -(dstdx.x + 1.0 as Float);
+        }
+        if dstdy.x > 0.5 as Float {
+            dstdy.x = 1.0 as Float - dstdy.x;
+        } else if dstdy.x < -0.5 as Float {
+            dstdy.x = -(dstdy.x + 1.0 as Float);
+        }
+        st
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct PlanarMapping2D {
     pub vs: Vector3f,
@@ -74,6 +205,31 @@ impl TextureMapping2D for PlanarMapping2D {
     }
 }
 
+pub trait TextureMapping3D {
+    fn map(&self, si: &SurfaceInteraction, dpdx: &mut Vector3f, dpdy: &mut Vector3f) -> Point3f;
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct IdentityMapping3D {
+    pub world_to_texture: Transform,
+}
+
+impl IdentityMapping3D {
+    pub fn new(world_to_texture: &Transform) -> Self {
+        IdentityMapping3D {
+            world_to_texture: *world_to_texture,
+        }
+    }
+}
+
+impl TextureMapping3D for IdentityMapping3D {
+    fn map(&self, si: &SurfaceInteraction, dpdx: &mut Vector3f, dpdy: &mut Vector3f) -> Point3f {
+        *dpdx = self.world_to_texture.transform_vector(&si.dpdx);
+        *dpdy = self.world_to_texture.transform_vector(&si.dpdy);
+        self.world_to_texture.transform_point(&si.p)
+    }
+}
+
 pub trait Texture<T> {
     fn evaluate(&self, si: &SurfaceInteraction) -> T;
 }
@@ -92,3 +248,131 @@ pub fn lanczos(x: Float, tau: Float) -> Float {
     let lanczos: Float = x.sin() / x;
     s * lanczos
 }
+
+pub fn smooth_step(min: Float, max: Float, value: Float) -> Float {
+    let v: Float = clamp_t((value - min) / (max - min), 0.0 as Float, 1.0 as Float);
+    v * v * (-2.0 as Float * v + 3.0 as Float)
+}
+
+/// Perlin noise function (in range [-1, 1]), which is zero at all
+/// integer lattice points.
+pub fn noise_flt(x: Float, y: Float, z: Float) -> Float {
+    // compute noise cell coordinates and offsets
+    let mut ix: i32 = x.floor() as i32;
+    let mut iy: i32 = y.floor() as i32;
+    let mut iz: i32 = z.floor() as i32;
+    let dx: Float = x - ix as Float;
+    let dy: Float = y - iy as Float;
+    let dz: Float = z - iz as Float;
+    // compute gradient weights
+    ix &= (NOISE_PERM_SIZE - 1) as i32;
+    iy &= (NOISE_PERM_SIZE - 1) as i32;
+    iz &= (NOISE_PERM_SIZE - 1) as i32;
+    let ix: usize = ix as usize;
+    let iy: usize = iy as usize;
+    let iz: usize = iz as usize;
+    let w000: Float = grad(ix, iy, iz, dx, dy, dz);
+    let w100: Float = grad(ix + 1, iy, iz, dx - 1.0, dy, dz);
+    let w010: Float = grad(ix, iy + 1, iz, dx, dy - 1.0, dz);
+    let w110: Float = grad(ix + 1, iy + 1, iz, dx - 1.0, dy - 1.0, dz);
+    let w001: Float = grad(ix, iy, iz + 1, dx, dy, dz - 1.0);
+    let w101: Float = grad(ix + 1, iy, iz + 1, dx - 1.0, dy, dz - 1.0);
+    let w011: Float = grad(ix, iy + 1, iz + 1, dx, dy - 1.0, dz - 1.0);
+    let w111: Float = grad(ix + 1, iy + 1, iz + 1, dx - 1.0, dy - 1.0, dz - 1.0);
+    // compute trilinear interpolation of weights
+    let wx: Float = noise_weight(dx);
+    let wy: Float = noise_weight(dy);
+    let wz: Float = noise_weight(dz);
+    let x00: Float = lerp(wx, w000, w100);
+    let x10: Float = lerp(wx, w010, w110);
+    let x01: Float = lerp(wx, w001, w101);
+    let x11: Float = lerp(wx, w011, w111);
+    let y0: Float = lerp(wy, x00, x10);
+    let y1: Float = lerp(wy, x01, x11);
+    lerp(wz, y0, y1)
+}
+
+pub fn noise_pnt3(p: &Point3f) -> Float {
+    noise_flt(p.x, p.y, p.z)
+}
+
+fn grad(x: usize, y: usize, z: usize, dx: Float, dy: Float, dz: Float) -> Float {
+    let mut h: usize = NOISE_PERM[NOISE_PERM[NOISE_PERM[x] + y] + z];
+    h &= 15;
+    let u: Float = if h < 8 || h == 12 || h == 13 { dx } else { dy };
+    let v: Float = if h < 4 || h == 12 || h == 13 { dy } else { dz };
+    let u: Float = if h & 1 != 0 { -u } else { u };
+    let v: Float = if h & 2 != 0 { -v } else { v };
+    u + v
+}
+
+fn noise_weight(t: Float) -> Float {
+    let t3: Float = t * t * t;
+    let t4: Float = t3 * t;
+    6.0 as Float * t4 * t - 15.0 as Float * t4 + 10.0 as Float * t3
+}
+
+/// Fractional Brownian motion: sum of noise octaves, the number of
+/// octaves is limited (antialiased) by the screen space
+/// differentials _dpdx_ and _dpdy_.
+pub fn fbm(p: &Point3f, dpdx: &Vector3f, dpdy: &Vector3f, omega: Float, max_octaves: i32) -> Float {
+    // compute number of octaves for antialiased FBm
+    let len2: Float = dpdx.length_squared().max(dpdy.length_squared());
+    let n: Float = clamp_t(
+        -1.0 as Float - 0.5 as Float * len2.log2(),
+        0.0 as Float,
+        max_octaves as Float,
+    );
+    let n_int: i32 = n.floor() as i32;
+    // compute sum of octaves of noise for FBm
+    let mut sum: Float = 0.0;
+    let mut lambda: Float = 1.0;
+    let mut o: Float = 1.0;
+    for _i in 0..n_int {
+        sum += o * noise_pnt3(&(*p * lambda));
+        lambda *= 1.99 as Float;
+        o *= omega;
+    }
+    let n_partial: Float = n - n_int as Float;
+    sum += o * smooth_step(0.3 as Float, 0.7 as Float, n_partial) * noise_pnt3(&(*p * lambda));
+    sum
+}
+
+/// Like fbm(), but sums the absolute values of the noise octaves.
+pub fn turbulence(
+    p: &Point3f,
+    dpdx: &Vector3f,
+    dpdy: &Vector3f,
+    omega: Float,
+    max_octaves: i32,
+) -> Float {
+    // compute number of octaves for antialiased FBm
+    let len2: Float = dpdx.length_squared().max(dpdy.length_squared());
+    let n: Float = clamp_t(
+        -1.0 as Float - 0.5 as Float * len2.log2(),
+        0.0 as Float,
+        max_octaves as Float,
+    );
+    let n_int: i32 = n.floor() as i32;
+    // compute sum of octaves of noise for turbulence
+    let mut sum: Float = 0.0;
+    let mut lambda: Float = 1.0;
+    let mut o: Float = 1.0;
+    for _i in 0..n_int {
+        sum += o * noise_pnt3(&(*p * lambda)).abs();
+        lambda *= 1.99 as Float;
+        o *= omega;
+    }
+    // account for contributions of clamped octaves in turbulence
+    let n_partial: Float = n - n_int as Float;
+    sum += o * lerp(
+        smooth_step(0.3 as Float, 0.7 as Float, n_partial),
+        0.2 as Float,
+        noise_pnt3(&(*p * lambda)).abs(),
+    );
+    for _i in n_int..max_octaves {
+        sum += o * 0.2 as Float;
+        o *= omega;
+    }
+    sum
+}
@@ -865,6 +865,7 @@ impl Transform {
         ret.dpdy = self.transform_vector(&si.dpdy);
         ret.bsdf = si.bsdf.clone();
         ret.primitive = None; // TODO? si.primitive;
+        ret.face_index = si.face_index;
         ret.shading.n = nrm_faceforward_nrm(&ret.shading.n, &ret.n);
         ret
     }
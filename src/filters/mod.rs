@@ -79,7 +79,34 @@
 //!
 //! ## LanczosSincFilter
 //!
-//! TODO
+//! A windowed sinc filter, where the sinc function is multiplied by
+//! the Lanczos window (with _tau_ controlling how many cycles the
+//! sinc function passes through before it is clamped to zero). Note
+//! that the filter has negative lobes.
+//!
+//! ```rust
+//! extern crate pbrt;
+//!
+//! use pbrt::core::geometry::Vector2f;
+//! use pbrt::core::pbrt::Float;
+//! use pbrt::filters::sinc::LanczosSincFilter;
+//!
+//! fn main() {
+//!     let xw: Float = 4.0;
+//!     let yw: Float = 4.0;
+//!     let tau: Float = 3.0;
+//!     let sinc_filter = LanczosSincFilter {
+//!         tau: tau,
+//!         radius: Vector2f { x: xw, y: yw },
+//!         inv_radius: Vector2f {
+//!             x: 1.0 / xw,
+//!             y: 1.0 / yw,
+//!         },
+//!     };
+//!
+//!     println!("sinc_filter = {:?}", sinc_filter);
+//! }
+//! ```
 //!
 //! ## TriangleFilter
 //!
@@ -109,4 +136,5 @@
 pub mod boxfilter;
 pub mod gaussian;
 pub mod mitchell;
+pub mod sinc;
 pub mod triangle;
@@ -0,0 +1,62 @@
+//std
+use std::f32::consts::PI;
+use std::sync::Arc;
+// pbrt
+use core::filter::Filter;
+use core::geometry::{Point2f, Vector2f};
+use core::paramset::ParamSet;
+use core::pbrt::Float;
+
+// see sinc.h
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LanczosSincFilter {
+    pub tau: Float,
+    // inherited from Filter (see filter.h)
+    pub radius: Vector2f,
+    pub inv_radius: Vector2f,
+}
+
+impl LanczosSincFilter {
+    pub fn create(ps: &ParamSet) -> Arc<Filter + Sync + Send> {
+        let xw: Float = ps.find_one_float("xwidth", 4.0);
+        let yw: Float = ps.find_one_float("ywidth", 4.0);
+        let tau: Float = ps.find_one_float("tau", 3.0);
+        let sinc_filter: Arc<Filter + Sync + Send> = Arc::new(LanczosSincFilter {
+            tau: tau,
+            radius: Vector2f { x: xw, y: yw },
+            inv_radius: Vector2f {
+                x: 1.0 / xw,
+                y: 1.0 / yw,
+            },
+        });
+        sinc_filter
+    }
+    pub fn sinc(&self, x: Float) -> Float {
+        let x: Float = x.abs();
+        if x < 1e-5 as Float {
+            return 1.0 as Float;
+        }
+        (PI * x).sin() / (PI * x)
+    }
+    pub fn windowed_sinc(&self, x: Float, radius: Float) -> Float {
+        let x: Float = x.abs();
+        if x > radius {
+            return 0.0 as Float;
+        }
+        let lanczos: Float = self.sinc(x / self.tau);
+        self.sinc(x) * lanczos
+    }
+}
+
+impl Filter for LanczosSincFilter {
+    fn evaluate(&self, p: Point2f) -> Float {
+        self.windowed_sinc(p.x, self.radius.x) * self.windowed_sinc(p.y, self.radius.y)
+    }
+    fn get_radius(&self) -> Vector2f {
+        Vector2f {
+            x: self.radius.x,
+            y: self.radius.y,
+        }
+    }
+}
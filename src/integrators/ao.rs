@@ -2,7 +2,7 @@
 use core::geometry::{nrm_cross_vec3, nrm_faceforward_vec3, vec3_dot_nrm};
 use core::geometry::{Bounds2i, Normal3f, Point2f, Ray, Vector3f};
 use core::integrator::SamplerIntegrator;
-use core::interaction::Interaction;
+use core::interaction::{Interaction, SurfaceInteraction};
 use core::material::TransportMode;
 use core::pbrt::{Float, Spectrum};
 use core::sampler::Sampler;
@@ -49,10 +49,9 @@ impl SamplerIntegrator for AOIntegrator {
         scene: &Scene,
         sampler: &mut Box<Sampler + Send + Sync>,
         // arena: &mut Arena,
-        _depth: i32,
+        depth: i32,
     ) -> Spectrum {
         // TODO: ProfilePhase p(Prof::SamplerIntegratorLi);
-        let mut l: Spectrum = Spectrum::default();
         let mut ray: Ray = Ray {
             o: r.o,
             d: r.d,
@@ -61,9 +60,22 @@ impl SamplerIntegrator for AOIntegrator {
             differential: r.differential,
             medium: r.medium.clone(),
         };
-        if let Some(mut isect) = scene.intersect(&mut ray) {
+        let isect = scene.intersect(&mut ray);
+        self.li_isect(&mut ray, isect, scene, sampler, depth)
+    }
+    fn li_isect(
+        &self,
+        ray: &mut Ray,
+        isect: Option<SurfaceInteraction>,
+        scene: &Scene,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        // arena: &mut Arena,
+        _depth: i32,
+    ) -> Spectrum {
+        let mut l: Spectrum = Spectrum::default();
+        if let Some(mut isect) = isect {
             let mode: TransportMode = TransportMode::Radiance;
-            isect.compute_scattering_functions(&mut ray, true, mode);
+            isect.compute_scattering_functions(ray, true, mode);
             // if (!isect.bsdf) {
             //     VLOG(2) << "Skipping intersection due to null bsdf";
             //     ray = isect.SpawnRay(ray.d);
@@ -75,6 +87,9 @@ impl SamplerIntegrator for AOIntegrator {
             let s: Vector3f = isect.dpdu.normalize();
             let t: Vector3f = nrm_cross_vec3(&isect.n, &s);
             let u: Vec<Point2f> = sampler.get_2d_array(self.n_samples);
+            let mut wis: Vec<Vector3f> = Vec::with_capacity(self.n_samples as usize);
+            let mut pdfs: Vec<Float> = Vec::with_capacity(self.n_samples as usize);
+            let mut rays: Vec<Ray> = Vec::with_capacity(self.n_samples as usize);
             for i in 0..self.n_samples as usize {
                 // Vector3f wi;
                 let mut wi: Vector3f;
@@ -92,14 +107,25 @@ impl SamplerIntegrator for AOIntegrator {
                     y: s.y * wi.x + t.y * wi.y + n.y * wi.z,
                     z: s.z * wi.x + t.z * wi.y + n.z * wi.z,
                 };
-                let mut ray: Ray = isect.spawn_ray(&wi);
-                if !scene.intersect_p(&mut ray) {
-                    l += Spectrum::new(vec3_dot_nrm(&wi, &n) / (pdf * self.n_samples as Float));
+                rays.push(isect.spawn_ray(&wi));
+                wis.push(wi);
+                pdfs.push(pdf);
+            }
+            // trace all shadow rays of the hemisphere in one batch
+            let occluded: Vec<bool> = scene.intersect_p_batch(&rays);
+            for i in 0..self.n_samples as usize {
+                if !occluded[i] {
+                    l += Spectrum::new(
+                        vec3_dot_nrm(&wis[i], &n) / (pdfs[i] * self.n_samples as Float),
+                    );
                 }
             }
         }
         l
     }
+    fn batch_camera_rays(&self) -> bool {
+        true
+    }
     fn get_pixel_bounds(&self) -> Bounds2i {
         self.pixel_bounds
     }
@@ -25,7 +25,7 @@ use core::material::TransportMode;
 use core::medium::{Medium, MediumInterface, PhaseFunction};
 use core::pbrt::{Float, Spectrum};
 use core::primitive::Primitive;
-use core::reflection::BxdfType;
+use core::reflection::{Bsdf, BxdfType};
 use core::sampler::Sampler;
 use core::sampling::Distribution1D;
 use core::scene::Scene;
@@ -178,6 +178,15 @@ impl<'a> Interaction for EndpointInteraction<'a> {
         // WORK
         None
     }
+    fn get_bsdf(&self) -> Option<Arc<Bsdf>> {
+        None
+    }
+    fn get_shading_n(&self) -> Option<Normal3f> {
+        None
+    }
+    fn get_phase(&self) -> Option<Arc<PhaseFunction>> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -227,6 +227,13 @@ impl Sampler for MLTSampler {
         self.current_pixel_sample_index += 1_i64;
         self.current_pixel_sample_index < self.samples_per_pixel
     }
+    fn set_sample_number(&mut self, sample_num: i64) -> bool {
+        // reset array offsets for next pixel sample
+        self.array_1d_offset = 0_usize;
+        self.array_2d_offset = 0_usize;
+        self.current_pixel_sample_index = sample_num;
+        self.current_pixel_sample_index < self.samples_per_pixel
+    }
     fn get_current_pixel(&self) -> Point2i {
         self.current_pixel
     }
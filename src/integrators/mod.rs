@@ -32,12 +32,37 @@
 //!
 //! ![Direct Lighting](/doc/img/cornell_box_pbrt_rust_directlighting.png)
 //!
+//! ## Whitted Ray Tracing
+//!
+//! The **WhittedIntegrator** evaluates direct lighting by sampling
+//! every light source once and follows perfect specular reflection
+//! and transmission recursively. It is fast and useful for quick
+//! previews before switching to a global illumination integrator.
+//!
 //! ## Path Tracing
 //!
 //! Path tracing incrementally generates paths of scattering events
 //! starting at the camera and ending at light sources in the scene.
 //!
 //! ![Path Tracing](/doc/img/cornell_box_pbrt_rust_path.png)
+//!
+//! ## Volumetric Path Tracing
+//!
+//! The **VolPathIntegrator** extends path tracing to participating
+//! media. Along each ray segment the medium is sampled to decide
+//! whether the path scatters inside the volume (using the phase
+//! function) or reaches the next surface. Direct lighting accounts
+//! for the beam transmittance between the scattering point and the
+//! light source.
+
+//! ## Stochastic Progressive Photon Mapping (SPPM)
+//!
+//! The **SPPMIntegrator** alternates between a camera pass, which
+//! finds a visible point per pixel, and a photon pass, which traces
+//! photons from the light sources and deposits them at nearby
+//! visible points. The search radius shrinks with every iteration,
+//! so the estimate converges to the correct result, including
+//! caustics seen via specular surfaces.
 
 //! ## Bidirectional Path Tracing (BDPT)
 //!
@@ -72,6 +97,39 @@ pub mod bdpt;
 pub mod directlighting;
 pub mod mlt;
 pub mod path;
+pub mod sppm;
+pub mod volpath;
+pub mod whitted;
+
+/// Issues a warning and returns black for radiance values that are
+/// NaN, infinite or have a negative luminance.
+fn check_radiance(l: Spectrum, pixel: &Point2i, sample_num: i64) -> Spectrum {
+    let y: Float = l.y();
+    if l.has_nans() {
+        println!(
+            "Not-a-number radiance value returned for pixel \
+             ({:?}, {:?}), sample {:?}. Setting to black.",
+            pixel.x, pixel.y, sample_num
+        );
+        Spectrum::new(0.0)
+    } else if y < -10.0e-5 as Float {
+        println!(
+            "Negative luminance value, {:?}, returned for pixel \
+             ({:?}, {:?}), sample {:?}. Setting to black.",
+            y, pixel.x, pixel.y, sample_num
+        );
+        Spectrum::new(0.0)
+    } else if y.is_infinite() {
+        println!(
+            "Infinite luminance value returned for pixel ({:?}, \
+             {:?}), sample {:?}. Setting to black.",
+            pixel.x, pixel.y, sample_num
+        );
+        Spectrum::new(0.0)
+    } else {
+        l
+    }
+}
 
 /// **Main function** to **render** a scene mutli-threaded (using all
 /// available cores).
@@ -142,6 +200,58 @@ pub fn render(
                             if !pnt2_inside_exclusive(&pixel, &pixel_bounds) {
                                 continue;
                             }
+                            if integrator.batch_camera_rays() {
+                                // generate all camera rays of the pixel ...
+                                let mut camera_samples: Vec<CameraSample> = Vec::new();
+                                let mut ray_weights: Vec<Float> = Vec::new();
+                                let mut rays: Vec<Ray> = Vec::new();
+                                let mut done: bool = false;
+                                while !done {
+                                    let camera_sample: CameraSample =
+                                        tile_sampler.get_camera_sample(&pixel);
+                                    let mut ray: Ray = Ray::default();
+                                    let ray_weight: Float =
+                                        camera.generate_ray_differential(&camera_sample, &mut ray);
+                                    ray.scale_differentials(
+                                        1.0 as Float
+                                            / (tile_sampler.get_samples_per_pixel() as Float)
+                                                .sqrt(),
+                                    );
+                                    if ray_weight > 0.0 {
+                                        rays.push(ray);
+                                    }
+                                    camera_samples.push(camera_sample);
+                                    ray_weights.push(ray_weight);
+                                    done = !tile_sampler.start_next_sample();
+                                }
+                                // ... intersect them in one batch ...
+                                let mut isects = scene.intersect_batch(&mut rays).into_iter();
+                                // ... and evaluate radiance along each of them
+                                let mut ray_index: usize = 0;
+                                for i in 0..camera_samples.len() {
+                                    // replay the dimensions used for the camera sample
+                                    tile_sampler.set_sample_number(i as i64);
+                                    tile_sampler.get_camera_sample(&pixel);
+                                    let mut l: Spectrum = Spectrum::new(0.0 as Float);
+                                    if ray_weights[i] > 0.0 {
+                                        l = integrator.li_isect(
+                                            &mut rays[ray_index],
+                                            isects.next().unwrap(),
+                                            scene,
+                                            &mut tile_sampler, // &mut arena,
+                                            0_i32,
+                                        );
+                                        ray_index += 1;
+                                    }
+                                    l = check_radiance(l, &pixel, i as i64);
+                                    film_tile.add_sample(
+                                        &camera_samples[i].p_film,
+                                        &mut l,
+                                        ray_weights[i],
+                                    );
+                                }
+                                continue;
+                            }
                             let mut done: bool = false;
                             while !done {
                                 // let's use the copy_arena crate instead of pbrt's MemoryArena
@@ -161,7 +271,6 @@ pub fn render(
                                 // TODO: ++nCameraRays;
                                 // evaluate radiance along camera ray
                                 let mut l: Spectrum = Spectrum::new(0.0 as Float);
-                                let y: Float = l.y();
                                 if ray_weight > 0.0 {
                                     l = integrator.li(
                                         &mut ray,
@@ -170,35 +279,11 @@ pub fn render(
                                         0_i32,
                                     );
                                 }
-                                if l.has_nans() {
-                                    println!(
-                                        "Not-a-number radiance value returned for pixel \
-                                         ({:?}, {:?}), sample {:?}. Setting to black.",
-                                        pixel.x,
-                                        pixel.y,
-                                        tile_sampler.get_current_sample_number()
-                                    );
-                                    l = Spectrum::new(0.0);
-                                } else if y < -10.0e-5 as Float {
-                                    println!(
-                                        "Negative luminance value, {:?}, returned for pixel \
-                                         ({:?}, {:?}), sample {:?}. Setting to black.",
-                                        y,
-                                        pixel.x,
-                                        pixel.y,
-                                        tile_sampler.get_current_sample_number()
-                                    );
-                                    l = Spectrum::new(0.0);
-                                } else if y.is_infinite() {
-                                    println!(
-                                        "Infinite luminance value returned for pixel ({:?}, \
-                                         {:?}), sample {:?}. Setting to black.",
-                                        pixel.x,
-                                        pixel.y,
-                                        tile_sampler.get_current_sample_number()
-                                    );
-                                    l = Spectrum::new(0.0);
-                                }
+                                l = check_radiance(
+                                    l,
+                                    &pixel,
+                                    tile_sampler.get_current_sample_number(),
+                                );
                                 // println!("Camera sample: {:?} -> ray: {:?} -> L = {:?}",
                                 //          camera_sample, ray, l);
                                 // add camera ray's contribution to image
@@ -3,7 +3,7 @@ use std::borrow::Borrow;
 use std::sync::Arc;
 // pbrt
 use core::geometry::{vec3_abs_dot_nrm, vec3_dot_nrm};
-use core::geometry::{Bounds2i, Ray, Vector3f};
+use core::geometry::{Bounds2i, Point2f, Ray, Vector3f};
 use core::integrator::uniform_sample_one_light;
 use core::integrator::SamplerIntegrator;
 use core::interaction::Interaction;
@@ -183,26 +183,52 @@ impl SamplerIntegrator for PathIntegrator {
                         ray = isect.spawn_ray(&wi);
 
                         // Account for subsurface scattering, if applicable
-                        // TODO: if (isect.bssrdf && ((sampled_type & BxdfType::BsdfTransmission as u8) != 0_u8)) {
-                        // Importance sample the BSSRDF
-                        //     SurfaceInteraction pi;
-                        //     Spectrum S = isect.bssrdf->Sample_S(
-                        //         scene, sampler.Get1D(), sampler.Get2D(), arena, &pi, &pdf);
-                        //     DCHECK(!std::isinf(beta.y()));
-                        //     if (S.IsBlack() || pdf == 0) break;
-                        //     beta *= S / pdf;
-                        //     // Account for the direct subsurface scattering component
-                        //     L += beta * UniformSampleOneLight(pi, scene, arena, sampler, false,
-                        //                                       lightDistribution->Lookup(pi.p));
-                        //     // Account for the indirect subsurface scattering component
-                        //     Spectrum f = pi.bsdf->Sample_f(pi.wo, &wi, sampler.Get2D(), &pdf,
-                        //                                    BSDF_ALL, &sampled_type);
-                        //     if (f.IsBlack() || pdf == 0) break;
-                        //     beta *= f * AbsDot(wi, pi.shading.n) / pdf;
-                        //     DCHECK(!std::isinf(beta.y()));
-                        //     specularBounce = (sampled_type & BSDF_SPECULAR) != 0;
-                        //     ray = pi.SpawnRay(wi);
-                        // }
+                        if let Some(ref bssrdf) = isect.bssrdf {
+                            if (sampled_type & BxdfType::BsdfTransmission as u8) != 0_u8 {
+                                // importance sample the BSSRDF
+                                let s2: Point2f = sampler.get_2d();
+                                let s1: Float = sampler.get_1d();
+                                let (s, pi_opt) = bssrdf.sample_s(scene, s1, &s2, &mut pdf);
+                                assert!(!(beta.y().is_infinite()));
+                                if s.is_black() || pdf == 0.0 as Float {
+                                    break;
+                                }
+                                if let Some(pi) = pi_opt {
+                                    beta *= s / pdf;
+                                    // account for the direct subsurface scattering component
+                                    let distrib: Arc<Distribution1D> =
+                                        light_distribution.lookup(&pi.p);
+                                    l += beta * uniform_sample_one_light(
+                                        &pi,
+                                        scene,
+                                        sampler,
+                                        false,
+                                        Some(Arc::borrow(&distrib)),
+                                    );
+                                    // account for the indirect subsurface scattering component
+                                    if let Some(ref pi_bsdf) = pi.bsdf {
+                                        let f: Spectrum = pi_bsdf.sample_f(
+                                            &pi.wo,
+                                            &mut wi,
+                                            &sampler.get_2d(),
+                                            &mut pdf,
+                                            BxdfType::BsdfAll as u8,
+                                            &mut sampled_type,
+                                        );
+                                        if f.is_black() || pdf == 0.0 as Float {
+                                            break;
+                                        }
+                                        beta *= f * vec3_abs_dot_nrm(&wi, &pi.shading.n) / pdf;
+                                        assert!(!(beta.y().is_infinite()));
+                                        specular_bounce =
+                                            (sampled_type & BxdfType::BsdfSpecular as u8) != 0_u8;
+                                        ray = pi.spawn_ray(&wi);
+                                    }
+                                } else {
+                                    break;
+                                }
+                            }
+                        }
 
                         // Possibly terminate the path with Russian roulette.
                         // Factor out radiance scaling due to refraction in rr_beta.
@@ -0,0 +1,544 @@
+extern crate num_cpus;
+extern crate pbr;
+
+// std
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+// pbrt
+use core::camera::{Camera, CameraSample};
+use core::film::Film;
+use core::geometry::{bnd3_expand, bnd3_union_bnd3, pnt3_distance_squared, vec3_abs_dot_nrm};
+use core::geometry::{
+    Bounds2i, Bounds3f, Normal3f, Point2f, Point2i, Point3f, Ray, Vector3f,
+};
+use core::integrator::{compute_light_power_distribution, uniform_sample_one_light};
+use core::interaction::Interaction;
+use core::lowdiscrepancy::radical_inverse;
+use core::material::TransportMode;
+use core::parallel::AtomicFloat;
+use core::pbrt::clamp_t;
+use core::pbrt::{Float, Spectrum};
+use core::reflection::{Bsdf, BxdfType};
+use core::sampler::Sampler;
+use core::scene::Scene;
+use samplers::halton::HaltonSampler;
+// others
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+
+// see sppm.h
+
+/// Stochastic Progressive Photon Mapping
+pub struct SPPMIntegrator {
+    pub camera: Arc<Camera + Send + Sync>,
+    pub initial_search_radius: Float,
+    pub n_iterations: i32,
+    pub max_depth: u32,
+    pub photons_per_iteration: i32,
+    pub write_frequency: i32,
+}
+
+impl SPPMIntegrator {
+    pub fn new(
+        camera: Arc<Camera + Send + Sync>,
+        n_iterations: i32,
+        photons_per_iteration: i32,
+        max_depth: u32,
+        initial_search_radius: Float,
+        write_frequency: i32,
+    ) -> Self {
+        let photons_per_iteration: i32 = if photons_per_iteration > 0_i32 {
+            photons_per_iteration
+        } else {
+            camera.get_film().cropped_pixel_bounds.area()
+        };
+        SPPMIntegrator {
+            camera: camera,
+            initial_search_radius: initial_search_radius,
+            n_iterations: n_iterations,
+            max_depth: max_depth,
+            photons_per_iteration: photons_per_iteration,
+            write_frequency: write_frequency,
+        }
+    }
+}
+
+// see sppm.cpp
+
+/// A point found by the camera pass where photons are gathered.
+#[derive(Default, Clone)]
+pub struct VisiblePoint {
+    pub p: Point3f,
+    pub wo: Vector3f,
+    pub bsdf: Option<Arc<Bsdf>>,
+    pub beta: Spectrum,
+}
+
+/// Per-pixel state accumulated over all SPPM iterations.
+#[derive(Default)]
+pub struct SPPMPixel {
+    pub radius: Float,
+    pub ld: Spectrum,
+    pub vp: VisiblePoint,
+    pub phi: [AtomicFloat; 3],
+    pub m: AtomicUsize,
+    pub n: Float,
+    pub tau: Spectrum,
+}
+
+fn to_grid(p: &Point3f, bounds: &Bounds3f, grid_res: &[i32; 3], pi: &mut [i32; 3]) -> bool {
+    let mut in_bounds: bool = true;
+    let pg: Vector3f = bounds.offset(p);
+    for i in 0..3 {
+        pi[i] = (grid_res[i] as Float * pg[i as u8]) as i32;
+        in_bounds &= pi[i] >= 0 && pi[i] < grid_res[i];
+        pi[i] = clamp_t(pi[i], 0, grid_res[i] - 1);
+    }
+    in_bounds
+}
+
+fn hash(p: &[i32; 3], hash_size: usize) -> usize {
+    ((p[0].wrapping_mul(73856093) ^ p[1].wrapping_mul(19349663) ^ p[2].wrapping_mul(83492791))
+        as u32 as usize)
+        % hash_size
+}
+
+/// **Main function** to **render** a scene multi-threaded (using all
+/// available cores) with **Stochastic Progressive Photon Mapping**
+/// (SPPM).
+pub fn render_sppm(
+    scene: &Scene,
+    camera: &Arc<Camera + Send + Sync>,
+    _sampler: &mut Box<Sampler + Send + Sync>,
+    integrator: &mut Box<SPPMIntegrator>,
+    num_threads: u8,
+) {
+    let num_cores: usize;
+    if num_threads == 0_u8 {
+        num_cores = num_cpus::get();
+    } else {
+        num_cores = num_threads as usize;
+    }
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(num_cores)
+        .build()
+        .unwrap();
+    // TODO: ProfilePhase p(Prof::IntegratorRender);
+
+    // initialize _pixel_bounds_ and _pixels_ array for SPPM
+    let film: Arc<Film> = camera.get_film();
+    let pixel_bounds: Bounds2i = film.cropped_pixel_bounds;
+    let n_pixels: usize = pixel_bounds.area() as usize;
+    let width: usize = (pixel_bounds.p_max.x - pixel_bounds.p_min.x) as usize;
+    let mut pixels: Vec<SPPMPixel> = Vec::with_capacity(n_pixels);
+    for _i in 0..n_pixels {
+        let mut pixel: SPPMPixel = SPPMPixel::default();
+        pixel.radius = integrator.initial_search_radius;
+        pixels.push(pixel);
+    }
+    let inv_sqrt_spp: Float = 1.0 as Float / (integrator.n_iterations as Float).sqrt();
+    // TODO: pixelMemoryBytes = nPixels * sizeof(SPPMPixel);
+    // compute _light_distr_ for sampling lights proportional to power
+    let light_distr = compute_light_power_distribution(scene);
+    // perform _n_iterations_ of SPPM integration
+    let sampler: Box<Sampler + Send + Sync> = Box::new(HaltonSampler::new(
+        integrator.n_iterations as i64,
+        pixel_bounds,
+        false,
+    ));
+    let max_depth: u32 = integrator.max_depth;
+    let photons_per_iteration: i32 = integrator.photons_per_iteration;
+    println!("Rendering ...");
+    for iter in pbr::PbIter::new(0..integrator.n_iterations) {
+        pool.install(|| {
+            // generate SPPM visible points
+            {
+                // TODO: ProfilePhase _(Prof::SPPMCameraPass);
+                pixels.par_chunks_mut(width).enumerate().for_each_with(
+                    sampler.clone(),
+                    |tile_sampler, (row, pixel_row)| {
+                        let y: i32 = pixel_bounds.p_min.y + row as i32;
+                        for (col, pixel) in pixel_row.iter_mut().enumerate() {
+                            let p_pixel: Point2i = Point2i {
+                                x: pixel_bounds.p_min.x + col as i32,
+                                y: y,
+                            };
+                            // prepare _tile_sampler_ for _p_pixel_
+                            tile_sampler.start_pixel(&p_pixel);
+                            tile_sampler.set_sample_number(iter as i64);
+                            // generate camera ray for pixel for SPPM
+                            let camera_sample: CameraSample =
+                                tile_sampler.get_camera_sample(&p_pixel);
+                            let mut ray: Ray = Ray::default();
+                            let beta_flt: Float =
+                                camera.generate_ray_differential(&camera_sample, &mut ray);
+                            if beta_flt == 0.0 as Float {
+                                continue;
+                            }
+                            let mut beta: Spectrum = Spectrum::new(beta_flt);
+                            ray.scale_differentials(inv_sqrt_spp);
+                            // follow camera ray path until a visible point is created
+                            let mut specular_bounce: bool = false;
+                            let mut depth: u32 = 0_u32;
+                            while depth < max_depth {
+                                // TODO: ++totalPhotonSurfaceInteractions;
+                                if let Some(mut isect) = scene.intersect(&mut ray) {
+                                    // process SPPM camera ray intersection
+
+                                    // compute BSDF at SPPM camera ray intersection
+                                    let mode: TransportMode = TransportMode::Radiance;
+                                    isect.compute_scattering_functions(&ray, true, mode);
+                                    if let Some(ref bsdf) = isect.bsdf.clone() {
+                                        // accumulate direct illumination at
+                                        // SPPM camera ray intersection
+                                        let wo: Vector3f = -ray.d;
+                                        if depth == 0 || specular_bounce {
+                                            pixel.ld += beta * isect.le(&wo);
+                                        }
+                                        pixel.ld += beta * uniform_sample_one_light(
+                                            &isect,
+                                            scene,
+                                            tile_sampler,
+                                            false,
+                                            None,
+                                        );
+                                        // possibly create visible point and end camera path
+                                        let is_diffuse: bool = bsdf.num_components(
+                                            BxdfType::BsdfDiffuse as u8
+                                                | BxdfType::BsdfReflection as u8
+                                                | BxdfType::BsdfTransmission as u8,
+                                        ) > 0;
+                                        let is_glossy: bool = bsdf.num_components(
+                                            BxdfType::BsdfGlossy as u8
+                                                | BxdfType::BsdfReflection as u8
+                                                | BxdfType::BsdfTransmission as u8,
+                                        ) > 0;
+                                        if is_diffuse || (is_glossy && depth == max_depth - 1) {
+                                            pixel.vp = VisiblePoint {
+                                                p: isect.p,
+                                                wo: wo,
+                                                bsdf: Some(bsdf.clone()),
+                                                beta: beta,
+                                            };
+                                            break;
+                                        }
+                                        // spawn ray from SPPM camera path vertex
+                                        if depth < max_depth - 1 {
+                                            let mut pdf: Float = 0.0 as Float;
+                                            let mut wi: Vector3f = Vector3f::default();
+                                            let mut sampled_type: u8 = u8::max_value();
+                                            let f: Spectrum = bsdf.sample_f(
+                                                &wo,
+                                                &mut wi,
+                                                &tile_sampler.get_2d(),
+                                                &mut pdf,
+                                                BxdfType::BsdfAll as u8,
+                                                &mut sampled_type,
+                                            );
+                                            if pdf == 0.0 as Float || f.is_black() {
+                                                break;
+                                            }
+                                            specular_bounce = (sampled_type
+                                                & BxdfType::BsdfSpecular as u8)
+                                                != 0_u8;
+                                            beta *= f * vec3_abs_dot_nrm(&wi, &isect.shading.n)
+                                                / pdf;
+                                            if beta.y() < 0.25 as Float {
+                                                let continue_prob: Float =
+                                                    (1.0 as Float).min(beta.y());
+                                                if tile_sampler.get_1d() > continue_prob {
+                                                    break;
+                                                }
+                                                beta = beta / continue_prob;
+                                            }
+                                            ray = isect.spawn_ray(&wi);
+                                        }
+                                    } else {
+                                        // skip over medium boundaries
+                                        ray = isect.spawn_ray(&ray.d);
+                                        continue;
+                                    }
+                                } else {
+                                    // accumulate light contributions for ray with no intersection
+                                    for light in &scene.lights {
+                                        pixel.ld += beta * light.le(&mut ray);
+                                    }
+                                    break;
+                                }
+                                depth += 1_u32;
+                            }
+                        }
+                    },
+                );
+            }
+            // create grid of all SPPM visible points
+            let mut grid_res: [i32; 3] = [0_i32; 3];
+            let mut grid_bounds: Bounds3f = Bounds3f::default();
+            // allocate grid for SPPM visible points
+            let hash_size: usize = n_pixels;
+            let mut grid: Vec<Vec<usize>> = vec![Vec::new(); hash_size];
+            let mut grid_built: bool = true;
+            {
+                // TODO: ProfilePhase _(Prof::SPPMGridConstruction);
+
+                // compute grid bounds for SPPM visible points
+                let mut max_radius: Float = 0.0 as Float;
+                for pixel in pixels.iter() {
+                    if pixel.vp.beta.is_black() {
+                        continue;
+                    }
+                    let vp_bound: Bounds3f = bnd3_expand(
+                        &Bounds3f {
+                            p_min: pixel.vp.p,
+                            p_max: pixel.vp.p,
+                        },
+                        pixel.radius,
+                    );
+                    grid_bounds = bnd3_union_bnd3(&grid_bounds, &vp_bound);
+                    max_radius = max_radius.max(pixel.radius);
+                }
+                // without any visible points (e.g. an empty view) there
+                // is nothing photons could contribute to in this iteration
+                if max_radius == 0.0 as Float {
+                    grid_built = false;
+                } else {
+                    // compute resolution of SPPM grid in each dimension
+                    let diag: Vector3f = grid_bounds.diagonal();
+                    let max_diag: Float = diag.x.max(diag.y.max(diag.z));
+                    let base_grid_res: i32 = (max_diag / max_radius) as i32;
+                    for i in 0..3 {
+                        grid_res[i] = ((base_grid_res as Float * diag[i as u8] / max_diag) as i32)
+                            .max(1_i32);
+                    }
+                    // add visible points to SPPM grid
+                    let grid_bounds: &Bounds3f = &grid_bounds;
+                    let grid_res: &[i32; 3] = &grid_res;
+                    let cells: Vec<Vec<(usize, usize)>> = pixels
+                        .par_iter()
+                        .enumerate()
+                        .map(|(pixel_index, pixel)| {
+                            let mut entries: Vec<(usize, usize)> = Vec::new();
+                            if !pixel.vp.beta.is_black() {
+                                // add pixel's visible point to applicable grid cells
+                                let radius: Float = pixel.radius;
+                                let mut p_min: [i32; 3] = [0_i32; 3];
+                                let mut p_max: [i32; 3] = [0_i32; 3];
+                                let r: Vector3f = Vector3f {
+                                    x: radius,
+                                    y: radius,
+                                    z: radius,
+                                };
+                                to_grid(&(pixel.vp.p - r), grid_bounds, grid_res, &mut p_min);
+                                to_grid(&(pixel.vp.p + r), grid_bounds, grid_res, &mut p_max);
+                                for z in p_min[2]..(p_max[2] + 1) {
+                                    for y in p_min[1]..(p_max[1] + 1) {
+                                        for x in p_min[0]..(p_max[0] + 1) {
+                                            // add visible point to grid cell $(x, y, z)$
+                                            let h: usize = hash(&[x, y, z], hash_size);
+                                            entries.push((h, pixel_index));
+                                        }
+                                    }
+                                }
+                            }
+                            entries
+                        })
+                        .collect();
+                    for entries in cells {
+                        for (h, pixel_index) in entries {
+                            grid[h].push(pixel_index);
+                        }
+                    }
+                }
+            }
+            // trace photons and accumulate contributions
+            if let Some(light_distr) = light_distr.as_ref().filter(|_| grid_built) {
+                // TODO: ProfilePhase _(Prof::SPPMPhotonPass);
+                let pixels: &Vec<SPPMPixel> = &pixels;
+                let grid: &Vec<Vec<usize>> = &grid;
+                let grid_bounds: &Bounds3f = &grid_bounds;
+                let grid_res: &[i32; 3] = &grid_res;
+                (0..photons_per_iteration)
+                    .into_par_iter()
+                    .for_each(|photon_index| {
+                        // follow photon path for _photon_index_
+                        let halton_index: u64 = iter as u64 * photons_per_iteration as u64
+                            + photon_index as u64;
+                        let mut halton_dim: u16 = 0_u16;
+                        // choose light to shoot photon from
+                        let mut light_pdf: Float = 0.0 as Float;
+                        let light_sample: Float = radical_inverse(halton_dim, halton_index);
+                        halton_dim += 1;
+                        let light_num: usize =
+                            light_distr.sample_discrete(light_sample, Some(&mut light_pdf));
+                        let ref light = scene.lights[light_num];
+                        // compute sample values for photon ray leaving light source
+                        let u_light_0: Point2f = Point2f {
+                            x: radical_inverse(halton_dim, halton_index),
+                            y: radical_inverse(halton_dim + 1, halton_index),
+                        };
+                        let u_light_1: Point2f = Point2f {
+                            x: radical_inverse(halton_dim + 2, halton_index),
+                            y: radical_inverse(halton_dim + 3, halton_index),
+                        };
+                        // TODO: lerp between camera's shutter_open and shutter_close
+                        let u_light_time: Float = radical_inverse(halton_dim + 4, halton_index);
+                        halton_dim += 5;
+                        // generate _photon_ray_ from light source and initialize _beta_
+                        let mut photon_ray: Ray = Ray::default();
+                        let mut n_light: Normal3f = Normal3f::default();
+                        let mut pdf_pos: Float = 0.0 as Float;
+                        let mut pdf_dir: Float = 0.0 as Float;
+                        let le: Spectrum = light.sample_le(
+                            &u_light_0,
+                            &u_light_1,
+                            u_light_time,
+                            &mut photon_ray,
+                            &mut n_light,
+                            &mut pdf_pos,
+                            &mut pdf_dir,
+                        );
+                        if pdf_pos == 0.0 as Float || pdf_dir == 0.0 as Float || le.is_black() {
+                            return;
+                        }
+                        let mut beta: Spectrum = (le * vec3_abs_dot_nrm(&photon_ray.d, &n_light))
+                            / (light_pdf * pdf_pos * pdf_dir);
+                        if beta.is_black() {
+                            return;
+                        }
+                        // follow photon path through scene and record intersections
+                        let mut depth: u32 = 0_u32;
+                        while depth < max_depth {
+                            if let Some(mut isect) = scene.intersect(&mut photon_ray) {
+                                // TODO: ++totalPhotonSurfaceInteractions;
+                                if depth > 0 {
+                                    // add photon contribution to nearby visible points
+                                    let mut photon_grid_index: [i32; 3] = [0_i32; 3];
+                                    if to_grid(
+                                        &isect.p,
+                                        grid_bounds,
+                                        grid_res,
+                                        &mut photon_grid_index,
+                                    ) {
+                                        let h: usize = hash(&photon_grid_index, hash_size);
+                                        // add photon contribution to visible
+                                        // points in _grid[h]_
+                                        for pixel_index in grid[h].iter() {
+                                            let pixel: &SPPMPixel = &pixels[*pixel_index];
+                                            let radius: Float = pixel.radius;
+                                            if pnt3_distance_squared(&pixel.vp.p, &isect.p)
+                                                > radius * radius
+                                            {
+                                                continue;
+                                            }
+                                            // update _pixel_ $\Phi$ and $M$ for nearby photon
+                                            let wi: Vector3f = -photon_ray.d;
+                                            if let Some(ref bsdf) = pixel.vp.bsdf {
+                                                let phi: Spectrum = beta * bsdf.f(
+                                                    &pixel.vp.wo,
+                                                    &wi,
+                                                    BxdfType::BsdfAll as u8,
+                                                );
+                                                for i in 0..3 {
+                                                    pixel.phi[i].add(phi[i]);
+                                                }
+                                                pixel.m.fetch_add(1, Ordering::SeqCst);
+                                            }
+                                        }
+                                    }
+                                }
+                                // sample new photon ray direction
+
+                                // compute BSDF at photon intersection point
+                                let mode: TransportMode = TransportMode::Importance;
+                                isect.compute_scattering_functions(&photon_ray, true, mode);
+                                if let Some(ref photon_bsdf) = isect.bsdf.clone() {
+                                    // sample BSDF _fr_ and direction _wi_ for reflected photon
+                                    let mut wi: Vector3f = Vector3f::default();
+                                    let wo: Vector3f = -photon_ray.d;
+                                    let mut pdf: Float = 0.0 as Float;
+                                    let mut flags: u8 = u8::max_value();
+                                    // generate _bsdf_sample_ for outgoing photon sample
+                                    let bsdf_sample: Point2f = Point2f {
+                                        x: radical_inverse(halton_dim, halton_index),
+                                        y: radical_inverse(halton_dim + 1, halton_index),
+                                    };
+                                    halton_dim += 2;
+                                    let fr: Spectrum = photon_bsdf.sample_f(
+                                        &wo,
+                                        &mut wi,
+                                        &bsdf_sample,
+                                        &mut pdf,
+                                        BxdfType::BsdfAll as u8,
+                                        &mut flags,
+                                    );
+                                    if fr.is_black() || pdf == 0.0 as Float {
+                                        break;
+                                    }
+                                    let bnew: Spectrum =
+                                        beta * fr * vec3_abs_dot_nrm(&wi, &isect.shading.n) / pdf;
+                                    // possibly terminate photon path with Russian roulette
+                                    let q: Float =
+                                        (0.0 as Float).max(1.0 as Float - bnew.y() / beta.y());
+                                    if radical_inverse(halton_dim, halton_index) < q {
+                                        break;
+                                    }
+                                    halton_dim += 1;
+                                    beta = bnew / (1.0 as Float - q);
+                                    photon_ray = isect.spawn_ray(&wi);
+                                } else {
+                                    // skip over medium boundaries
+                                    photon_ray = isect.spawn_ray(&photon_ray.d);
+                                    continue;
+                                }
+                            } else {
+                                break;
+                            }
+                            depth += 1_u32;
+                        }
+                    });
+            }
+            // update pixel values from this pass's photons
+            {
+                // TODO: ProfilePhase _(Prof::SPPMStatsUpdate);
+                pixels.par_iter_mut().for_each(|p| {
+                    let m: usize = p.m.load(Ordering::SeqCst);
+                    if m > 0 {
+                        // update pixel photon count, search radius, and $\tau$ from photons
+                        let gamma: Float = 2.0 as Float / 3.0 as Float;
+                        let n_new: Float = p.n + gamma * m as Float;
+                        let r_new: Float = p.radius * (n_new / (p.n + m as Float)).sqrt();
+                        let mut phi: Spectrum = Spectrum::default();
+                        for j in 0..3 {
+                            phi[j] = Float::from(&p.phi[j]);
+                        }
+                        p.tau = (p.tau + p.vp.beta * phi) * (r_new * r_new)
+                            / (p.radius * p.radius);
+                        p.n = n_new;
+                        p.radius = r_new;
+                        p.m.store(0, Ordering::SeqCst);
+                        for j in 0..3 {
+                            p.phi[j] = AtomicFloat::default();
+                        }
+                    }
+                    // reset _VisiblePoint_ in pixel
+                    p.vp.beta = Spectrum::default();
+                    p.vp.bsdf = None;
+                });
+            }
+        });
+        // periodically store SPPM image in film and write image
+        if iter + 1 == integrator.n_iterations || ((iter + 1) % integrator.write_frequency) == 0
+        {
+            let np: u64 = (iter + 1) as u64 * photons_per_iteration as u64;
+            let mut image: Vec<Spectrum> = Vec::with_capacity(n_pixels);
+            for pixel in pixels.iter() {
+                let mut l: Spectrum = pixel.ld / (iter + 1) as Float;
+                l += pixel.tau / (np as Float * PI * pixel.radius * pixel.radius);
+                image.push(l);
+            }
+            film.set_image(&image[..]);
+            film.write_image(1.0 as Float);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+// pbrt
+use core::geometry::vec3_abs_dot_nrm;
+use core::geometry::{Bounds2i, Normal3f, Ray, Vector3f};
+use core::integrator::SamplerIntegrator;
+use core::interaction::{Interaction, InteractionCommon};
+use core::light::VisibilityTester;
+use core::material::TransportMode;
+use core::pbrt::{Float, Spectrum};
+use core::reflection::BxdfType;
+use core::sampler::Sampler;
+use core::scene::Scene;
+
+// see whitted.h
+
+/// Whitted-style ray tracing (direct lighting plus perfect specular
+/// reflection and transmission)
+pub struct WhittedIntegrator {
+    // inherited from SamplerIntegrator (see integrator.h)
+    pixel_bounds: Bounds2i,
+    // see whitted.h
+    max_depth: i32,
+}
+
+impl WhittedIntegrator {
+    pub fn new(max_depth: i32, pixel_bounds: Bounds2i) -> Self {
+        WhittedIntegrator {
+            pixel_bounds: pixel_bounds,
+            max_depth: max_depth,
+        }
+    }
+}
+
+impl SamplerIntegrator for WhittedIntegrator {
+    fn preprocess(&mut self, _scene: &Scene, _sampler: &mut Box<Sampler + Send + Sync>) {}
+    fn li(
+        &self,
+        ray: &mut Ray,
+        scene: &Scene,
+        sampler: &mut Box<Sampler + Send + Sync>,
+        // arena: &mut Arena,
+        depth: i32,
+    ) -> Spectrum {
+        let mut l: Spectrum = Spectrum::new(0.0 as Float);
+        // find closest ray intersection or return background radiance
+        if let Some(mut isect) = scene.intersect(ray) {
+            // compute emitted and reflected light at ray intersection point
+
+            // initialize common variables for Whitted integrator
+            let wo: Vector3f = isect.wo;
+            // compute scattering functions for surface interaction
+            let mode: TransportMode = TransportMode::Radiance;
+            isect.compute_scattering_functions(ray /* arena, */, false, mode);
+            // bump and normal mapping may have changed the shading normal
+            let n: Normal3f = isect.shading.n;
+            if let Some(ref bsdf) = isect.bsdf.clone() {
+                // compute emitted light if ray hit an area light source
+                l += isect.le(&wo);
+                // add contribution of each light source
+                let it_common: InteractionCommon = InteractionCommon {
+                    p: isect.get_p(),
+                    time: isect.get_time(),
+                    p_error: isect.get_p_error(),
+                    wo: isect.get_wo(),
+                    n: isect.get_n(),
+                    medium_interface: isect.get_medium_interface(),
+                };
+                for light in &scene.lights {
+                    let mut wi: Vector3f = Vector3f::default();
+                    let mut pdf: Float = 0.0 as Float;
+                    let mut visibility: VisibilityTester = VisibilityTester::default();
+                    let li: Spectrum = light.sample_li(
+                        &it_common,
+                        &sampler.get_2d(),
+                        &mut wi,
+                        &mut pdf,
+                        &mut visibility,
+                    );
+                    if li.is_black() || pdf == 0.0 as Float {
+                        continue;
+                    }
+                    let f: Spectrum = bsdf.f(&wo, &wi, BxdfType::BsdfAll as u8);
+                    if !f.is_black() && visibility.unoccluded(scene) {
+                        l += f * li * Spectrum::new(vec3_abs_dot_nrm(&wi, &n) / pdf);
+                    }
+                }
+                if depth + 1_i32 < self.max_depth {
+                    // trace rays for specular reflection and refraction
+                    l += self.specular_reflect(
+                        ray, &isect, scene, sampler, // arena,
+                        depth,
+                    );
+                    l += self.specular_transmit(
+                        ray, &isect, scene, sampler, // arena,
+                        depth,
+                    );
+                }
+            } else {
+                // skip over medium boundaries
+                let mut new_ray: Ray = isect.spawn_ray(&ray.d);
+                return self.li(&mut new_ray, scene, sampler, depth);
+            }
+        } else {
+            for light in &scene.lights {
+                l += light.le(ray);
+            }
+        }
+        l
+    }
+    fn get_pixel_bounds(&self) -> Bounds2i {
+        self.pixel_bounds
+    }
+}
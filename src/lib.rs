@@ -23,16 +23,14 @@
 extern crate hexf;
 extern crate atomic;
 extern crate byteorder;
-#[cfg(feature = "openexr")]
 extern crate half;
 extern crate image;
+extern crate inflate;
 extern crate num;
 #[cfg(feature = "openexr")]
 extern crate openexr;
 extern crate ply_rs;
 extern crate rayon;
-extern crate time;
-extern crate typed_arena;
 
 pub mod accelerators;
 pub mod blockqueue;
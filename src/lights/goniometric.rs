@@ -0,0 +1,240 @@
+// std
+use std;
+use std::f32::consts::PI;
+use std::sync::Arc;
+// pbrt
+use core::geometry::{pnt3_distance_squared, spherical_direction, spherical_phi, spherical_theta};
+use core::geometry::{Normal3f, Point2f, Point2i, Point3f, Ray, Vector3f};
+use core::ies::IesProfile;
+use core::imageio::read_image;
+use core::interaction::{Interaction, InteractionCommon};
+use core::light::{Light, LightFlags, VisibilityTester};
+use core::medium::{Medium, MediumInterface};
+use core::mipmap::{ImageWrap, MipMap};
+use core::pbrt::{Float, Spectrum};
+use core::pbrt::{INV_2_PI, INV_PI};
+use core::sampling::{uniform_sample_sphere, uniform_sphere_pdf};
+use core::scene::Scene;
+use core::transform::Transform;
+
+// see goniometric.h
+
+pub struct GonioPhotometricLight {
+    // private data (see goniometric.h)
+    pub p_light: Point3f,
+    pub i: Spectrum,
+    pub mipmap: Option<Arc<MipMap<Spectrum>>>,
+    pub mipmap_integral: Spectrum,
+    // inherited from class Light (see light.h)
+    pub flags: u8,
+    pub n_samples: i32,
+    pub medium_interface: MediumInterface,
+    pub light_to_world: Transform,
+    pub world_to_light: Transform,
+}
+
+impl GonioPhotometricLight {
+    pub fn new(
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+        i: &Spectrum,
+        texname: String,
+        profile: Option<Arc<IesProfile>>,
+    ) -> Self {
+        let mut inside: Option<Arc<Medium + Send + Sync>> = None;
+        let mut outside: Option<Arc<Medium + Send + Sync>> = None;
+        if let Some(ref mi_outside) = medium_interface.outside {
+            // in C++: MediumInterface(const Medium *medium) : inside(medium), outside(medium)
+            inside = Some(mi_outside.clone());
+            outside = Some(mi_outside.clone());
+        }
+        // create _mipmap_ for _GonioPhotometricLight_
+        let mut mipmap: Option<Arc<MipMap<Spectrum>>> = None;
+        let mut image: Option<(Vec<Spectrum>, Point2i)> = None;
+        if let Some(ref ies) = profile {
+            // tabulate IES profile in $(\phi, \theta)$ of the swapped
+            // direction used by _scale()_
+            let resolution: Point2i = Point2i { x: 512, y: 256 };
+            let mut texels: Vec<Spectrum> = Vec::new();
+            for t in 0..resolution.y {
+                let theta: Float = (t as Float + 0.5 as Float) / resolution.y as Float * PI;
+                for s in 0..resolution.x {
+                    let phi: Float =
+                        (s as Float + 0.5 as Float) / resolution.x as Float * 2.0 as Float * PI;
+                    let mut wl: Vector3f = spherical_direction(theta.sin(), theta.cos(), phi);
+                    std::mem::swap(&mut wl.y, &mut wl.z);
+                    texels.push(Spectrum::new(ies.evaluate_direction(&wl)));
+                }
+            }
+            image = Some((texels, resolution));
+        } else if texname != String::from("") {
+            image = read_image(&texname);
+        }
+        if let Some((texels, resolution)) = image {
+            let do_trilinear: bool = false;
+            let max_aniso: Float = 8.0 as Float;
+            let wrap_mode: ImageWrap = ImageWrap::Repeat;
+            mipmap = Some(Arc::new(MipMap::new(
+                &resolution,
+                &texels[..],
+                do_trilinear,
+                max_aniso,
+                wrap_mode,
+            )));
+        }
+        // integrate $I(\omega)$ over the sphere (each texel covers a
+        // solid angle proportional to $\sin\theta$)
+        let mut mipmap_integral: Spectrum = Spectrum::new(4.0 as Float * PI);
+        if let Some(ref mipmap) = mipmap {
+            let width: i32 = mipmap.width();
+            let height: i32 = mipmap.height();
+            let d_theta: Float = PI / height as Float;
+            let d_phi: Float = 2.0 as Float * PI / width as Float;
+            let mut sum: Spectrum = Spectrum::default();
+            for t in 0..height {
+                let sin_theta: Float = ((t as Float + 0.5 as Float) * d_theta).sin();
+                for s in 0..width {
+                    sum += *mipmap.texel(0, s as isize, t as isize) * sin_theta;
+                }
+            }
+            mipmap_integral = sum * d_theta * d_phi;
+        }
+        GonioPhotometricLight {
+            p_light: light_to_world.transform_point(&Point3f::default()),
+            i: *i,
+            mipmap: mipmap,
+            mipmap_integral: mipmap_integral,
+            flags: LightFlags::DeltaPosition as u8,
+            n_samples: 1_i32,
+            medium_interface: MediumInterface {
+                inside: inside,
+                outside: outside,
+            },
+            light_to_world: *light_to_world,
+            world_to_light: Transform::inverse(light_to_world),
+        }
+    }
+    pub fn scale(&self, w: &Vector3f) -> Spectrum {
+        let mut wp: Vector3f = self.world_to_light.transform_vector(w).normalize();
+        std::mem::swap(&mut wp.y, &mut wp.z);
+        let theta: Float = spherical_theta(&wp);
+        let phi: Float = spherical_phi(&wp);
+        let st: Point2f = Point2f {
+            x: phi * INV_2_PI,
+            y: theta * INV_PI,
+        };
+        if let Some(ref mipmap) = self.mipmap {
+            mipmap.lookup_pnt_flt(&st, 0.0 as Float)
+        } else {
+            Spectrum::new(1.0 as Float)
+        }
+    }
+}
+
+impl Light for GonioPhotometricLight {
+    fn sample_li(
+        &self,
+        iref: &InteractionCommon,
+        _u: &Point2f,
+        wi: &mut Vector3f,
+        pdf: &mut Float,
+        vis: &mut VisibilityTester,
+    ) -> Spectrum {
+        // TODO: ProfilePhase _(Prof::LightSample);
+        *wi = (self.p_light - iref.p).normalize();
+        *pdf = 1.0 as Float;
+        // medium_interface1
+        let mut inside: Option<Arc<Medium + Send + Sync>> = None;
+        let mut outside: Option<Arc<Medium + Send + Sync>> = None;
+        if let Some(ref mi_arc) = iref.medium_interface {
+            if let Some(ref mi_inside_arc) = mi_arc.get_inside() {
+                inside = Some(mi_inside_arc.clone());
+            }
+            if let Some(ref mi_outside_arc) = mi_arc.get_outside() {
+                outside = Some(mi_outside_arc.clone());
+            }
+        }
+        let medium_interface1_arc: Arc<MediumInterface> =
+            Arc::new(MediumInterface::new(inside, outside));
+        // medium_interface2
+        let mut inside: Option<Arc<Medium + Send + Sync>> = None;
+        let mut outside: Option<Arc<Medium + Send + Sync>> = None;
+        if let Some(ref mi_inside_arc) = self.medium_interface.inside {
+            inside = Some(mi_inside_arc.clone());
+        }
+        if let Some(ref mi_outside_arc) = self.medium_interface.outside {
+            outside = Some(mi_outside_arc.clone());
+        }
+        let medium_interface2_arc: Arc<MediumInterface> =
+            Arc::new(MediumInterface::new(inside, outside));
+        *vis = VisibilityTester {
+            p0: InteractionCommon {
+                p: iref.p,
+                time: iref.time,
+                p_error: iref.p_error,
+                wo: iref.wo,
+                n: iref.n,
+                medium_interface: Some(medium_interface1_arc.clone()),
+            },
+            p1: InteractionCommon {
+                p: self.p_light,
+                time: iref.time,
+                p_error: Vector3f::default(),
+                wo: Vector3f::default(),
+                n: Normal3f::default(),
+                medium_interface: Some(medium_interface2_arc.clone()),
+            },
+        };
+        self.i * self.scale(&-*wi) / pnt3_distance_squared(&self.p_light, &iref.p)
+    }
+    fn power(&self) -> Spectrum {
+        self.i * self.mipmap_integral
+    }
+    fn preprocess(&self, _scene: &Scene) {}
+    /// Default implementation returns no emitted radiance for a ray
+    /// that escapes the scene bounds.
+    fn le(&self, _ray: &mut Ray) -> Spectrum {
+        Spectrum::new(0.0 as Float)
+    }
+    fn pdf_li(&self, _iref: &Interaction, _wi: Vector3f) -> Float {
+        0.0 as Float
+    }
+    fn sample_le(
+        &self,
+        u1: &Point2f,
+        _u2: &Point2f,
+        time: Float,
+        ray: &mut Ray,
+        n_light: &mut Normal3f,
+        pdf_pos: &mut Float,
+        pdf_dir: &mut Float,
+    ) -> Spectrum {
+        // TODO: ProfilePhase _(Prof::LightSample);
+        let mut inside: Option<Arc<Medium + Send + Sync>> = None;
+        if let Some(ref mi_inside) = self.medium_interface.inside {
+            inside = Some(mi_inside.clone());
+        }
+        *ray = Ray {
+            o: self.p_light,
+            d: uniform_sample_sphere(u1),
+            t_max: std::f32::INFINITY,
+            time: time,
+            differential: None,
+            medium: inside,
+        };
+        *n_light = Normal3f::from(ray.d);
+        *pdf_pos = 1.0 as Float;
+        *pdf_dir = uniform_sphere_pdf();
+        self.i * self.scale(&ray.d)
+    }
+    fn get_flags(&self) -> u8 {
+        self.flags
+    }
+    fn get_n_samples(&self) -> i32 {
+        self.n_samples
+    }
+    fn pdf_le(&self, _ray: &Ray, _n_light: &Normal3f, pdf_pos: &mut Float, pdf_dir: &mut Float) {
+        *pdf_pos = 0.0 as Float;
+        *pdf_dir = uniform_sphere_pdf();
+    }
+}
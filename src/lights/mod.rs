@@ -30,7 +30,13 @@
 //!
 //! ## Goniophotometric Diagram Lights
 //!
-//! TODO
+//! A goniophotometric diagram describes the angular distribution of
+//! luminance from a point light source. The **GonioPhotometricLight**
+//! stores this distribution as an image (a 2D (theta, phi) map) which
+//! scales the intensity of an otherwise isotropic point light.
+//! Instead of an image an IES LM-63 photometric data file can be
+//! given (**ies** parameter), which is also supported by point and
+//! spot lights.
 //!
 //! ## Infinite Area Lights
 //!
@@ -49,7 +55,9 @@
 //!
 //! ## Texture Projection Lights
 //!
-//! TODO
+//! The **ProjectionLight** projects an image into the scene, much like
+//! a slide projector. Light is only emitted inside a perspective
+//! frustum with a given field of view (**fov**).
 //!
 //! ## Spotlights
 //!
@@ -58,6 +66,8 @@
 
 pub mod diffuse;
 pub mod distant;
+pub mod goniometric;
 pub mod infinite;
 pub mod point;
+pub mod projection;
 pub mod spot;
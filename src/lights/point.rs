@@ -3,8 +3,9 @@ use std;
 use std::f32::consts::PI;
 use std::sync::Arc;
 // pbrt
-use core::geometry::{pnt3_distance_squared};
+use core::geometry::pnt3_distance_squared;
 use core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
+use core::ies::IesProfile;
 use core::interaction::{Interaction, InteractionCommon};
 use core::light::{Light, LightFlags, VisibilityTester};
 use core::medium::{Medium, MediumInterface};
@@ -20,10 +21,13 @@ pub struct PointLight {
     // private data (see point.h)
     pub p_light: Point3f,
     pub i: Spectrum,
+    pub profile: Option<Arc<IesProfile>>,
+    pub profile_integral: Float,
     // inherited from class Light (see light.h)
     pub flags: u8,
     pub n_samples: i32,
     pub medium_interface: MediumInterface,
+    pub world_to_light: Transform,
 }
 
 impl PointLight {
@@ -31,6 +35,7 @@ impl PointLight {
         light_to_world: &Transform,
         medium_interface: &MediumInterface,
         i: &Spectrum,
+        profile: Option<Arc<IesProfile>>,
     ) -> Self {
         let mut inside: Option<Arc<Medium + Send + Sync>> = None;
         let mut outside: Option<Arc<Medium + Send + Sync>> = None;
@@ -40,15 +45,33 @@ impl PointLight {
         if let Some(ref mi_outside) = medium_interface.outside {
             outside = Some(mi_outside.clone());
         }
+        // integrate (optional) IES profile over the sphere of directions
+        let mut profile_integral: Float = 4.0 as Float * PI;
+        if let Some(ref ies) = profile {
+            profile_integral = ies.integral();
+        }
         PointLight {
             p_light: light_to_world.transform_point(&Point3f::default()),
             i: *i,
+            profile: profile,
+            profile_integral: profile_integral,
             flags: LightFlags::DeltaPosition as u8,
             n_samples: 1_i32,
             medium_interface: MediumInterface {
                 inside: inside,
                 outside: outside,
             },
+            world_to_light: Transform::inverse(light_to_world),
+        }
+    }
+    /// Scale factor of the intensity for a direction _w_ leaving the
+    /// light (see _IesProfile::evaluate_direction()_).
+    pub fn scale(&self, w: &Vector3f) -> Float {
+        if let Some(ref ies) = self.profile {
+            let wl: Vector3f = self.world_to_light.transform_vector(w).normalize();
+            ies.evaluate_direction(&wl)
+        } else {
+            1.0 as Float
         }
     }
 }
@@ -83,10 +106,10 @@ impl Light for PointLight {
                 medium_interface: None,
             },
         };
-        self.i / pnt3_distance_squared(&self.p_light, &iref.p)
+        self.i * self.scale(&-*wi) / pnt3_distance_squared(&self.p_light, &iref.p)
     }
     fn power(&self) -> Spectrum {
-        self.i * (4.0 as Float * PI)
+        self.i * self.profile_integral
     }
     fn preprocess(&self, _scene: &Scene) {}
     /// Default implementation returns no emitted radiance for a ray
@@ -119,7 +142,7 @@ impl Light for PointLight {
         *n_light = Normal3f::from(ray.d);
         *pdf_pos = 1.0 as Float;
         *pdf_dir = uniform_sphere_pdf();
-        self.i
+        self.i * self.scale(&ray.d)
     }
     fn get_flags(&self) -> u8 {
         self.flags
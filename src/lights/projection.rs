@@ -0,0 +1,282 @@
+// std
+use std;
+use std::f32::consts::PI;
+use std::sync::Arc;
+// pbrt
+use core::geometry::{pnt2_inside_bnd2, pnt3_distance_squared};
+use core::geometry::{Bounds2f, Normal3f, Point2f, Point3f, Ray, Vector2f, Vector3f};
+use core::imageio::read_image;
+use core::interaction::{Interaction, InteractionCommon};
+use core::light::{Light, LightFlags, VisibilityTester};
+use core::medium::{Medium, MediumInterface};
+use core::mipmap::{ImageWrap, MipMap};
+use core::pbrt::{Float, Spectrum};
+use core::reflection::cos_theta;
+use core::sampling::{uniform_cone_pdf, uniform_sample_cone};
+use core::scene::Scene;
+use core::transform::Transform;
+
+// see projection.h
+
+pub struct ProjectionLight {
+    // private data (see projection.h)
+    pub projection_map: Option<Arc<MipMap<Spectrum>>>,
+    pub p_light: Point3f,
+    pub i: Spectrum,
+    pub light_projection: Transform,
+    pub hither: Float,
+    pub yon: Float,
+    pub screen_bounds: Bounds2f,
+    pub cos_total_width: Float,
+    // inherited from class Light (see light.h)
+    pub flags: u8,
+    pub n_samples: i32,
+    pub medium_interface: MediumInterface,
+    pub light_to_world: Transform,
+    pub world_to_light: Transform,
+}
+
+impl ProjectionLight {
+    pub fn new(
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+        i: &Spectrum,
+        texname: String,
+        fov: Float,
+    ) -> Self {
+        let mut inside: Option<Arc<Medium + Send + Sync>> = None;
+        let mut outside: Option<Arc<Medium + Send + Sync>> = None;
+        if let Some(ref mi_outside) = medium_interface.outside {
+            // in C++: MediumInterface(const Medium *medium) : inside(medium), outside(medium)
+            inside = Some(mi_outside.clone());
+            outside = Some(mi_outside.clone());
+        }
+        // create _ProjectionLight_ MIP map
+        let mut projection_map: Option<Arc<MipMap<Spectrum>>> = None;
+        let mut aspect: Float = 1.0 as Float;
+        if texname != String::from("") {
+            if let Some((texels, resolution)) = read_image(&texname) {
+                let do_trilinear: bool = false;
+                let max_aniso: Float = 8.0 as Float;
+                let wrap_mode: ImageWrap = ImageWrap::Repeat;
+                projection_map = Some(Arc::new(MipMap::new(
+                    &resolution,
+                    &texels[..],
+                    do_trilinear,
+                    max_aniso,
+                    wrap_mode,
+                )));
+                aspect = resolution.x as Float / resolution.y as Float;
+            }
+        }
+        // initialize _ProjectionLight_ projection matrix
+        let screen_bounds: Bounds2f = if aspect > 1.0 as Float {
+            Bounds2f {
+                p_min: Point2f {
+                    x: -aspect,
+                    y: -1.0 as Float,
+                },
+                p_max: Point2f {
+                    x: aspect,
+                    y: 1.0 as Float,
+                },
+            }
+        } else {
+            Bounds2f {
+                p_min: Point2f {
+                    x: -1.0 as Float,
+                    y: -1.0 as Float / aspect,
+                },
+                p_max: Point2f {
+                    x: 1.0 as Float,
+                    y: 1.0 as Float / aspect,
+                },
+            }
+        };
+        let hither: Float = 1e-3 as Float;
+        let yon: Float = 1e30 as Float;
+        let light_projection: Transform = Transform::perspective(fov, hither, yon);
+        // compute cosine of cone surrounding projection directions
+        let screen_to_light: Transform = Transform::inverse(&light_projection);
+        let p_corner: Point3f = Point3f {
+            x: screen_bounds.p_max.x,
+            y: screen_bounds.p_max.y,
+            z: 0.0 as Float,
+        };
+        let p_light_corner: Point3f = screen_to_light.transform_point(&p_corner);
+        let w_corner: Vector3f = Vector3f {
+            x: p_light_corner.x,
+            y: p_light_corner.y,
+            z: p_light_corner.z,
+        }.normalize();
+        ProjectionLight {
+            projection_map: projection_map,
+            p_light: light_to_world.transform_point(&Point3f::default()),
+            i: *i,
+            light_projection: light_projection,
+            hither: hither,
+            yon: yon,
+            screen_bounds: screen_bounds,
+            cos_total_width: w_corner.z,
+            flags: LightFlags::DeltaPosition as u8,
+            n_samples: 1_i32,
+            medium_interface: MediumInterface {
+                inside: inside,
+                outside: outside,
+            },
+            light_to_world: *light_to_world,
+            world_to_light: Transform::inverse(light_to_world),
+        }
+    }
+    pub fn projection(&self, w: &Vector3f) -> Spectrum {
+        let wl: Vector3f = self.world_to_light.transform_vector(w);
+        // discard directions behind projection light
+        if wl.z < self.hither {
+            return Spectrum::default();
+        }
+        // project point onto projection plane and compute light
+        let p: Point3f = self.light_projection.transform_point(&Point3f {
+            x: wl.x,
+            y: wl.y,
+            z: wl.z,
+        });
+        let p2: Point2f = Point2f { x: p.x, y: p.y };
+        if !pnt2_inside_bnd2(&p2, &self.screen_bounds) {
+            return Spectrum::default();
+        }
+        if let Some(ref projection_map) = self.projection_map {
+            let offset: Vector2f = self.screen_bounds.offset(&p2);
+            let st: Point2f = Point2f {
+                x: offset.x,
+                y: offset.y,
+            };
+            projection_map.lookup_pnt_flt(&st, 0.0 as Float)
+        } else {
+            Spectrum::new(1.0 as Float)
+        }
+    }
+}
+
+impl Light for ProjectionLight {
+    fn sample_li(
+        &self,
+        iref: &InteractionCommon,
+        _u: &Point2f,
+        wi: &mut Vector3f,
+        pdf: &mut Float,
+        vis: &mut VisibilityTester,
+    ) -> Spectrum {
+        // TODO: ProfilePhase _(Prof::LightSample);
+        *wi = (self.p_light - iref.p).normalize();
+        *pdf = 1.0 as Float;
+        // medium_interface1
+        let mut inside: Option<Arc<Medium + Send + Sync>> = None;
+        let mut outside: Option<Arc<Medium + Send + Sync>> = None;
+        if let Some(ref mi_arc) = iref.medium_interface {
+            if let Some(ref mi_inside_arc) = mi_arc.get_inside() {
+                inside = Some(mi_inside_arc.clone());
+            }
+            if let Some(ref mi_outside_arc) = mi_arc.get_outside() {
+                outside = Some(mi_outside_arc.clone());
+            }
+        }
+        let medium_interface1_arc: Arc<MediumInterface> =
+            Arc::new(MediumInterface::new(inside, outside));
+        // medium_interface2
+        let mut inside: Option<Arc<Medium + Send + Sync>> = None;
+        let mut outside: Option<Arc<Medium + Send + Sync>> = None;
+        if let Some(ref mi_inside_arc) = self.medium_interface.inside {
+            inside = Some(mi_inside_arc.clone());
+        }
+        if let Some(ref mi_outside_arc) = self.medium_interface.outside {
+            outside = Some(mi_outside_arc.clone());
+        }
+        let medium_interface2_arc: Arc<MediumInterface> =
+            Arc::new(MediumInterface::new(inside, outside));
+        *vis = VisibilityTester {
+            p0: InteractionCommon {
+                p: iref.p,
+                time: iref.time,
+                p_error: iref.p_error,
+                wo: iref.wo,
+                n: iref.n,
+                medium_interface: Some(medium_interface1_arc.clone()),
+            },
+            p1: InteractionCommon {
+                p: self.p_light,
+                time: iref.time,
+                p_error: Vector3f::default(),
+                wo: Vector3f::default(),
+                n: Normal3f::default(),
+                medium_interface: Some(medium_interface2_arc.clone()),
+            },
+        };
+        self.i * self.projection(&-*wi) / pnt3_distance_squared(&self.p_light, &iref.p)
+    }
+    fn power(&self) -> Spectrum {
+        let s: Spectrum = if let Some(ref projection_map) = self.projection_map {
+            projection_map.lookup_pnt_flt(
+                &Point2f {
+                    x: 0.5 as Float,
+                    y: 0.5 as Float,
+                },
+                0.5 as Float,
+            )
+        } else {
+            Spectrum::new(1.0 as Float)
+        };
+        s * self.i * 2.0 as Float * PI * (1.0 as Float - self.cos_total_width)
+    }
+    fn preprocess(&self, _scene: &Scene) {}
+    /// Default implementation returns no emitted radiance for a ray
+    /// that escapes the scene bounds.
+    fn le(&self, _ray: &mut Ray) -> Spectrum {
+        Spectrum::new(0.0 as Float)
+    }
+    fn pdf_li(&self, _iref: &Interaction, _wi: Vector3f) -> Float {
+        0.0 as Float
+    }
+    fn sample_le(
+        &self,
+        u1: &Point2f,
+        _u2: &Point2f,
+        time: Float,
+        ray: &mut Ray,
+        n_light: &mut Normal3f,
+        pdf_pos: &mut Float,
+        pdf_dir: &mut Float,
+    ) -> Spectrum {
+        // TODO: ProfilePhase _(Prof::LightSample);
+        let v: Vector3f = uniform_sample_cone(u1, self.cos_total_width);
+        let mut inside: Option<Arc<Medium + Send + Sync>> = None;
+        if let Some(ref mi_inside) = self.medium_interface.inside {
+            inside = Some(mi_inside.clone());
+        }
+        *ray = Ray {
+            o: self.p_light,
+            d: self.light_to_world.transform_vector(&v),
+            t_max: std::f32::INFINITY,
+            time: time,
+            differential: None,
+            medium: inside,
+        };
+        *n_light = Normal3f::from(ray.d);
+        *pdf_pos = 1.0 as Float;
+        *pdf_dir = uniform_cone_pdf(self.cos_total_width);
+        self.i * self.projection(&ray.d)
+    }
+    fn get_flags(&self) -> u8 {
+        self.flags
+    }
+    fn get_n_samples(&self) -> i32 {
+        self.n_samples
+    }
+    fn pdf_le(&self, ray: &Ray, _n_light: &Normal3f, pdf_pos: &mut Float, pdf_dir: &mut Float) {
+        *pdf_pos = 0.0 as Float;
+        if cos_theta(&self.world_to_light.transform_vector(&ray.d)) >= self.cos_total_width {
+            *pdf_dir = uniform_cone_pdf(self.cos_total_width);
+        } else {
+            *pdf_dir = 0.0 as Float;
+        }
+    }
+}
@@ -3,8 +3,9 @@ use std;
 use std::f32::consts::PI;
 use std::sync::Arc;
 // pbrt
-use core::geometry::{pnt3_distance_squared};
+use core::geometry::pnt3_distance_squared;
 use core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
+use core::ies::IesProfile;
 use core::interaction::{Interaction, InteractionCommon};
 use core::light::{Light, LightFlags, VisibilityTester};
 use core::medium::{Medium, MediumInterface};
@@ -24,6 +25,8 @@ pub struct SpotLight {
     pub i: Spectrum,
     pub cos_total_width: Float,
     pub cos_falloff_start: Float,
+    pub profile: Option<Arc<IesProfile>>,
+    pub profile_integral: Float,
     // inherited from class Light (see light.h)
     pub flags: u8,
     pub n_samples: i32,
@@ -39,6 +42,7 @@ impl SpotLight {
         i: &Spectrum,
         total_width: Float,
         falloff_start: Float,
+        profile: Option<Arc<IesProfile>>,
     ) -> Self {
         let mut inside: Option<Arc<Medium + Send + Sync>> = None;
         let mut outside: Option<Arc<Medium + Send + Sync>> = None;
@@ -47,11 +51,24 @@ impl SpotLight {
             inside = Some(mi_outside.clone());
             outside = Some(mi_outside.clone());
         }
+        let cos_total_width: Float = radians(total_width).cos();
+        let cos_falloff_start: Float = radians(falloff_start).cos();
+        // integrate (optional) IES profile within the spotlight cone
+        let mut profile_integral: Float = 2.0 as Float
+            * PI
+            * (1.0 as Float - 0.5 as Float * (cos_falloff_start + cos_total_width));
+        if let Some(ref ies) = profile {
+            profile_integral = ies.integral_with(&|cos_theta| {
+                SpotLight::smooth_falloff(cos_theta, cos_total_width, cos_falloff_start)
+            });
+        }
         SpotLight {
             p_light: light_to_world.transform_point(&Point3f::default()),
             i: *i,
-            cos_total_width: radians(total_width).cos(),
-            cos_falloff_start: radians(falloff_start).cos(),
+            cos_total_width: cos_total_width,
+            cos_falloff_start: cos_falloff_start,
+            profile: profile,
+            profile_integral: profile_integral,
             flags: LightFlags::DeltaPosition as u8,
             n_samples: 1_i32,
             medium_interface: MediumInterface {
@@ -65,15 +82,26 @@ impl SpotLight {
     pub fn falloff(&self, w: &Vector3f) -> Float {
         let wl: Vector3f = self.world_to_light.transform_vector(w).normalize();
         let cos_theta: Float = wl.z;
-        if cos_theta < self.cos_total_width {
+        let falloff: Float =
+            SpotLight::smooth_falloff(cos_theta, self.cos_total_width, self.cos_falloff_start);
+        if falloff == 0.0 as Float {
+            return falloff;
+        }
+        if let Some(ref ies) = self.profile {
+            falloff * ies.evaluate_direction(&wl)
+        } else {
+            falloff
+        }
+    }
+    fn smooth_falloff(cos_theta: Float, cos_total_width: Float, cos_falloff_start: Float) -> Float {
+        if cos_theta < cos_total_width {
             return 0.0 as Float;
         }
-        if cos_theta >= self.cos_falloff_start {
+        if cos_theta >= cos_falloff_start {
             return 1.0 as Float;
         }
         // compute falloff inside spotlight cone
-        let delta: Float =
-            (cos_theta - self.cos_total_width) / (self.cos_falloff_start - self.cos_total_width);
+        let delta: Float = (cos_theta - cos_total_width) / (cos_falloff_start - cos_total_width);
         (delta * delta) * (delta * delta)
     }
 }
@@ -135,10 +163,7 @@ impl Light for SpotLight {
         self.i * self.falloff(&-*wi) / pnt3_distance_squared(&self.p_light, &iref.p)
     }
     fn power(&self) -> Spectrum {
-        self.i
-            * 2.0 as Float
-            * PI
-            * (1.0 as Float - 0.5 as Float * (self.cos_falloff_start + self.cos_total_width))
+        self.i * self.profile_integral
     }
     fn preprocess(&self, _scene: &Scene) {}
     /// Default implementation returns no emitted radiance for a ray
@@ -7,6 +7,7 @@ use core::geometry::{spherical_direction, vec3_dot_vec3, Point2f, Vector3f};
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
 use core::microfacet::{MicrofacetDistribution, TrowbridgeReitzDistribution};
+use core::mipmap::MipMap;
 use core::paramset::TextureParams;
 use core::pbrt::{clamp_t, lerp, Float, Spectrum};
 use core::reflection::{
@@ -33,11 +34,15 @@ pub struct DisneyMaterial {
     flatness: Arc<Texture<Float> + Send + Sync>,
     diff_trans: Arc<Texture<Float> + Send + Sync>,
     bumpmap: Option<Arc<Texture<Float> + Send + Sync>>,
+    normal_map: Option<Arc<MipMap<Spectrum>>>,
     thin: bool,
 }
 
 impl DisneyMaterial {
-    pub fn create(mp: &mut TextureParams) -> Arc<dyn Material + Send + Sync> {
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<dyn Material + Send + Sync> {
         let color = mp.get_spectrum_texture("color", Spectrum::from(0.5));
         let metallic = mp.get_float_texture("metallic", 0.0);
         let eta = mp.get_float_texture("eta", 1.5);
@@ -71,6 +76,7 @@ impl DisneyMaterial {
             flatness,
             diff_trans,
             bumpmap,
+            normal_map,
             thin,
         })
     }
@@ -83,7 +89,9 @@ impl Material for DisneyMaterial {
         mode: TransportMode,
         _allow_multiple_lobes: bool,
     ) {
-        if let Some(ref bump) = self.bumpmap {
+        if let Some(ref normal_map) = self.normal_map {
+            Self::normal_map(normal_map, si);
+        } else if let Some(ref bump) = self.bumpmap {
             Self::bump(bump, si);
         }
 
@@ -4,8 +4,9 @@ use std::sync::Arc;
 use core::api::BsdfState;
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
+use core::mipmap::MipMap;
 use core::paramset::TextureParams;
-use core::pbrt::Float;
+use core::pbrt::{Float, Spectrum};
 use core::reflection::{Bsdf, Bxdf, FourierBSDF, FourierBSDFTable};
 use core::texture::Texture;
 
@@ -14,20 +15,24 @@ use core::texture::Texture;
 pub struct FourierMaterial {
     pub bsdf_table: Arc<FourierBSDFTable>,
     pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
 }
 
 impl FourierMaterial {
     pub fn new(
         bsdf_table: Arc<FourierBSDFTable>,
         bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
     ) -> Self {
         FourierMaterial {
             bump_map: bump_map,
+            normal_map: normal_map,
             bsdf_table: bsdf_table,
         }
     }
     pub fn create(
         mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
         bsdf_state: &mut BsdfState,
     ) -> Arc<Material + Send + Sync> {
         let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
@@ -35,7 +40,11 @@ impl FourierMaterial {
         let bsdffile: String = mp.find_filename("bsdffile", String::new());
         if let Some(bsdf_table) = bsdf_state.loaded_bsdfs.get(&bsdffile.clone()) {
             // use the BSDF table found
-            Arc::new(FourierMaterial::new(bsdf_table.clone(), bump_map))
+            Arc::new(FourierMaterial::new(
+                bsdf_table.clone(),
+                bump_map,
+                normal_map,
+            ))
         } else {
             // read BSDF table from file
             let mut bsdf_table: FourierBSDFTable = FourierBSDFTable::default();
@@ -46,12 +55,19 @@ impl FourierMaterial {
             );
             let bsdf_table_arc: Arc<FourierBSDFTable> = Arc::new(bsdf_table);
             // TODO: bsdf_state.loaded_bsdfs.insert(bsdffile.clone(), bsdf_table_arc.clone());
-            Arc::new(FourierMaterial::new(bsdf_table_arc.clone(), bump_map))
+            Arc::new(FourierMaterial::new(
+                bsdf_table_arc.clone(),
+                bump_map,
+                normal_map,
+            ))
         }
     }
     pub fn bsdf(&self, si: &mut SurfaceInteraction, mode: TransportMode) -> Bsdf {
         let mut bxdfs: Vec<Arc<Bxdf + Send + Sync>> = Vec::new();
-        if let Some(ref bump_map) = self.bump_map {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            FourierMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
             FourierMaterial::bump(bump_map, si);
         }
         bxdfs.push(Arc::new(FourierBSDF::new(self.bsdf_table.clone(), mode)));
@@ -5,6 +5,8 @@ use std::sync::Arc;
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
 use core::microfacet::TrowbridgeReitzDistribution;
+use core::mipmap::MipMap;
+use core::paramset::TextureParams;
 use core::pbrt::{Float, Spectrum};
 use core::reflection::{
     Bsdf, Bxdf, FresnelDielectric, FresnelSpecular, MicrofacetReflection, SpecularReflection,
@@ -21,11 +23,60 @@ pub struct GlassMaterial {
     pub kt: Arc<Texture<Spectrum> + Sync + Send>, // default: 1.0
     pub u_roughness: Arc<Texture<Float> + Sync + Send>, // default: 0.0
     pub v_roughness: Arc<Texture<Float> + Sync + Send>, // default: 0.0
-    pub index: Arc<Texture<Float> + Sync + Send>, // TODO: bump_map
+    pub index: Arc<Texture<Float> + Sync + Send>,
+    pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
     pub remap_roughness: bool,
 }
 
 impl GlassMaterial {
+    pub fn new(
+        kr: Arc<Texture<Spectrum> + Send + Sync>,
+        kt: Arc<Texture<Spectrum> + Send + Sync>,
+        u_roughness: Arc<Texture<Float> + Sync + Send>,
+        v_roughness: Arc<Texture<Float> + Sync + Send>,
+        index: Arc<Texture<Float> + Sync + Send>,
+        bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+        remap_roughness: bool,
+    ) -> Self {
+        GlassMaterial {
+            kr: kr,
+            kt: kt,
+            u_roughness: u_roughness,
+            v_roughness: v_roughness,
+            index: index,
+            bump_map: bump_map,
+            normal_map: normal_map,
+            remap_roughness: remap_roughness,
+        }
+    }
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
+        let kr: Arc<Texture<Spectrum> + Sync + Send> =
+            mp.get_spectrum_texture("Kr", Spectrum::new(1.0));
+        let kt: Arc<Texture<Spectrum> + Sync + Send> =
+            mp.get_spectrum_texture("Kt", Spectrum::new(1.0));
+        // TODO: std::shared_ptr<Texture<Float>> eta = mp.GetFloatTextureOrNull("eta");
+        let eta: Arc<Texture<Float> + Sync + Send> = mp.get_float_texture("index", 1.5);
+        let roughu: Arc<Texture<Float> + Sync + Send> = mp.get_float_texture("uroughness", 0.0);
+        let roughv: Arc<Texture<Float> + Sync + Send> = mp.get_float_texture("vroughness", 0.0);
+        let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
+            mp.get_float_texture_or_null("bumpmap");
+        let remap_roughness: bool = mp.find_bool("remaproughness", true);
+        Arc::new(GlassMaterial::new(
+            kr,
+            kt,
+            roughu,
+            roughv,
+            eta,
+            bump_map,
+            normal_map,
+            remap_roughness,
+        ))
+    }
     pub fn bsdf(
         &self,
         si: &SurfaceInteraction,
@@ -91,6 +142,12 @@ impl Material for GlassMaterial {
         mode: TransportMode,
         allow_multiple_lobes: bool,
     ) {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            GlassMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
+            GlassMaterial::bump(bump_map, si);
+        }
         si.bsdf = Some(Arc::new(self.bsdf(si, mode, allow_multiple_lobes)));
     }
 }
@@ -6,6 +6,7 @@ use std::sync::Arc;
 use core::geometry::{Point2f, Vector3f};
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
+use core::mipmap::MipMap;
 use core::paramset::TextureParams;
 use core::pbrt::{clamp_t, radians};
 use core::pbrt::{Float, Spectrum};
@@ -23,6 +24,8 @@ pub struct HairMaterial {
     pub beta_m: Arc<Texture<Float> + Sync + Send>, // default: 0.3
     pub beta_n: Arc<Texture<Float> + Sync + Send>, // default: 0.3
     pub alpha: Arc<Texture<Float> + Sync + Send>, // default: 2.0
+    pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
 }
 
 impl HairMaterial {
@@ -35,6 +38,8 @@ impl HairMaterial {
         beta_m: Arc<Texture<Float> + Send + Sync>,
         beta_n: Arc<Texture<Float> + Send + Sync>,
         alpha: Arc<Texture<Float> + Send + Sync>,
+        bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
     ) -> Self {
         HairMaterial {
             sigma_a: sigma_a,
@@ -45,9 +50,14 @@ impl HairMaterial {
             beta_m: beta_m,
             beta_n: beta_n,
             alpha: alpha,
+            bump_map: bump_map,
+            normal_map: normal_map,
         }
     }
-    pub fn create(mp: &mut TextureParams) -> Arc<Material + Send + Sync> {
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
         let mut sigma_a: Option<Arc<Texture<Spectrum> + Send + Sync>> =
             mp.get_spectrum_texture_or_null("sigma_a");
         let color: Option<Arc<Texture<Spectrum> + Send + Sync>> =
@@ -112,6 +122,8 @@ impl HairMaterial {
         let beta_m = mp.get_float_texture("beta_m", 0.3);
         let beta_n = mp.get_float_texture("beta_n", 0.3);
         let alpha = mp.get_float_texture("alpha", 2.0);
+        let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
+            mp.get_float_texture_or_null("bumpmap");
         Arc::new(HairMaterial::new(
             sigma_a,
             color,
@@ -121,6 +133,8 @@ impl HairMaterial {
             beta_m,
             beta_n,
             alpha,
+            bump_map,
+            normal_map,
         ))
     }
     pub fn bsdf(&self, si: &SurfaceInteraction) -> Bsdf {
@@ -166,6 +180,12 @@ impl Material for HairMaterial {
         _mode: TransportMode,
         _allow_multiple_lobes: bool,
     ) {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            HairMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
+            HairMaterial::bump(bump_map, si);
+        }
         si.bsdf = Some(Arc::new(self.bsdf(si)));
     }
 }
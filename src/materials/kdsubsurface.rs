@@ -0,0 +1,225 @@
+//std
+use std;
+use std::sync::Arc;
+// pbrt
+use core::bssrdf::{compute_beam_diffusion_bssrdf, subsurface_from_diffuse};
+use core::bssrdf::{BssrdfTable, TabulatedBssrdf};
+use core::interaction::SurfaceInteraction;
+use core::material::{Material, TransportMode};
+use core::microfacet::{MicrofacetDistribution, TrowbridgeReitzDistribution};
+use core::mipmap::MipMap;
+use core::paramset::TextureParams;
+use core::pbrt::{Float, Spectrum};
+use core::primitive::Primitive;
+use core::reflection::{
+    Bsdf, Bxdf, FresnelDielectric, FresnelSpecular, MicrofacetReflection, MicrofacetTransmission,
+    SpecularReflection, SpecularTransmission,
+};
+use core::texture::Texture;
+
+// see kdsubsurface.h
+
+/// Subsurface scattering material which is specified by its diffuse
+/// reflectance and the mean free path instead of scattering
+/// coefficients.
+pub struct KdSubsurfaceMaterial {
+    pub scale: Float,
+    pub kd: Arc<Texture<Spectrum> + Sync + Send>, // default: 0.5
+    pub kr: Arc<Texture<Spectrum> + Sync + Send>, // default: 1.0
+    pub kt: Arc<Texture<Spectrum> + Sync + Send>, // default: 1.0
+    pub mfp: Arc<Texture<Spectrum> + Sync + Send>, // default: 1.0
+    pub u_roughness: Arc<Texture<Float> + Sync + Send>, // default: 0.0
+    pub v_roughness: Arc<Texture<Float> + Sync + Send>, // default: 0.0
+    pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
+    pub eta: Float, // default: 1.33
+    pub remap_roughness: bool,
+    pub table: Arc<BssrdfTable>,
+}
+
+impl KdSubsurfaceMaterial {
+    pub fn new(
+        scale: Float,
+        kd: Arc<Texture<Spectrum> + Sync + Send>,
+        kr: Arc<Texture<Spectrum> + Sync + Send>,
+        kt: Arc<Texture<Spectrum> + Sync + Send>,
+        mfp: Arc<Texture<Spectrum> + Sync + Send>,
+        g: Float,
+        eta: Float,
+        u_roughness: Arc<Texture<Float> + Sync + Send>,
+        v_roughness: Arc<Texture<Float> + Sync + Send>,
+        bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+        remap_roughness: bool,
+    ) -> Self {
+        let mut table: BssrdfTable = BssrdfTable::new(100, 64);
+        compute_beam_diffusion_bssrdf(g, eta, &mut table);
+        KdSubsurfaceMaterial {
+            scale: scale,
+            kd: kd,
+            kr: kr,
+            kt: kt,
+            mfp: mfp,
+            u_roughness: u_roughness,
+            v_roughness: v_roughness,
+            bump_map: bump_map,
+            normal_map: normal_map,
+            eta: eta,
+            remap_roughness: remap_roughness,
+            table: Arc::new(table),
+        }
+    }
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
+        let kd_rgb: [Float; 3] = [0.5, 0.5, 0.5];
+        let kd: Arc<Texture<Spectrum> + Send + Sync> =
+            mp.get_spectrum_texture("Kd", Spectrum::from_rgb(&kd_rgb));
+        let mfp: Arc<Texture<Spectrum> + Send + Sync> =
+            mp.get_spectrum_texture("mfp", Spectrum::new(1.0 as Float));
+        let kr: Arc<Texture<Spectrum> + Send + Sync> =
+            mp.get_spectrum_texture("Kr", Spectrum::new(1.0 as Float));
+        let kt: Arc<Texture<Spectrum> + Send + Sync> =
+            mp.get_spectrum_texture("Kt", Spectrum::new(1.0 as Float));
+        let roughu: Arc<Texture<Float> + Send + Sync> =
+            mp.get_float_texture("uroughness", 0.0 as Float);
+        let roughv: Arc<Texture<Float> + Send + Sync> =
+            mp.get_float_texture("vroughness", 0.0 as Float);
+        let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
+            mp.get_float_texture_or_null("bumpmap");
+        let eta: Float = mp.find_float("eta", 1.33 as Float);
+        let scale: Float = mp.find_float("scale", 1.0 as Float);
+        let g: Float = mp.find_float("g", 0.0 as Float);
+        let remap_roughness: bool = mp.find_bool("remaproughness", true);
+        Arc::new(KdSubsurfaceMaterial::new(
+            scale,
+            kd,
+            kr,
+            kt,
+            mfp,
+            g,
+            eta,
+            roughu,
+            roughv,
+            bump_map,
+            normal_map,
+            remap_roughness,
+        ))
+    }
+    pub fn bsdf(
+        &self,
+        si: &mut SurfaceInteraction,
+        mode: TransportMode,
+        allow_multiple_lobes: bool,
+    ) -> Bsdf {
+        let mut bxdfs: Vec<Arc<Bxdf + Send + Sync>> = Vec::new();
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            KdSubsurfaceMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
+            KdSubsurfaceMaterial::bump(bump_map, si);
+        }
+        let r: Spectrum = self
+            .kr
+            .evaluate(si)
+            .clamp(0.0 as Float, std::f32::INFINITY as Float);
+        let t: Spectrum = self
+            .kt
+            .evaluate(si)
+            .clamp(0.0 as Float, std::f32::INFINITY as Float);
+        let mut urough: Float = self.u_roughness.evaluate(si);
+        let mut vrough: Float = self.v_roughness.evaluate(si);
+        if r.is_black() && t.is_black() {
+            return Bsdf::new(si, self.eta, bxdfs);
+        }
+        let is_specular: bool = urough == 0.0 as Float && vrough == 0.0 as Float;
+        if is_specular && allow_multiple_lobes {
+            bxdfs.push(Arc::new(FresnelSpecular::new(
+                r,
+                t,
+                1.0 as Float,
+                self.eta,
+                mode,
+            )));
+        } else {
+            if self.remap_roughness {
+                urough = TrowbridgeReitzDistribution::roughness_to_alpha(urough);
+                vrough = TrowbridgeReitzDistribution::roughness_to_alpha(vrough);
+            }
+            let distrib: Arc<MicrofacetDistribution + Send + Sync> =
+                Arc::new(TrowbridgeReitzDistribution::new(urough, vrough, true));
+            if !r.is_black() {
+                let fresnel = Arc::new(FresnelDielectric {
+                    eta_i: 1.0 as Float,
+                    eta_t: self.eta,
+                });
+                if is_specular {
+                    bxdfs.push(Arc::new(SpecularReflection::new(r, fresnel)));
+                } else {
+                    bxdfs.push(Arc::new(MicrofacetReflection::new(
+                        r,
+                        distrib.clone(),
+                        fresnel,
+                    )));
+                }
+            }
+            if !t.is_black() {
+                if is_specular {
+                    bxdfs.push(Arc::new(SpecularTransmission::new(
+                        t,
+                        1.0 as Float,
+                        self.eta,
+                        mode,
+                    )));
+                } else {
+                    bxdfs.push(Arc::new(MicrofacetTransmission::new(
+                        t,
+                        distrib,
+                        1.0 as Float,
+                        self.eta,
+                        mode,
+                    )));
+                }
+            }
+        }
+        Bsdf::new(si, self.eta, bxdfs)
+    }
+}
+
+impl Material for KdSubsurfaceMaterial {
+    fn compute_scattering_functions(
+        &self,
+        si: &mut SurfaceInteraction,
+        // arena: &mut Arena,
+        mode: TransportMode,
+        allow_multiple_lobes: bool,
+    ) {
+        si.bsdf = Some(Arc::new(self.bsdf(si, mode, allow_multiple_lobes)));
+        let mfree: Spectrum = self
+            .mfp
+            .evaluate(si)
+            .clamp(0.0 as Float, std::f32::INFINITY as Float)
+            * self.scale;
+        let kd: Spectrum = self
+            .kd
+            .evaluate(si)
+            .clamp(0.0 as Float, std::f32::INFINITY as Float);
+        let mut sig_a: Spectrum = Spectrum::default();
+        let mut sig_s: Spectrum = Spectrum::default();
+        subsurface_from_diffuse(&self.table, &kd, &mfree, &mut sig_a, &mut sig_s);
+        if let Some(primitive) = si.primitive {
+            if let Some(material) = primitive.get_material() {
+                si.bssrdf = Some(Arc::new(TabulatedBssrdf::new(
+                    si,
+                    material,
+                    mode,
+                    self.eta,
+                    &sig_a,
+                    &sig_s,
+                    self.table.clone(),
+                )));
+            }
+        }
+    }
+}
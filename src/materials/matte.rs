@@ -4,6 +4,7 @@ use std::sync::Arc;
 // pbrt
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
+use core::mipmap::MipMap;
 use core::paramset::TextureParams;
 use core::pbrt::clamp_t;
 use core::pbrt::{Float, Spectrum};
@@ -16,24 +17,34 @@ use core::texture::Texture;
 pub struct MatteMaterial {
     pub kd: Arc<Texture<Spectrum> + Sync + Send>, // default: 0.5
     pub sigma: Arc<Texture<Float> + Sync + Send>, // default: 0.0
-                                                  // TODO: bump_map
+    pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
 }
 
 impl MatteMaterial {
     pub fn new(
         kd: Arc<Texture<Spectrum> + Send + Sync>,
         sigma: Arc<Texture<Float> + Sync + Send>,
+        bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
     ) -> Self {
         MatteMaterial {
             kd: kd,
             sigma: sigma,
+            bump_map: bump_map,
+            normal_map: normal_map,
         }
     }
-    pub fn create(mp: &mut TextureParams) -> Arc<Material + Send + Sync> {
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
         let kd: Arc<Texture<Spectrum> + Sync + Send> =
             mp.get_spectrum_texture("Kd", Spectrum::new(0.5));
         let sigma: Arc<Texture<Float> + Sync + Send> = mp.get_float_texture("sigma", 0.0);
-        Arc::new(MatteMaterial { kd, sigma })
+        let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
+            mp.get_float_texture_or_null("bumpmap");
+        Arc::new(MatteMaterial::new(kd, sigma, bump_map, normal_map))
     }
     pub fn bsdf(&self, si: &SurfaceInteraction) -> Bsdf {
         let mut bxdfs: Vec<Arc<Bxdf + Send + Sync>> = Vec::new();
@@ -65,6 +76,12 @@ impl Material for MatteMaterial {
         _mode: TransportMode,
         _allow_multiple_lobes: bool,
     ) {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            MatteMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
+            MatteMaterial::bump(bump_map, si);
+        }
         si.bsdf = Some(Arc::new(self.bsdf(si)));
     }
 }
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
 use core::microfacet::TrowbridgeReitzDistribution;
+use core::mipmap::MipMap;
 use core::paramset::TextureParams;
 use core::pbrt::{Float, Spectrum};
 use core::reflection::{Bsdf, Bxdf, FresnelConductor, MicrofacetReflection};
@@ -90,7 +91,8 @@ pub struct MetalMaterial {
     pub roughness: Arc<Texture<Float> + Sync + Send>, // default: 0.01
     pub u_roughness: Option<Arc<Texture<Float> + Sync + Send>>,
     pub v_roughness: Option<Arc<Texture<Float> + Sync + Send>>,
-    // TODO: bump_map
+    pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
     pub remap_roughness: bool,
 }
 
@@ -101,6 +103,8 @@ impl MetalMaterial {
         roughness: Arc<Texture<Float> + Sync + Send>,
         u_roughness: Option<Arc<Texture<Float> + Sync + Send>>,
         v_roughness: Option<Arc<Texture<Float> + Sync + Send>>,
+        bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
         remap_roughness: bool,
     ) -> Self {
         MetalMaterial {
@@ -109,10 +113,15 @@ impl MetalMaterial {
             roughness: roughness,
             u_roughness: u_roughness,
             v_roughness: v_roughness,
+            bump_map: bump_map,
+            normal_map: normal_map,
             remap_roughness: remap_roughness,
         }
     }
-    pub fn create(mp: &mut TextureParams) -> Arc<Material + Send + Sync> {
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
         let copper_n: Spectrum =
             Spectrum::from_sampled(&COPPER_WAVELENGTHS, &COPPER_N, COPPER_SAMPLES as i32);
         let eta: Arc<Texture<Spectrum> + Send + Sync> = mp.get_spectrum_texture("eta", copper_n);
@@ -125,7 +134,8 @@ impl MetalMaterial {
             mp.get_float_texture_or_null("uroughness");
         let v_roughness: Option<Arc<Texture<Float> + Send + Sync>> =
             mp.get_float_texture_or_null("vroughness");
-        // TODO: std::shared_ptr<Texture<Float>> bumpMap = mp.GetFloatTextureOrNull("bumpmap");
+        let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
+            mp.get_float_texture_or_null("bumpmap");
         let remap_roughness: bool = mp.find_bool("remaproughness", true);
         Arc::new(MetalMaterial::new(
             eta,
@@ -133,6 +143,8 @@ impl MetalMaterial {
             roughness,
             u_roughness,
             v_roughness,
+            bump_map,
+            normal_map,
             remap_roughness,
         ))
     }
@@ -177,6 +189,12 @@ impl Material for MetalMaterial {
         _mode: TransportMode,
         _allow_multiple_lobes: bool,
     ) {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            MetalMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
+            MetalMaterial::bump(bump_map, si);
+        }
         si.bsdf = Some(Arc::new(self.bsdf(si)));
     }
 }
@@ -4,6 +4,8 @@ use std::sync::Arc;
 // pbrt
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
+use core::mipmap::MipMap;
+use core::paramset::TextureParams;
 use core::pbrt::{Float, Spectrum};
 use core::reflection::{Bsdf, Bxdf, FresnelNoOp, SpecularReflection};
 use core::texture::Texture;
@@ -13,12 +15,31 @@ use core::texture::Texture;
 /// A simple mirror, modeled with perfect specular reflection.
 pub struct MirrorMaterial {
     pub kr: Arc<Texture<Spectrum> + Sync + Send>, // default: 0.9
-                                                  // TODO: bump_map
+    pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
 }
 
 impl MirrorMaterial {
-    pub fn new(kr: Arc<Texture<Spectrum> + Send + Sync>) -> Self {
-        MirrorMaterial { kr: kr }
+    pub fn new(
+        kr: Arc<Texture<Spectrum> + Send + Sync>,
+        bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Self {
+        MirrorMaterial {
+            kr: kr,
+            bump_map: bump_map,
+            normal_map: normal_map,
+        }
+    }
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
+        let kr: Arc<Texture<Spectrum> + Sync + Send> =
+            mp.get_spectrum_texture("Kr", Spectrum::new(0.9));
+        let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
+            mp.get_float_texture_or_null("bumpmap");
+        Arc::new(MirrorMaterial::new(kr, bump_map, normal_map))
     }
     pub fn bsdf(&self, si: &SurfaceInteraction) -> Bsdf {
         let mut bxdfs: Vec<Arc<Bxdf + Send + Sync>> = Vec::new();
@@ -40,6 +61,12 @@ impl Material for MirrorMaterial {
         _mode: TransportMode,
         _allow_multiple_lobes: bool,
     ) {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            MirrorMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
+            MirrorMaterial::bump(bump_map, si);
+        }
         si.bsdf = Some(Arc::new(self.bsdf(si)));
     }
 }
@@ -28,10 +28,13 @@ pub mod disney;
 pub mod fourier;
 pub mod glass;
 pub mod hair;
+pub mod kdsubsurface;
 pub mod matte;
 pub mod metal;
 pub mod mirror;
 pub mod mixmat;
 pub mod plastic;
 pub mod substrate;
+pub mod subsurface;
+pub mod translucent;
 pub mod uber;
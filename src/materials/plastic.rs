@@ -5,6 +5,8 @@ use std::sync::Arc;
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
 use core::microfacet::TrowbridgeReitzDistribution;
+use core::mipmap::MipMap;
+use core::paramset::TextureParams;
 use core::pbrt::{Float, Spectrum};
 use core::reflection::{Bsdf, Bxdf, FresnelDielectric, LambertianReflection, MicrofacetReflection};
 use core::texture::Texture;
@@ -17,7 +19,8 @@ pub struct PlasticMaterial {
     pub kd: Arc<Texture<Spectrum> + Sync + Send>, // default: 0.25
     pub ks: Arc<Texture<Spectrum> + Sync + Send>, // default: 0.25
     pub roughness: Arc<Texture<Float> + Sync + Send>, // default: 0.1
-    // TODO: bump_map
+    pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
     pub remap_roughness: bool,
 }
 
@@ -26,15 +29,40 @@ impl PlasticMaterial {
         kd: Arc<Texture<Spectrum> + Send + Sync>,
         ks: Arc<Texture<Spectrum> + Send + Sync>,
         roughness: Arc<Texture<Float> + Sync + Send>,
+        bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
         remap_roughness: bool,
     ) -> Self {
         PlasticMaterial {
             kd: kd,
             ks: ks,
             roughness: roughness,
+            bump_map: bump_map,
+            normal_map: normal_map,
             remap_roughness: remap_roughness,
         }
     }
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
+        let kd: Arc<Texture<Spectrum> + Sync + Send> =
+            mp.get_spectrum_texture("Kd", Spectrum::new(0.25));
+        let ks: Arc<Texture<Spectrum> + Sync + Send> =
+            mp.get_spectrum_texture("Ks", Spectrum::new(0.25));
+        let roughness: Arc<Texture<Float> + Sync + Send> = mp.get_float_texture("roughness", 0.1);
+        let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
+            mp.get_float_texture_or_null("bumpmap");
+        let remap_roughness: bool = mp.find_bool("remaproughness", true);
+        Arc::new(PlasticMaterial::new(
+            kd,
+            ks,
+            roughness,
+            bump_map,
+            normal_map,
+            remap_roughness,
+        ))
+    }
     pub fn bsdf(&self, si: &SurfaceInteraction) -> Bsdf {
         let mut bxdfs: Vec<Arc<Bxdf + Send + Sync>> = Vec::new();
         // initialize diffuse component of plastic material
@@ -75,6 +103,12 @@ impl Material for PlasticMaterial {
         _mode: TransportMode,
         _allow_multiple_lobes: bool,
     ) {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            PlasticMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
+            PlasticMaterial::bump(bump_map, si);
+        }
         si.bsdf = Some(Arc::new(self.bsdf(si)));
     }
 }
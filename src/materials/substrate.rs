@@ -5,6 +5,7 @@ use std::sync::Arc;
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
 use core::microfacet::TrowbridgeReitzDistribution;
+use core::mipmap::MipMap;
 use core::paramset::TextureParams;
 use core::pbrt::{Float, Spectrum};
 use core::reflection::{Bsdf, Bxdf, FresnelBlend};
@@ -17,7 +18,8 @@ pub struct SubstrateMaterial {
     pub ks: Arc<Texture<Spectrum> + Sync + Send>, // default: 0.5
     pub nu: Arc<Texture<Float> + Sync + Send>,    // default: 0.1
     pub nv: Arc<Texture<Float> + Sync + Send>,    // default: 0.1
-    // TODO: bump_map
+    pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
     pub remap_roughness: bool,
 }
 
@@ -27,7 +29,8 @@ impl SubstrateMaterial {
         ks: Arc<Texture<Spectrum> + Send + Sync>,
         nu: Arc<Texture<Float> + Sync + Send>,
         nv: Arc<Texture<Float> + Sync + Send>,
-        // TODO: bump_map
+        bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
         remap_roughness: bool,
     ) -> Self {
         SubstrateMaterial {
@@ -35,22 +38,31 @@ impl SubstrateMaterial {
             ks: ks,
             nu: nu,
             nv: nv,
+            bump_map: bump_map,
+            normal_map: normal_map,
             remap_roughness: remap_roughness,
         }
     }
-    pub fn create(mp: &mut TextureParams) -> Arc<Material + Send + Sync> {
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
         let kd: Arc<Texture<Spectrum> + Sync + Send> =
             mp.get_spectrum_texture("Kd", Spectrum::new(0.5));
         let ks: Arc<Texture<Spectrum> + Sync + Send> =
             mp.get_spectrum_texture("Ks", Spectrum::new(0.5));
         let uroughness: Arc<Texture<Float> + Sync + Send> = mp.get_float_texture("uroughness", 0.1);
         let vroughness: Arc<Texture<Float> + Sync + Send> = mp.get_float_texture("vroughness", 0.1);
+        let bump_map: Option<Arc<Texture<Float> + Send + Sync>> =
+            mp.get_float_texture_or_null("bumpmap");
         let remap_roughness: bool = mp.find_bool("remaproughness", true);
         Arc::new(SubstrateMaterial::new(
             kd,
             ks,
             uroughness,
             vroughness,
+            bump_map,
+            normal_map,
             remap_roughness,
         ))
     }
@@ -87,6 +99,12 @@ impl Material for SubstrateMaterial {
         _mode: TransportMode,
         _allow_multiple_lobes: bool,
     ) {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            SubstrateMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
+            SubstrateMaterial::bump(bump_map, si);
+        }
         si.bsdf = Some(Arc::new(self.bsdf(si)));
     }
 }
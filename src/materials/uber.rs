@@ -5,6 +5,7 @@ use std::sync::Arc;
 use core::interaction::SurfaceInteraction;
 use core::material::{Material, TransportMode};
 use core::microfacet::TrowbridgeReitzDistribution;
+use core::mipmap::MipMap;
 use core::paramset::TextureParams;
 use core::pbrt::{Float, Spectrum};
 use core::reflection::{
@@ -26,6 +27,7 @@ pub struct UberMaterial {
     pub v_roughness: Option<Arc<Texture<Float> + Sync + Send>>,
     pub eta: Arc<Texture<Float> + Sync + Send>, // default: 1.5
     pub bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+    pub normal_map: Option<Arc<MipMap<Spectrum>>>,
     pub remap_roughness: bool,
 }
 
@@ -41,6 +43,7 @@ impl UberMaterial {
         opacity: Arc<Texture<Spectrum> + Sync + Send>,
         eta: Arc<Texture<Float> + Send + Sync>,
         bump_map: Option<Arc<Texture<Float> + Sync + Send>>,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
         remap_roughness: bool,
     ) -> Self {
         UberMaterial {
@@ -54,10 +57,14 @@ impl UberMaterial {
             v_roughness: v_roughness,
             eta: eta,
             bump_map: bump_map,
+            normal_map: normal_map,
             remap_roughness: remap_roughness,
         }
     }
-    pub fn create(mp: &mut TextureParams) -> Arc<Material + Send + Sync> {
+    pub fn create(
+        mp: &mut TextureParams,
+        normal_map: Option<Arc<MipMap<Spectrum>>>,
+    ) -> Arc<Material + Send + Sync> {
         let kd: Arc<Texture<Spectrum> + Sync + Send> =
             mp.get_spectrum_texture("Kd", Spectrum::new(0.25));
         let ks: Arc<Texture<Spectrum> + Sync + Send> =
@@ -91,6 +98,7 @@ impl UberMaterial {
                 opacity,
                 eta.clone(),
                 bump_map,
+                normal_map,
                 remap_roughness,
             ))
         } else {
@@ -106,13 +114,17 @@ impl UberMaterial {
                 opacity,
                 eta,
                 bump_map,
+                normal_map,
                 remap_roughness,
             ))
         }
     }
     pub fn bsdf(&self, si: &mut SurfaceInteraction, mode: TransportMode) -> Bsdf {
         let mut bxdfs: Vec<Arc<Bxdf + Send + Sync>> = Vec::new();
-        if let Some(ref bump_map) = self.bump_map {
+        // perform normal or bump mapping, if present
+        if let Some(ref normal_map) = self.normal_map {
+            UberMaterial::normal_map(normal_map, si);
+        } else if let Some(ref bump_map) = self.bump_map {
             UberMaterial::bump(bump_map, si);
         }
         let e: Float = self.eta.evaluate(si);
@@ -0,0 +1,241 @@
+// std
+use std::sync::Arc;
+// pbrt
+use core::geometry::{Bounds3f, Point3f, Point3i, Ray, Vector3f};
+use core::interaction::MediumInteraction;
+use core::medium::{HenyeyGreenstein, Medium};
+use core::pbrt::lerp;
+use core::pbrt::{Float, Spectrum};
+use core::sampler::Sampler;
+use core::transform::Transform;
+
+// see grid.h
+
+#[derive(Clone)]
+pub struct GridDensityMedium {
+    pub sigma_a: Spectrum,
+    pub sigma_s: Spectrum,
+    pub g: Float,
+    pub nx: i32,
+    pub ny: i32,
+    pub nz: i32,
+    pub world_to_medium: Transform,
+    pub density: Arc<Vec<Float>>,
+    pub sigma_t: Float,
+    pub inv_max_density: Float,
+}
+
+impl GridDensityMedium {
+    pub fn new(
+        sigma_a: &Spectrum,
+        sigma_s: &Spectrum,
+        g: Float,
+        nx: i32,
+        ny: i32,
+        nz: i32,
+        medium_to_world: &Transform,
+        d: Vec<Float>,
+    ) -> Self {
+        // precompute values for Monte Carlo sampling of _GridDensityMedium_
+        let sigma_t: Float = (*sigma_a + *sigma_s)[0];
+        let sigma_t_spectrum: Spectrum = *sigma_a + *sigma_s;
+        if sigma_t_spectrum[1] != sigma_t || sigma_t_spectrum[2] != sigma_t {
+            println!(
+                "ERROR: GridDensityMedium requires a spectrally uniform attenuation coefficient!"
+            );
+        }
+        let mut max_density: Float = 0.0 as Float;
+        for i in 0..(nx * ny * nz) as usize {
+            max_density = max_density.max(d[i]);
+        }
+        GridDensityMedium {
+            sigma_a: *sigma_a,
+            sigma_s: *sigma_s,
+            g: g,
+            nx: nx,
+            ny: ny,
+            nz: nz,
+            world_to_medium: Transform::inverse(medium_to_world),
+            density: Arc::new(d),
+            sigma_t: sigma_t,
+            inv_max_density: 1.0 as Float / max_density,
+        }
+    }
+    /// Returns the density at an integer sample position (zero
+    /// outside of the grid).
+    pub fn d(&self, p: &Point3i) -> Float {
+        if p.x < 0 || p.x >= self.nx || p.y < 0 || p.y >= self.ny || p.z < 0 || p.z >= self.nz {
+            return 0.0 as Float;
+        }
+        self.density[((p.z * self.ny + p.y) * self.nx + p.x) as usize]
+    }
+    /// Trilinearly interpolates the density at a point in the
+    /// medium's [0,1]^3 space.
+    pub fn density(&self, p: &Point3f) -> Float {
+        // compute voxel coordinates and offsets for _p_
+        let p_samples: Point3f = Point3f {
+            x: p.x * self.nx as Float - 0.5 as Float,
+            y: p.y * self.ny as Float - 0.5 as Float,
+            z: p.z * self.nz as Float - 0.5 as Float,
+        };
+        let pi: Point3i = Point3i {
+            x: p_samples.x.floor() as i32,
+            y: p_samples.y.floor() as i32,
+            z: p_samples.z.floor() as i32,
+        };
+        let d: Vector3f = Vector3f {
+            x: p_samples.x - pi.x as Float,
+            y: p_samples.y - pi.y as Float,
+            z: p_samples.z - pi.z as Float,
+        };
+        // trilinearly interpolate density values to compute local density
+        let d00: Float = lerp(
+            d.x,
+            self.d(&pi),
+            self.d(&Point3i {
+                x: pi.x + 1,
+                y: pi.y,
+                z: pi.z,
+            }),
+        );
+        let d10: Float = lerp(
+            d.x,
+            self.d(&Point3i {
+                x: pi.x,
+                y: pi.y + 1,
+                z: pi.z,
+            }),
+            self.d(&Point3i {
+                x: pi.x + 1,
+                y: pi.y + 1,
+                z: pi.z,
+            }),
+        );
+        let d01: Float = lerp(
+            d.x,
+            self.d(&Point3i {
+                x: pi.x,
+                y: pi.y,
+                z: pi.z + 1,
+            }),
+            self.d(&Point3i {
+                x: pi.x + 1,
+                y: pi.y,
+                z: pi.z + 1,
+            }),
+        );
+        let d11: Float = lerp(
+            d.x,
+            self.d(&Point3i {
+                x: pi.x,
+                y: pi.y + 1,
+                z: pi.z + 1,
+            }),
+            self.d(&Point3i {
+                x: pi.x + 1,
+                y: pi.y + 1,
+                z: pi.z + 1,
+            }),
+        );
+        let d0: Float = lerp(d.y, d00, d10);
+        let d1: Float = lerp(d.y, d01, d11);
+        lerp(d.z, d0, d1)
+    }
+    fn to_medium_space(&self, r_world: &Ray) -> Ray {
+        let d_length: Float = r_world.d.length();
+        self.world_to_medium.transform_ray(&Ray {
+            o: r_world.o,
+            d: r_world.d.normalize(),
+            t_max: r_world.t_max * d_length,
+            time: r_world.time,
+            differential: None,
+            medium: None,
+        })
+    }
+}
+
+impl Medium for GridDensityMedium {
+    fn tr(&self, r_world: &Ray, sampler: &mut Sampler) -> Spectrum {
+        // TODO: ProfilePhase _(Prof::MediumTr);
+        let ray: Ray = self.to_medium_space(r_world);
+        // compute $[\tmin, \tmax]$ interval of _ray_'s overlap with medium bounds
+        let b: Bounds3f = Bounds3f {
+            p_min: Point3f::default(),
+            p_max: Point3f {
+                x: 1.0 as Float,
+                y: 1.0 as Float,
+                z: 1.0 as Float,
+            },
+        };
+        let mut t_min: Float = 0.0 as Float;
+        let mut t_max: Float = 0.0 as Float;
+        if !b.intersect_b(&ray, &mut t_min, &mut t_max) {
+            return Spectrum::new(1.0 as Float);
+        }
+        // perform ratio tracking to estimate the transmittance value
+        let mut tr: Float = 1.0 as Float;
+        let mut t: Float = t_min;
+        loop {
+            t -= (1.0 as Float - sampler.get_1d()).ln() * self.inv_max_density / self.sigma_t;
+            if t >= t_max {
+                break;
+            }
+            let density: Float = self.density(&ray.position(t));
+            tr *= 1.0 as Float - (0.0 as Float).max(density * self.inv_max_density);
+            // Added after book publication: when transmittance gets
+            // low, start applying Russian roulette to terminate
+            // sampling.
+            let rr_threshold: Float = 0.1 as Float;
+            if tr < rr_threshold {
+                let q: Float = (0.05 as Float).max(1.0 as Float - tr);
+                if sampler.get_1d() < q {
+                    return Spectrum::new(0.0 as Float);
+                }
+                tr /= 1.0 as Float - q;
+            }
+        }
+        Spectrum::new(tr)
+    }
+    fn sample(
+        &self,
+        r_world: &Ray,
+        sampler: &mut Sampler,
+    ) -> (Spectrum, Option<MediumInteraction>) {
+        // TODO: ProfilePhase _(Prof::MediumSample);
+        let ray: Ray = self.to_medium_space(r_world);
+        // compute $[\tmin, \tmax]$ interval of _ray_'s overlap with medium bounds
+        let b: Bounds3f = Bounds3f {
+            p_min: Point3f::default(),
+            p_max: Point3f {
+                x: 1.0 as Float,
+                y: 1.0 as Float,
+                z: 1.0 as Float,
+            },
+        };
+        let mut t_min: Float = 0.0 as Float;
+        let mut t_max: Float = 0.0 as Float;
+        if !b.intersect_b(&ray, &mut t_min, &mut t_max) {
+            return (Spectrum::new(1.0 as Float), None);
+        }
+        // run delta-tracking iterations to sample a medium interaction
+        let mut t: Float = t_min;
+        loop {
+            t -= (1.0 as Float - sampler.get_1d()).ln() * self.inv_max_density / self.sigma_t;
+            if t >= t_max {
+                break;
+            }
+            if self.density(&ray.position(t)) * self.inv_max_density > sampler.get_1d() {
+                // populate _mi_ with medium interaction information and return
+                let mi: MediumInteraction = MediumInteraction::new(
+                    &r_world.position(t / r_world.d.length()),
+                    &(-r_world.d),
+                    r_world.time,
+                    Some(Arc::new(self.clone())),
+                    Some(Arc::new(HenyeyGreenstein { g: self.g })),
+                );
+                return (self.sigma_s / self.sigma_t, Some(mi));
+            }
+        }
+        (Spectrum::new(1.0 as Float), None)
+    }
+}
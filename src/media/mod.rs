@@ -5,8 +5,17 @@
 //! - GridDensityMedium
 //! - HomogeneousMedium
 //!
+//! ## Grid Density Medium
+//!
+//! The **GridDensityMedium** stores medium densities at a regular 3D
+//! grid of positions (e.g. smoke or clouds exported from a fluid
+//! simulation) and trilinearly interpolates between them. Sampling
+//! and transmittance estimation use delta and ratio tracking against
+//! the maximum density of the grid.
+//!
 //! ## Homogeneous Medium
 //!
 //! ![A Volumetric Caustic](/doc/img/volume_caustic_pbrt_rust_mlt.png)
 
+pub mod grid;
 pub mod homogeneous;
@@ -0,0 +1,233 @@
+// pbrt
+use core::geometry::{Point2f, Point2i};
+use core::lowdiscrepancy::{sample_generator_matrix, sobol_2d, van_der_corput, C_MAX_MIN_DIST};
+use core::pbrt::Float;
+use core::pbrt::{is_power_of_2, log_2_int_i32, round_up_pow2_32};
+use core::rng::Rng;
+use core::sampler::{PixelSampler, Sampler};
+use core::sampling::shuffle;
+
+// see maxmin.h
+
+/// Number of generator matrices (one per power of two samples per
+/// pixel, up to 2^16) used by the **MaxMinDistSampler**.
+pub const MAX_MIN_DIST_MATRICES: i32 = 17;
+
+#[derive(Debug, Clone)]
+pub struct MaxMinDistSampler {
+    pub c_pixel: [u32; 32],
+    pub samples_per_pixel: i64,
+    pub n_sampled_dimensions: i64,
+    // inherited from class PixelSampler (see sampler.h)
+    pub samples_1d: Vec<Vec<Float>>,
+    pub samples_2d: Vec<Vec<Point2f>>,
+    pub current_1d_dimension: i32,
+    pub current_2d_dimension: i32,
+    pub rng: Rng,
+    // inherited from class Sampler (see sampler.h)
+    pub current_pixel: Point2i,
+    pub current_pixel_sample_index: i64,
+    pub samples_1d_array_sizes: Vec<i32>,
+    pub samples_2d_array_sizes: Vec<i32>,
+    pub sample_array_1d: Vec<Vec<Float>>,
+    pub sample_array_2d: Vec<Vec<Point2f>>,
+    pub array_1d_offset: usize,
+    pub array_2d_offset: usize,
+}
+
+impl MaxMinDistSampler {
+    pub fn new(samples_per_pixel: i64, n_sampled_dimensions: i64) -> Self {
+        let mut spp: i32 = samples_per_pixel as i32;
+        let c_index: i32 = log_2_int_i32(spp);
+        if c_index >= MAX_MIN_DIST_MATRICES {
+            spp = 1_i32 << (MAX_MIN_DIST_MATRICES - 1);
+            println!(
+                "WARNING: No more than {} samples per pixel are supported with MaxMinDistSampler. Rounding down.",
+                spp
+            );
+        }
+        if !is_power_of_2(spp) {
+            spp = round_up_pow2_32(spp);
+            println!(
+                "WARNING: Non power-of-two sample count rounded up to {} for MaxMinDistSampler.",
+                spp
+            );
+        }
+        let c_pixel: [u32; 32] = C_MAX_MIN_DIST[log_2_int_i32(spp) as usize];
+        let mut mmds: MaxMinDistSampler = MaxMinDistSampler {
+            c_pixel: c_pixel,
+            samples_per_pixel: spp as i64,
+            n_sampled_dimensions: n_sampled_dimensions,
+            samples_1d: Vec::new(),
+            samples_2d: Vec::new(),
+            current_1d_dimension: 0_i32,
+            current_2d_dimension: 0_i32,
+            rng: Rng::default(),
+            current_pixel: Point2i::default(),
+            current_pixel_sample_index: 0_i64,
+            samples_1d_array_sizes: Vec::new(),
+            samples_2d_array_sizes: Vec::new(),
+            sample_array_1d: Vec::new(),
+            sample_array_2d: Vec::new(),
+            array_1d_offset: 0_usize,
+            array_2d_offset: 0_usize,
+        };
+        for _i in 0..mmds.n_sampled_dimensions {
+            let additional_1d: Vec<Float> = vec![0.0; mmds.samples_per_pixel as usize];
+            let additional_2d: Vec<Point2f> =
+                vec![Point2f::default(); mmds.samples_per_pixel as usize];
+            mmds.samples_1d.push(additional_1d);
+            mmds.samples_2d.push(additional_2d);
+        }
+        mmds
+    }
+}
+
+impl Sampler for MaxMinDistSampler {
+    fn start_pixel(&mut self, p: &Point2i) {
+        // TODO: ProfilePhase _(Prof::StartPixel);
+        let inv_spp: Float = 1.0 as Float / self.samples_per_pixel as Float;
+        for i in 0..self.samples_per_pixel as usize {
+            self.samples_2d[0][i] = Point2f {
+                x: i as Float * inv_spp,
+                y: sample_generator_matrix(&self.c_pixel, i as u32, 0_u32),
+            };
+        }
+        shuffle(
+            &mut self.samples_2d[0][..],
+            self.samples_per_pixel as i32,
+            1,
+            &mut self.rng,
+        );
+        // generate remaining samples for _MaxMinDistSampler_
+        for samples in &mut self.samples_1d {
+            van_der_corput(1, self.samples_per_pixel as i32, samples, &mut self.rng);
+        }
+        for samples in self.samples_2d.iter_mut().skip(1) {
+            sobol_2d(1, self.samples_per_pixel as i32, samples, &mut self.rng);
+        }
+        for i in 0..self.samples_1d_array_sizes.len() {
+            let samples: &mut [Float] = self.sample_array_1d[i].as_mut_slice();
+            van_der_corput(
+                self.samples_1d_array_sizes[i],
+                self.samples_per_pixel as i32,
+                samples,
+                &mut self.rng,
+            );
+        }
+        for i in 0..self.samples_2d_array_sizes.len() {
+            let samples: &mut [Point2f] = self.sample_array_2d[i].as_mut_slice();
+            sobol_2d(
+                self.samples_2d_array_sizes[i],
+                self.samples_per_pixel as i32,
+                samples,
+                &mut self.rng,
+            );
+        }
+        // PixelSampler::StartPixel(p);
+        self.current_pixel = *p;
+        self.current_pixel_sample_index = 0_i64;
+        // reset array offsets for next pixel sample
+        self.array_1d_offset = 0_usize;
+        self.array_2d_offset = 0_usize;
+    }
+    fn get_1d(&mut self) -> Float {
+        // TODO: ProfilePhase _(Prof::GetSample);
+        assert!(
+            self.current_pixel_sample_index < self.samples_per_pixel,
+            "current_pixel_sample_index = {}, samples_per_pixel = {}",
+            self.current_pixel_sample_index,
+            self.samples_per_pixel
+        );
+        if self.current_1d_dimension < self.samples_1d.len() as i32 {
+            let sample: Float = self.samples_1d[self.current_1d_dimension as usize]
+                [self.current_pixel_sample_index as usize];
+            self.current_1d_dimension += 1;
+            sample
+        } else {
+            self.rng.uniform_float()
+        }
+    }
+    fn get_2d(&mut self) -> Point2f {
+        // TODO: ProfilePhase _(Prof::GetSample);
+        assert!(
+            self.current_pixel_sample_index < self.samples_per_pixel,
+            "current_pixel_sample_index = {}, samples_per_pixel = {}",
+            self.current_pixel_sample_index,
+            self.samples_per_pixel
+        );
+        if self.current_2d_dimension < self.samples_2d.len() as i32 {
+            let sample: Point2f = self.samples_2d[self.current_2d_dimension as usize]
+                [self.current_pixel_sample_index as usize];
+            self.current_2d_dimension += 1;
+            sample
+        } else {
+            // C++ call order for Point2f(rng.UniformFloat(), rng.UniformFloat());
+            let y = self.rng.uniform_float();
+            let x = self.rng.uniform_float();
+            Point2f { x: x, y: y }
+        }
+    }
+    fn request_2d_array(&mut self, n: i32) {
+        assert_eq!(self.round_count(n), n);
+        self.samples_2d_array_sizes.push(n);
+        let size: usize = (n * self.samples_per_pixel as i32) as usize;
+        let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
+        self.sample_array_2d.push(additional_points);
+    }
+    fn round_count(&self, count: i32) -> i32 {
+        round_up_pow2_32(count)
+    }
+    fn get_2d_array(&mut self, n: i32) -> Vec<Point2f> {
+        let mut samples: Vec<Point2f> = Vec::new();
+        if self.array_2d_offset == self.sample_array_2d.len() {
+            return samples;
+        }
+        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
+        assert!(
+            self.current_pixel_sample_index < self.samples_per_pixel,
+            "self.current_pixel_sample_index ({}) < self.samples_per_pixel ({})",
+            self.current_pixel_sample_index,
+            self.samples_per_pixel
+        );
+        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
+        let end: usize = start + n as usize;
+        samples = self.sample_array_2d[self.array_2d_offset][start..end].to_vec();
+        self.array_2d_offset += 1;
+        samples
+    }
+    fn start_next_sample(&mut self) -> bool {
+        self.current_1d_dimension = 0_i32;
+        self.current_2d_dimension = 0_i32;
+        // Sampler::StartNextSample()
+        // reset array offsets for next pixel sample
+        self.array_1d_offset = 0_usize;
+        self.array_2d_offset = 0_usize;
+        self.current_pixel_sample_index += 1_i64;
+        self.current_pixel_sample_index < self.samples_per_pixel
+    }
+    fn set_sample_number(&mut self, sample_num: i64) -> bool {
+        self.current_1d_dimension = 0_i32;
+        self.current_2d_dimension = 0_i32;
+        // Sampler::SetSampleNumber(sample_num);
+        // reset array offsets for next pixel sample
+        self.array_1d_offset = 0_usize;
+        self.array_2d_offset = 0_usize;
+        self.current_pixel_sample_index = sample_num;
+        self.current_pixel_sample_index < self.samples_per_pixel
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.rng.set_sequence(seed);
+    }
+    fn get_current_pixel(&self) -> Point2i {
+        self.current_pixel
+    }
+    fn get_current_sample_number(&self) -> i64 {
+        self.current_pixel_sample_index
+    }
+    fn get_samples_per_pixel(&self) -> i64 {
+        self.samples_per_pixel
+    }
+}
+
+impl PixelSampler for MaxMinDistSampler {}
@@ -48,8 +48,24 @@
 //!
 //! ![lowdiscrepancy](/doc/img/cornell_box_pbrt_rust_lowdiscrepancy.png)
 //!
+//! ## Stratified Sampler
+//!
+//! The Stratified Sampler subdivides the pixel area into rectangular
+//! regions (strata) and generates a single (optionally jittered)
+//! sample inside each region. Arrays of 2D samples are generated with
+//! Latin hypercube sampling.
+//!
+//! ## Max-Min-Distance Sampler
+//!
+//! The Max-Min-Distance Sampler uses generator matrices for the image
+//! plane dimensions which maximize the minimum distance between the
+//! sample points of a pixel, and the (0,2)-sequence for all other
+//! dimensions.
+//!
 
 pub mod halton;
+pub mod maxmin;
 pub mod random;
 pub mod sobol;
+pub mod stratified;
 pub mod zerotwosequence;
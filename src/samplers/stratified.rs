@@ -0,0 +1,221 @@
+// pbrt
+use core::geometry::{Point2f, Point2i};
+use core::pbrt::Float;
+use core::rng::Rng;
+use core::sampler::{PixelSampler, Sampler};
+use core::sampling::{latin_hypercube, shuffle, stratified_sample_1d, stratified_sample_2d};
+
+// see stratified.h
+
+#[derive(Debug, Clone)]
+pub struct StratifiedSampler {
+    pub x_pixel_samples: i32,
+    pub y_pixel_samples: i32,
+    pub jitter_samples: bool,
+    pub samples_per_pixel: i64,
+    pub n_sampled_dimensions: i64,
+    // inherited from class PixelSampler (see sampler.h)
+    pub samples_1d: Vec<Vec<Float>>,
+    pub samples_2d: Vec<Vec<Point2f>>,
+    pub current_1d_dimension: i32,
+    pub current_2d_dimension: i32,
+    pub rng: Rng,
+    // inherited from class Sampler (see sampler.h)
+    pub current_pixel: Point2i,
+    pub current_pixel_sample_index: i64,
+    pub samples_1d_array_sizes: Vec<i32>,
+    pub samples_2d_array_sizes: Vec<i32>,
+    pub sample_array_1d: Vec<Vec<Float>>,
+    pub sample_array_2d: Vec<Vec<Point2f>>,
+    pub array_1d_offset: usize,
+    pub array_2d_offset: usize,
+}
+
+impl StratifiedSampler {
+    pub fn new(
+        x_pixel_samples: i32,
+        y_pixel_samples: i32,
+        jitter_samples: bool,
+        n_sampled_dimensions: i64,
+    ) -> Self {
+        let samples_per_pixel: i64 = (x_pixel_samples * y_pixel_samples) as i64;
+        let mut ss: StratifiedSampler = StratifiedSampler {
+            x_pixel_samples: x_pixel_samples,
+            y_pixel_samples: y_pixel_samples,
+            jitter_samples: jitter_samples,
+            samples_per_pixel: samples_per_pixel,
+            n_sampled_dimensions: n_sampled_dimensions,
+            samples_1d: Vec::new(),
+            samples_2d: Vec::new(),
+            current_1d_dimension: 0_i32,
+            current_2d_dimension: 0_i32,
+            rng: Rng::default(),
+            current_pixel: Point2i::default(),
+            current_pixel_sample_index: 0_i64,
+            samples_1d_array_sizes: Vec::new(),
+            samples_2d_array_sizes: Vec::new(),
+            sample_array_1d: Vec::new(),
+            sample_array_2d: Vec::new(),
+            array_1d_offset: 0_usize,
+            array_2d_offset: 0_usize,
+        };
+        for _i in 0..ss.n_sampled_dimensions {
+            let additional_1d: Vec<Float> = vec![0.0; ss.samples_per_pixel as usize];
+            let additional_2d: Vec<Point2f> =
+                vec![Point2f::default(); ss.samples_per_pixel as usize];
+            ss.samples_1d.push(additional_1d);
+            ss.samples_2d.push(additional_2d);
+        }
+        ss
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel(&mut self, p: &Point2i) {
+        // TODO: ProfilePhase _(Prof::StartPixel);
+        let n_samples: i32 = self.x_pixel_samples * self.y_pixel_samples;
+        // generate single stratified samples for the pixel
+        for samples in &mut self.samples_1d {
+            stratified_sample_1d(samples, n_samples, &mut self.rng, self.jitter_samples);
+            shuffle(samples, n_samples, 1, &mut self.rng);
+        }
+        for samples in &mut self.samples_2d {
+            stratified_sample_2d(
+                samples,
+                self.x_pixel_samples,
+                self.y_pixel_samples,
+                &mut self.rng,
+                self.jitter_samples,
+            );
+            shuffle(samples, n_samples, 1, &mut self.rng);
+        }
+        // generate arrays of stratified samples for the pixel
+        for i in 0..self.samples_1d_array_sizes.len() {
+            let count: usize = self.samples_1d_array_sizes[i] as usize;
+            for j in 0..self.samples_per_pixel as usize {
+                let samples: &mut [Float] =
+                    &mut self.sample_array_1d[i][(j * count)..((j + 1) * count)];
+                stratified_sample_1d(samples, count as i32, &mut self.rng, self.jitter_samples);
+                shuffle(samples, count as i32, 1, &mut self.rng);
+            }
+        }
+        for i in 0..self.samples_2d_array_sizes.len() {
+            let count: usize = self.samples_2d_array_sizes[i] as usize;
+            let mut lhs: Vec<Float> = vec![0.0 as Float; 2 * count];
+            for j in 0..self.samples_per_pixel as usize {
+                latin_hypercube(&mut lhs[..], count as i32, 2, &mut self.rng);
+                for k in 0..count {
+                    self.sample_array_2d[i][j * count + k] = Point2f {
+                        x: lhs[2 * k],
+                        y: lhs[2 * k + 1],
+                    };
+                }
+            }
+        }
+        // PixelSampler::StartPixel(p);
+        self.current_pixel = *p;
+        self.current_pixel_sample_index = 0_i64;
+        // reset array offsets for next pixel sample
+        self.array_1d_offset = 0_usize;
+        self.array_2d_offset = 0_usize;
+    }
+    fn get_1d(&mut self) -> Float {
+        // TODO: ProfilePhase _(Prof::GetSample);
+        assert!(
+            self.current_pixel_sample_index < self.samples_per_pixel,
+            "current_pixel_sample_index = {}, samples_per_pixel = {}",
+            self.current_pixel_sample_index,
+            self.samples_per_pixel
+        );
+        if self.current_1d_dimension < self.samples_1d.len() as i32 {
+            let sample: Float = self.samples_1d[self.current_1d_dimension as usize]
+                [self.current_pixel_sample_index as usize];
+            self.current_1d_dimension += 1;
+            sample
+        } else {
+            self.rng.uniform_float()
+        }
+    }
+    fn get_2d(&mut self) -> Point2f {
+        // TODO: ProfilePhase _(Prof::GetSample);
+        assert!(
+            self.current_pixel_sample_index < self.samples_per_pixel,
+            "current_pixel_sample_index = {}, samples_per_pixel = {}",
+            self.current_pixel_sample_index,
+            self.samples_per_pixel
+        );
+        if self.current_2d_dimension < self.samples_2d.len() as i32 {
+            let sample: Point2f = self.samples_2d[self.current_2d_dimension as usize]
+                [self.current_pixel_sample_index as usize];
+            self.current_2d_dimension += 1;
+            sample
+        } else {
+            // C++ call order for Point2f(rng.UniformFloat(), rng.UniformFloat());
+            let y = self.rng.uniform_float();
+            let x = self.rng.uniform_float();
+            Point2f { x: x, y: y }
+        }
+    }
+    fn request_2d_array(&mut self, n: i32) {
+        assert_eq!(self.round_count(n), n);
+        self.samples_2d_array_sizes.push(n);
+        let size: usize = (n * self.samples_per_pixel as i32) as usize;
+        let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
+        self.sample_array_2d.push(additional_points);
+    }
+    fn round_count(&self, count: i32) -> i32 {
+        count
+    }
+    fn get_2d_array(&mut self, n: i32) -> Vec<Point2f> {
+        let mut samples: Vec<Point2f> = Vec::new();
+        if self.array_2d_offset == self.sample_array_2d.len() {
+            return samples;
+        }
+        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
+        assert!(
+            self.current_pixel_sample_index < self.samples_per_pixel,
+            "self.current_pixel_sample_index ({}) < self.samples_per_pixel ({})",
+            self.current_pixel_sample_index,
+            self.samples_per_pixel
+        );
+        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
+        let end: usize = start + n as usize;
+        samples = self.sample_array_2d[self.array_2d_offset][start..end].to_vec();
+        self.array_2d_offset += 1;
+        samples
+    }
+    fn start_next_sample(&mut self) -> bool {
+        self.current_1d_dimension = 0_i32;
+        self.current_2d_dimension = 0_i32;
+        // Sampler::StartNextSample()
+        // reset array offsets for next pixel sample
+        self.array_1d_offset = 0_usize;
+        self.array_2d_offset = 0_usize;
+        self.current_pixel_sample_index += 1_i64;
+        self.current_pixel_sample_index < self.samples_per_pixel
+    }
+    fn set_sample_number(&mut self, sample_num: i64) -> bool {
+        self.current_1d_dimension = 0_i32;
+        self.current_2d_dimension = 0_i32;
+        // Sampler::SetSampleNumber(sample_num);
+        // reset array offsets for next pixel sample
+        self.array_1d_offset = 0_usize;
+        self.array_2d_offset = 0_usize;
+        self.current_pixel_sample_index = sample_num;
+        self.current_pixel_sample_index < self.samples_per_pixel
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.rng.set_sequence(seed);
+    }
+    fn get_current_pixel(&self) -> Point2i {
+        self.current_pixel
+    }
+    fn get_current_sample_number(&self) -> i64 {
+        self.current_pixel_sample_index
+    }
+    fn get_samples_per_pixel(&self) -> i64 {
+        self.samples_per_pixel
+    }
+}
+
+impl PixelSampler for StratifiedSampler {}
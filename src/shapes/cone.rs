@@ -0,0 +1,403 @@
+// std
+use std::f32::consts::PI;
+use std::sync::Arc;
+// pbrt
+use core::efloat::quadratic_efloat;
+use core::efloat::EFloat;
+use core::geometry::{nrm_abs_dot_vec3, pnt3_distance_squared, vec3_cross_vec3, vec3_dot_vec3};
+use core::geometry::{Bounds3f, Normal3f, Point2f, Point3f, Ray, Vector3f};
+use core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
+use core::material::Material;
+use core::pbrt::Float;
+use core::pbrt::{clamp_t, gamma, radians};
+use core::shape::Shape;
+use core::transform::Transform;
+
+// see cone.h
+
+#[derive(Clone)]
+pub struct Cone {
+    pub radius: Float,
+    pub height: Float,
+    pub phi_max: Float,
+    // inherited from class Shape (see shape.h)
+    object_to_world: Transform,
+    world_to_object: Transform,
+    reverse_orientation: bool,
+    transform_swaps_handedness: bool,
+    pub material: Option<Arc<Material + Send + Sync>>,
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Cone {
+            // Shape
+            object_to_world: Transform::default(),
+            world_to_object: Transform::default(),
+            reverse_orientation: false,
+            transform_swaps_handedness: false,
+            // Cone
+            radius: 1.0,
+            height: 1.0,
+            phi_max: radians(360.0),
+            material: None,
+        }
+    }
+}
+
+impl Cone {
+    pub fn new(
+        object_to_world: Transform,
+        world_to_object: Transform,
+        reverse_orientation: bool,
+        height: Float,
+        radius: Float,
+        phi_max: Float,
+    ) -> Self {
+        Cone {
+            // Shape
+            object_to_world: object_to_world,
+            world_to_object: world_to_object,
+            reverse_orientation: reverse_orientation,
+            transform_swaps_handedness: false,
+            // Cone
+            radius: radius,
+            height: height,
+            phi_max: radians(clamp_t(phi_max, 0.0, 360.0)),
+            material: None,
+        }
+    }
+}
+
+impl Shape for Cone {
+    fn object_bound(&self) -> Bounds3f {
+        Bounds3f {
+            p_min: Point3f {
+                x: -self.radius,
+                y: -self.radius,
+                z: 0.0,
+            },
+            p_max: Point3f {
+                x: self.radius,
+                y: self.radius,
+                z: self.height,
+            },
+        }
+    }
+    fn world_bound(&self) -> Bounds3f {
+        // in C++: Bounds3f Shape::WorldBound() const { return (*ObjectToWorld)(ObjectBound()); }
+        self.object_to_world.transform_bounds(&self.object_bound())
+    }
+    fn intersect(&self, r: &Ray) -> Option<(SurfaceInteraction, Float)> {
+        // TODO: ProfilePhase p(Prof::ShapeIntersect);
+        // transform _Ray_ to object space
+        let mut o_err: Vector3f = Vector3f::default();
+        let mut d_err: Vector3f = Vector3f::default();
+        let ray: Ray = self
+            .world_to_object
+            .transform_ray_with_error(r, &mut o_err, &mut d_err);
+
+        // compute quadratic cone coefficients
+
+        // initialize _EFloat_ ray coordinate values
+        let ox = EFloat::new(ray.o.x as f32, o_err.x as f32);
+        let oy = EFloat::new(ray.o.y as f32, o_err.y as f32);
+        let oz = EFloat::new(ray.o.z as f32, o_err.z as f32);
+        let dx = EFloat::new(ray.d.x as f32, d_err.x as f32);
+        let dy = EFloat::new(ray.d.y as f32, d_err.y as f32);
+        let dz = EFloat::new(ray.d.z as f32, d_err.z as f32);
+        let mut k: EFloat =
+            EFloat::new(self.radius as f32, 0.0) / EFloat::new(self.height as f32, 0.0);
+        k = k * k;
+        let height: EFloat = EFloat::new(self.height as f32, 0.0);
+        let a: EFloat = dx * dx + dy * dy - k * dz * dz;
+        let b: EFloat = (dx * ox + dy * oy - k * dz * (oz - height)) * 2.0f32;
+        let c: EFloat = ox * ox + oy * oy - k * (oz - height) * (oz - height);
+
+        // Solve quadratic equation for _t_ values
+        let mut t0: EFloat = EFloat::default();
+        let mut t1: EFloat = EFloat::default();
+        if !quadratic_efloat(a, b, c, &mut t0, &mut t1) {
+            return None;
+        }
+        // check quadric shape _t0_ and _t1_ for nearest intersection
+        if t0.upper_bound() > ray.t_max as f32 || t1.lower_bound() <= 0.0f32 {
+            return None;
+        }
+        let mut t_shape_hit: EFloat = t0;
+        if t_shape_hit.lower_bound() <= 0.0f32 {
+            t_shape_hit = t1;
+            if t_shape_hit.upper_bound() > ray.t_max as f32 {
+                return None;
+            }
+        }
+        // compute cone inverse mapping
+        let mut p_hit: Point3f = ray.position(t_shape_hit.v);
+        let mut phi: Float = p_hit.y.atan2(p_hit.x);
+        if phi < 0.0 as Float {
+            phi += 2.0 as Float * PI;
+        }
+        // test cone intersection against clipping parameters
+        if p_hit.z < 0.0 as Float || p_hit.z > self.height || phi > self.phi_max {
+            if t_shape_hit == t1 {
+                return None;
+            }
+            t_shape_hit = t1;
+            if t1.upper_bound() > ray.t_max {
+                return None;
+            }
+            // compute cone inverse mapping
+            p_hit = ray.position(t_shape_hit.v);
+            phi = p_hit.y.atan2(p_hit.x);
+            if phi < 0.0 as Float {
+                phi += 2.0 as Float * PI;
+            }
+            if p_hit.z < 0.0 as Float || p_hit.z > self.height || phi > self.phi_max {
+                return None;
+            }
+        }
+        // find parametric representation of cone hit
+        let u: Float = phi / self.phi_max;
+        let v: Float = p_hit.z / self.height;
+        // compute cone $\dpdu$ and $\dpdv$
+        let dpdu: Vector3f = Vector3f {
+            x: -self.phi_max * p_hit.y,
+            y: self.phi_max * p_hit.x,
+            z: 0.0,
+        };
+        let dpdv: Vector3f = Vector3f {
+            x: -p_hit.x / (1.0 as Float - v),
+            y: -p_hit.y / (1.0 as Float - v),
+            z: self.height,
+        };
+        // compute cone $\dndu$ and $\dndv$
+        let d2_p_duu: Vector3f = Vector3f {
+            x: p_hit.x,
+            y: p_hit.y,
+            z: 0.0,
+        } * -self.phi_max
+            * self.phi_max;
+        let d2_p_duv: Vector3f = Vector3f {
+            x: p_hit.y,
+            y: -p_hit.x,
+            z: 0.0,
+        } * (self.phi_max / (1.0 as Float - v));
+        let d2_p_dvv: Vector3f = Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        // compute coefficients for fundamental forms
+        let ec: Float = vec3_dot_vec3(&dpdu, &dpdu);
+        let fc: Float = vec3_dot_vec3(&dpdu, &dpdv);
+        let gc: Float = vec3_dot_vec3(&dpdv, &dpdv);
+        let nc: Vector3f = vec3_cross_vec3(&dpdu, &dpdv).normalize();
+        let el: Float = vec3_dot_vec3(&nc, &d2_p_duu);
+        let fl: Float = vec3_dot_vec3(&nc, &d2_p_duv);
+        let gl: Float = vec3_dot_vec3(&nc, &d2_p_dvv);
+        // compute $\dndu$ and $\dndv$ from fundamental form coefficients
+        let inv_egf2: Float = 1.0 / (ec * gc - fc * fc);
+        let dndu = dpdu * (fl * fc - el * gc) * inv_egf2 + dpdv * (el * fc - fl * ec) * inv_egf2;
+        let dndu = Normal3f {
+            x: dndu.x,
+            y: dndu.y,
+            z: dndu.z,
+        };
+        let dndv = dpdu * (gl * fc - fl * gc) * inv_egf2 + dpdv * (fl * fc - gl * ec) * inv_egf2;
+        let dndv = Normal3f {
+            x: dndv.x,
+            y: dndv.y,
+            z: dndv.z,
+        };
+        // compute error bounds for cone intersection
+
+        // compute error bounds for intersection computed with ray equation
+        let px: EFloat = ox + t_shape_hit * dx;
+        let py: EFloat = oy + t_shape_hit * dy;
+        let pz: EFloat = oz + t_shape_hit * dz;
+        let p_error: Vector3f = Vector3f {
+            x: px.get_absolute_error() as Float,
+            y: py.get_absolute_error() as Float,
+            z: pz.get_absolute_error() as Float,
+        };
+        // initialize _SurfaceInteraction_ from parametric information
+        let uv_hit: Point2f = Point2f { x: u, y: v };
+        let wo: Vector3f = -ray.d;
+        let si: SurfaceInteraction = SurfaceInteraction::new(
+            &p_hit, &p_error, &uv_hit, &wo, &dpdu, &dpdv, &dndu, &dndv, ray.time, None,
+        );
+        let mut isect: SurfaceInteraction = self.object_to_world.transform_surface_interaction(&si);
+        if let Some(_shape) = si.shape {
+            isect.shape = si.shape;
+        }
+        if let Some(_primitive) = si.primitive {
+            isect.primitive = si.primitive;
+        }
+        Some((isect, t_shape_hit.v as Float))
+    }
+    fn intersect_p(&self, r: &Ray) -> bool {
+        // TODO: ProfilePhase p(Prof::ShapeIntersect);
+        // transform _Ray_ to object space
+        let mut o_err: Vector3f = Vector3f::default();
+        let mut d_err: Vector3f = Vector3f::default();
+        let ray: Ray = self
+            .world_to_object
+            .transform_ray_with_error(r, &mut o_err, &mut d_err);
+
+        // compute quadratic cone coefficients
+
+        // initialize _EFloat_ ray coordinate values
+        let ox = EFloat::new(ray.o.x as f32, o_err.x as f32);
+        let oy = EFloat::new(ray.o.y as f32, o_err.y as f32);
+        let oz = EFloat::new(ray.o.z as f32, o_err.z as f32);
+        let dx = EFloat::new(ray.d.x as f32, d_err.x as f32);
+        let dy = EFloat::new(ray.d.y as f32, d_err.y as f32);
+        let dz = EFloat::new(ray.d.z as f32, d_err.z as f32);
+        let mut k: EFloat =
+            EFloat::new(self.radius as f32, 0.0) / EFloat::new(self.height as f32, 0.0);
+        k = k * k;
+        let height: EFloat = EFloat::new(self.height as f32, 0.0);
+        let a: EFloat = dx * dx + dy * dy - k * dz * dz;
+        let b: EFloat = (dx * ox + dy * oy - k * dz * (oz - height)) * 2.0f32;
+        let c: EFloat = ox * ox + oy * oy - k * (oz - height) * (oz - height);
+
+        // Solve quadratic equation for _t_ values
+        let mut t0: EFloat = EFloat::default();
+        let mut t1: EFloat = EFloat::default();
+        if !quadratic_efloat(a, b, c, &mut t0, &mut t1) {
+            return false;
+        }
+        // check quadric shape _t0_ and _t1_ for nearest intersection
+        if t0.upper_bound() > ray.t_max as f32 || t1.lower_bound() <= 0.0f32 {
+            return false;
+        }
+        let mut t_shape_hit: EFloat = t0;
+        if t_shape_hit.lower_bound() <= 0.0f32 {
+            t_shape_hit = t1;
+            if t_shape_hit.upper_bound() > ray.t_max as f32 {
+                return false;
+            }
+        }
+        // compute cone inverse mapping
+        let mut p_hit: Point3f = ray.position(t_shape_hit.v);
+        let mut phi: Float = p_hit.y.atan2(p_hit.x);
+        if phi < 0.0 as Float {
+            phi += 2.0 as Float * PI;
+        }
+        // test cone intersection against clipping parameters
+        if p_hit.z < 0.0 as Float || p_hit.z > self.height || phi > self.phi_max {
+            if t_shape_hit == t1 {
+                return false;
+            }
+            t_shape_hit = t1;
+            if t1.upper_bound() > ray.t_max {
+                return false;
+            }
+            // compute cone inverse mapping
+            p_hit = ray.position(t_shape_hit.v);
+            phi = p_hit.y.atan2(p_hit.x);
+            if phi < 0.0 as Float {
+                phi += 2.0 as Float * PI;
+            }
+            if p_hit.z < 0.0 as Float || p_hit.z > self.height || phi > self.phi_max {
+                return false;
+            }
+        }
+        true
+    }
+    fn get_reverse_orientation(&self) -> bool {
+        self.reverse_orientation
+    }
+    fn get_transform_swaps_handedness(&self) -> bool {
+        self.transform_swaps_handedness
+    }
+    fn area(&self) -> Float {
+        self.radius * ((self.height * self.height) + (self.radius * self.radius)).sqrt()
+            * self.phi_max
+            / 2.0 as Float
+    }
+    fn sample(&self, u: &Point2f, pdf: &mut Float) -> InteractionCommon {
+        // the area of a ring at height $v$ is proportional to its
+        // radius $r (1 - v)$, invert the resulting CDF
+        let one_minus_v: Float = u[0].sqrt();
+        let phi: Float = u[1] * self.phi_max;
+        let p_obj: Point3f = Point3f {
+            x: self.radius * one_minus_v * phi.cos(),
+            y: self.radius * one_minus_v * phi.sin(),
+            z: self.height * (1.0 as Float - one_minus_v),
+        };
+        let mut it: InteractionCommon = InteractionCommon::default();
+        // the (unnormalized) normal is independent of $v$
+        let n: Vector3f = vec3_cross_vec3(
+            &Vector3f {
+                x: -phi.sin(),
+                y: phi.cos(),
+                z: 0.0,
+            },
+            &Vector3f {
+                x: -self.radius * phi.cos(),
+                y: -self.radius * phi.sin(),
+                z: self.height,
+            },
+        );
+        it.n = self
+            .object_to_world
+            .transform_normal(&Normal3f {
+                x: n.x,
+                y: n.y,
+                z: n.z,
+            }).normalize();
+        if self.reverse_orientation {
+            it.n *= -1.0 as Float;
+        }
+        let p_obj_error: Vector3f = Vector3f {
+            x: p_obj.x,
+            y: p_obj.y,
+            z: p_obj.z,
+        }.abs()
+            * gamma(5_i32);
+        it.p = self.object_to_world.transform_point_with_abs_error(
+            &p_obj,
+            &p_obj_error,
+            &mut it.p_error,
+        );
+        *pdf = 1.0 as Float / self.area();
+        it
+    }
+    fn sample_with_ref_point(
+        &self,
+        iref: &InteractionCommon,
+        u: &Point2f,
+        pdf: &mut Float,
+    ) -> InteractionCommon {
+        let intr: InteractionCommon = self.sample(u, pdf);
+        let mut wi: Vector3f = intr.p - iref.p;
+        if wi.length_squared() == 0.0 as Float {
+            *pdf = 0.0 as Float;
+        } else {
+            wi = wi.normalize();
+            // convert from area measure, as returned by the Sample()
+            // call above, to solid angle measure.
+            *pdf *= pnt3_distance_squared(&iref.p, &intr.p) / nrm_abs_dot_vec3(&intr.n, &-wi);
+            if (*pdf).is_infinite() {
+                *pdf = 0.0 as Float;
+            }
+        }
+        intr
+    }
+    fn pdf_with_ref_point(&self, iref: &Interaction, wi: &Vector3f) -> Float {
+        // intersect sample ray with area light geometry
+        let ray: Ray = iref.spawn_ray(wi);
+        if let Some((isect_light, _t_hit)) = self.intersect(&ray) {
+            // convert light sample weight to solid angle measure
+            let mut pdf: Float = pnt3_distance_squared(&iref.get_p(), &isect_light.p)
+                / (nrm_abs_dot_vec3(&isect_light.n, &-(*wi)) * self.area());
+            if pdf.is_infinite() {
+                pdf = 0.0 as Float;
+            }
+            pdf
+        } else {
+            0.0 as Float
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// std
+use std::sync::Arc;
+// pbrt
+use core::geometry::{Point2f, Point3f};
+use core::paramset::ParamSet;
+use core::pbrt::Float;
+use core::shape::Shape;
+use core::transform::Transform;
+use shapes::triangle::create_triangle_mesh;
+
+// see heightfield.cpp
+
+/// Tessellates a regular grid of heights (**Pz**, **nu** by **nv**
+/// values) over $[0,1]^2$ into a triangle mesh. The $(x, y)$
+/// coordinates of the vertices are also used as texture coordinates.
+pub fn create_heightfield(
+    o2w: &Transform,
+    w2o: &Transform,
+    reverse_orientation: bool,
+    params: &ParamSet,
+) -> Vec<Arc<Shape + Send + Sync>> {
+    let nx: i32 = params.find_one_int("nu", -1);
+    let ny: i32 = params.find_one_int("nv", -1);
+    let z: Vec<Float> = params.find_float("Pz");
+    if nx < 2 || ny < 2 {
+        panic!("heightfield needs \"nu\" and \"nv\" (at least 2 each)");
+    }
+    if z.len() != (nx * ny) as usize {
+        panic!(
+            "heightfield has {} \"Pz\" values, but {} (nu * nv) were expected",
+            z.len(),
+            nx * ny
+        );
+    }
+    let n_triangles: usize = 2 * ((nx - 1) * (ny - 1)) as usize;
+    let n_vertices: usize = (nx * ny) as usize;
+    // compute heightfield vertex positions
+    let mut p: Vec<Point3f> = Vec::with_capacity(n_vertices);
+    let mut uvs: Vec<Point2f> = Vec::with_capacity(n_vertices);
+    let mut pos: usize = 0;
+    for y in 0..ny {
+        for x in 0..nx {
+            let u: Float = x as Float / (nx - 1) as Float;
+            let v: Float = y as Float / (ny - 1) as Float;
+            p.push(Point3f {
+                x: u,
+                y: v,
+                z: z[pos],
+            });
+            uvs.push(Point2f { x: u, y: v });
+            pos += 1;
+        }
+    }
+    // fill in heightfield vertex offset array
+    let vert = |x: i32, y: i32| -> usize { (x + y * nx) as usize };
+    let mut indices: Vec<usize> = Vec::with_capacity(3 * n_triangles);
+    for y in 0..(ny - 1) {
+        for x in 0..(nx - 1) {
+            indices.push(vert(x, y));
+            indices.push(vert(x + 1, y));
+            indices.push(vert(x + 1, y + 1));
+
+            indices.push(vert(x, y));
+            indices.push(vert(x + 1, y + 1));
+            indices.push(vert(x, y + 1));
+        }
+    }
+    create_triangle_mesh(
+        o2w,
+        w2o,
+        reverse_orientation,
+        n_triangles,
+        indices,
+        n_vertices,
+        &p,
+        &Vec::new(),
+        &Vec::new(),
+        uvs,
+        Vec::new(),
+    )
+}
@@ -0,0 +1,482 @@
+// std
+use std;
+use std::f32::consts::PI;
+use std::sync::Arc;
+// pbrt
+use core::efloat::quadratic_efloat;
+use core::efloat::EFloat;
+use core::geometry::{nrm_abs_dot_vec3, pnt3_distance_squared, vec3_cross_vec3, vec3_dot_vec3};
+use core::geometry::{Bounds3f, Normal3f, Point2f, Point3f, Ray, Vector3f};
+use core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
+use core::material::Material;
+use core::pbrt::Float;
+use core::pbrt::{clamp_t, gamma, radians};
+use core::shape::Shape;
+use core::transform::Transform;
+
+// see hyperboloid.h
+
+#[derive(Clone)]
+pub struct Hyperboloid {
+    pub p1: Point3f,
+    pub p2: Point3f,
+    pub z_min: Float,
+    pub z_max: Float,
+    pub phi_max: Float,
+    pub r_max: Float,
+    pub ah: Float,
+    pub ch: Float,
+    // inherited from class Shape (see shape.h)
+    object_to_world: Transform,
+    world_to_object: Transform,
+    reverse_orientation: bool,
+    transform_swaps_handedness: bool,
+    pub material: Option<Arc<Material + Send + Sync>>,
+}
+
+impl Hyperboloid {
+    pub fn new(
+        object_to_world: Transform,
+        world_to_object: Transform,
+        reverse_orientation: bool,
+        point1: Point3f,
+        point2: Point3f,
+        phi_max: Float,
+    ) -> Self {
+        let mut p1: Point3f = point1;
+        let mut p2: Point3f = point2;
+        let radius1: Float = (p1.x * p1.x + p1.y * p1.y).sqrt();
+        let radius2: Float = (p2.x * p2.x + p2.y * p2.y).sqrt();
+        let r_max: Float = radius1.max(radius2);
+        let z_min: Float = p1.z.min(p2.z);
+        let z_max: Float = p1.z.max(p2.z);
+        // compute implicit function coefficients for hyperboloid
+        if p2.z == 0.0 as Float {
+            std::mem::swap(&mut p1, &mut p2);
+        }
+        let mut pp: Point3f = p1;
+        let mut ah: Float;
+        let mut ch: Float;
+        loop {
+            pp += (p2 - p1) * 2.0 as Float;
+            let xy1: Float = pp.x * pp.x + pp.y * pp.y;
+            let xy2: Float = p2.x * p2.x + p2.y * p2.y;
+            ah = (1.0 as Float / xy1 - (pp.z * pp.z) / (xy1 * p2.z * p2.z))
+                / (1.0 as Float - (xy2 * pp.z * pp.z) / (xy1 * p2.z * p2.z));
+            ch = (ah * xy2 - 1.0 as Float) / (p2.z * p2.z);
+            if !(ah.is_infinite() || ah.is_nan()) {
+                break;
+            }
+        }
+        Hyperboloid {
+            // Shape
+            object_to_world: object_to_world,
+            world_to_object: world_to_object,
+            reverse_orientation: reverse_orientation,
+            transform_swaps_handedness: false,
+            // Hyperboloid
+            p1: p1,
+            p2: p2,
+            z_min: z_min,
+            z_max: z_max,
+            phi_max: radians(clamp_t(phi_max, 0.0, 360.0)),
+            r_max: r_max,
+            ah: ah,
+            ch: ch,
+            material: None,
+        }
+    }
+    /// The area of the (full) surface of revolution between _p1_ and
+    /// the point at $v$ on the line from _p1_ to _p2_ is $2 \pi G(v)$
+    /// with $G(v) = \int_0^v \sqrt{\alpha t^2 + \beta t + \gamma} dt$,
+    /// returns the coefficients $\alpha$, $\beta$ and $\gamma$.
+    fn area_coefficients(&self) -> (Float, Float, Float) {
+        let d: Vector3f = self.p2 - self.p1;
+        let r0: Float = self.p1.x * self.p1.x + self.p1.y * self.p1.y;
+        let s: Float = self.p1.x * d.x + self.p1.y * d.y;
+        let m: Float = d.x * d.x + d.y * d.y;
+        let dz2: Float = d.z * d.z;
+        (dz2 * m + m * m, 2.0 as Float * s * (dz2 + m), dz2 * r0 + s * s)
+    }
+    fn area_integral(&self, v: Float) -> Float {
+        let (alpha, beta, gamma) = self.area_coefficients();
+        let q: Float = ((alpha * v + beta) * v + gamma).max(0.0 as Float);
+        if alpha <= 1e-12 as Float {
+            // the line is parallel to the z axis (cylinder)
+            return gamma.sqrt() * v;
+        }
+        let lin: Float = 2.0 as Float * alpha * v + beta;
+        let mut g: Float = lin / (4.0 as Float * alpha) * q.sqrt();
+        let disc: Float = 4.0 as Float * alpha * gamma - beta * beta;
+        if disc > 0.0 as Float {
+            let arg: Float = lin + 2.0 as Float * alpha.sqrt() * q.sqrt();
+            if arg > 0.0 as Float {
+                g += disc / (8.0 as Float * alpha * alpha.sqrt()) * arg.ln();
+            }
+        }
+        g
+    }
+}
+
+impl Shape for Hyperboloid {
+    fn object_bound(&self) -> Bounds3f {
+        Bounds3f {
+            p_min: Point3f {
+                x: -self.r_max,
+                y: -self.r_max,
+                z: self.z_min,
+            },
+            p_max: Point3f {
+                x: self.r_max,
+                y: self.r_max,
+                z: self.z_max,
+            },
+        }
+    }
+    fn world_bound(&self) -> Bounds3f {
+        // in C++: Bounds3f Shape::WorldBound() const { return (*ObjectToWorld)(ObjectBound()); }
+        self.object_to_world.transform_bounds(&self.object_bound())
+    }
+    fn intersect(&self, r: &Ray) -> Option<(SurfaceInteraction, Float)> {
+        // TODO: ProfilePhase p(Prof::ShapeIntersect);
+        // transform _Ray_ to object space
+        let mut o_err: Vector3f = Vector3f::default();
+        let mut d_err: Vector3f = Vector3f::default();
+        let ray: Ray = self
+            .world_to_object
+            .transform_ray_with_error(r, &mut o_err, &mut d_err);
+
+        // compute quadratic hyperboloid coefficients
+
+        // initialize _EFloat_ ray coordinate values
+        let ox = EFloat::new(ray.o.x as f32, o_err.x as f32);
+        let oy = EFloat::new(ray.o.y as f32, o_err.y as f32);
+        let oz = EFloat::new(ray.o.z as f32, o_err.z as f32);
+        let dx = EFloat::new(ray.d.x as f32, d_err.x as f32);
+        let dy = EFloat::new(ray.d.y as f32, d_err.y as f32);
+        let dz = EFloat::new(ray.d.z as f32, d_err.z as f32);
+        let ah: f32 = self.ah as f32;
+        let ch: f32 = self.ch as f32;
+        let a: EFloat = dx * dx * ah + dy * dy * ah - dz * dz * ch;
+        let b: EFloat = (dx * ox * ah + dy * oy * ah - dz * oz * ch) * 2.0f32;
+        let c: EFloat = ox * ox * ah + oy * oy * ah - oz * oz * ch - EFloat::new(1.0, 0.0);
+
+        // Solve quadratic equation for _t_ values
+        let mut t0: EFloat = EFloat::default();
+        let mut t1: EFloat = EFloat::default();
+        if !quadratic_efloat(a, b, c, &mut t0, &mut t1) {
+            return None;
+        }
+        // check quadric shape _t0_ and _t1_ for nearest intersection
+        if t0.upper_bound() > ray.t_max as f32 || t1.lower_bound() <= 0.0f32 {
+            return None;
+        }
+        let mut t_shape_hit: EFloat = t0;
+        if t_shape_hit.lower_bound() <= 0.0f32 {
+            t_shape_hit = t1;
+            if t_shape_hit.upper_bound() > ray.t_max as f32 {
+                return None;
+            }
+        }
+        // compute hyperboloid inverse mapping
+        let mut p_hit: Point3f = ray.position(t_shape_hit.v);
+        let mut v: Float = (p_hit.z - self.p1.z) / (self.p2.z - self.p1.z);
+        let mut pr: Point3f = self.p1 * (1.0 as Float - v) + self.p2 * v;
+        let mut phi: Float =
+            (pr.x * p_hit.y - p_hit.x * pr.y).atan2(p_hit.x * pr.x + p_hit.y * pr.y);
+        if phi < 0.0 as Float {
+            phi += 2.0 as Float * PI;
+        }
+        // test hyperboloid intersection against clipping parameters
+        if p_hit.z < self.z_min || p_hit.z > self.z_max || phi > self.phi_max {
+            if t_shape_hit == t1 {
+                return None;
+            }
+            t_shape_hit = t1;
+            if t1.upper_bound() > ray.t_max {
+                return None;
+            }
+            // compute hyperboloid inverse mapping
+            p_hit = ray.position(t_shape_hit.v);
+            v = (p_hit.z - self.p1.z) / (self.p2.z - self.p1.z);
+            pr = self.p1 * (1.0 as Float - v) + self.p2 * v;
+            phi = (pr.x * p_hit.y - p_hit.x * pr.y).atan2(p_hit.x * pr.x + p_hit.y * pr.y);
+            if phi < 0.0 as Float {
+                phi += 2.0 as Float * PI;
+            }
+            if p_hit.z < self.z_min || p_hit.z > self.z_max || phi > self.phi_max {
+                return None;
+            }
+        }
+        // find parametric representation of hyperboloid hit
+        let u: Float = phi / self.phi_max;
+        // compute hyperboloid $\dpdu$ and $\dpdv$
+        let cos_phi: Float = phi.cos();
+        let sin_phi: Float = phi.sin();
+        let dpdu: Vector3f = Vector3f {
+            x: -self.phi_max * p_hit.y,
+            y: self.phi_max * p_hit.x,
+            z: 0.0,
+        };
+        let dpdv: Vector3f = Vector3f {
+            x: (self.p2.x - self.p1.x) * cos_phi - (self.p2.y - self.p1.y) * sin_phi,
+            y: (self.p2.x - self.p1.x) * sin_phi + (self.p2.y - self.p1.y) * cos_phi,
+            z: self.p2.z - self.p1.z,
+        };
+        // compute hyperboloid $\dndu$ and $\dndv$
+        let d2_p_duu: Vector3f = Vector3f {
+            x: p_hit.x,
+            y: p_hit.y,
+            z: 0.0,
+        } * -self.phi_max
+            * self.phi_max;
+        let d2_p_duv: Vector3f = Vector3f {
+            x: -dpdv.y,
+            y: dpdv.x,
+            z: 0.0,
+        } * self.phi_max;
+        let d2_p_dvv: Vector3f = Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        // compute coefficients for fundamental forms
+        let ec: Float = vec3_dot_vec3(&dpdu, &dpdu);
+        let fc: Float = vec3_dot_vec3(&dpdu, &dpdv);
+        let gc: Float = vec3_dot_vec3(&dpdv, &dpdv);
+        let nc: Vector3f = vec3_cross_vec3(&dpdu, &dpdv).normalize();
+        let el: Float = vec3_dot_vec3(&nc, &d2_p_duu);
+        let fl: Float = vec3_dot_vec3(&nc, &d2_p_duv);
+        let gl: Float = vec3_dot_vec3(&nc, &d2_p_dvv);
+        // compute $\dndu$ and $\dndv$ from fundamental form coefficients
+        let inv_egf2: Float = 1.0 / (ec * gc - fc * fc);
+        let dndu = dpdu * (fl * fc - el * gc) * inv_egf2 + dpdv * (el * fc - fl * ec) * inv_egf2;
+        let dndu = Normal3f {
+            x: dndu.x,
+            y: dndu.y,
+            z: dndu.z,
+        };
+        let dndv = dpdu * (gl * fc - fl * gc) * inv_egf2 + dpdv * (fl * fc - gl * ec) * inv_egf2;
+        let dndv = Normal3f {
+            x: dndv.x,
+            y: dndv.y,
+            z: dndv.z,
+        };
+        // compute error bounds for hyperboloid intersection
+
+        // compute error bounds for intersection computed with ray equation
+        let px: EFloat = ox + t_shape_hit * dx;
+        let py: EFloat = oy + t_shape_hit * dy;
+        let pz: EFloat = oz + t_shape_hit * dz;
+        let p_error: Vector3f = Vector3f {
+            x: px.get_absolute_error() as Float,
+            y: py.get_absolute_error() as Float,
+            z: pz.get_absolute_error() as Float,
+        };
+        // initialize _SurfaceInteraction_ from parametric information
+        let uv_hit: Point2f = Point2f { x: u, y: v };
+        let wo: Vector3f = -ray.d;
+        let si: SurfaceInteraction = SurfaceInteraction::new(
+            &p_hit, &p_error, &uv_hit, &wo, &dpdu, &dpdv, &dndu, &dndv, ray.time, None,
+        );
+        let mut isect: SurfaceInteraction = self.object_to_world.transform_surface_interaction(&si);
+        if let Some(_shape) = si.shape {
+            isect.shape = si.shape;
+        }
+        if let Some(_primitive) = si.primitive {
+            isect.primitive = si.primitive;
+        }
+        Some((isect, t_shape_hit.v as Float))
+    }
+    fn intersect_p(&self, r: &Ray) -> bool {
+        // TODO: ProfilePhase p(Prof::ShapeIntersect);
+        // transform _Ray_ to object space
+        let mut o_err: Vector3f = Vector3f::default();
+        let mut d_err: Vector3f = Vector3f::default();
+        let ray: Ray = self
+            .world_to_object
+            .transform_ray_with_error(r, &mut o_err, &mut d_err);
+
+        // compute quadratic hyperboloid coefficients
+
+        // initialize _EFloat_ ray coordinate values
+        let ox = EFloat::new(ray.o.x as f32, o_err.x as f32);
+        let oy = EFloat::new(ray.o.y as f32, o_err.y as f32);
+        let oz = EFloat::new(ray.o.z as f32, o_err.z as f32);
+        let dx = EFloat::new(ray.d.x as f32, d_err.x as f32);
+        let dy = EFloat::new(ray.d.y as f32, d_err.y as f32);
+        let dz = EFloat::new(ray.d.z as f32, d_err.z as f32);
+        let ah: f32 = self.ah as f32;
+        let ch: f32 = self.ch as f32;
+        let a: EFloat = dx * dx * ah + dy * dy * ah - dz * dz * ch;
+        let b: EFloat = (dx * ox * ah + dy * oy * ah - dz * oz * ch) * 2.0f32;
+        let c: EFloat = ox * ox * ah + oy * oy * ah - oz * oz * ch - EFloat::new(1.0, 0.0);
+
+        // Solve quadratic equation for _t_ values
+        let mut t0: EFloat = EFloat::default();
+        let mut t1: EFloat = EFloat::default();
+        if !quadratic_efloat(a, b, c, &mut t0, &mut t1) {
+            return false;
+        }
+        // check quadric shape _t0_ and _t1_ for nearest intersection
+        if t0.upper_bound() > ray.t_max as f32 || t1.lower_bound() <= 0.0f32 {
+            return false;
+        }
+        let mut t_shape_hit: EFloat = t0;
+        if t_shape_hit.lower_bound() <= 0.0f32 {
+            t_shape_hit = t1;
+            if t_shape_hit.upper_bound() > ray.t_max as f32 {
+                return false;
+            }
+        }
+        // compute hyperboloid inverse mapping
+        let mut p_hit: Point3f = ray.position(t_shape_hit.v);
+        let mut v: Float = (p_hit.z - self.p1.z) / (self.p2.z - self.p1.z);
+        let mut pr: Point3f = self.p1 * (1.0 as Float - v) + self.p2 * v;
+        let mut phi: Float =
+            (pr.x * p_hit.y - p_hit.x * pr.y).atan2(p_hit.x * pr.x + p_hit.y * pr.y);
+        if phi < 0.0 as Float {
+            phi += 2.0 as Float * PI;
+        }
+        // test hyperboloid intersection against clipping parameters
+        if p_hit.z < self.z_min || p_hit.z > self.z_max || phi > self.phi_max {
+            if t_shape_hit == t1 {
+                return false;
+            }
+            t_shape_hit = t1;
+            if t1.upper_bound() > ray.t_max {
+                return false;
+            }
+            // compute hyperboloid inverse mapping
+            p_hit = ray.position(t_shape_hit.v);
+            v = (p_hit.z - self.p1.z) / (self.p2.z - self.p1.z);
+            pr = self.p1 * (1.0 as Float - v) + self.p2 * v;
+            phi = (pr.x * p_hit.y - p_hit.x * pr.y).atan2(p_hit.x * pr.x + p_hit.y * pr.y);
+            if phi < 0.0 as Float {
+                phi += 2.0 as Float * PI;
+            }
+            if p_hit.z < self.z_min || p_hit.z > self.z_max || phi > self.phi_max {
+                return false;
+            }
+        }
+        true
+    }
+    fn get_reverse_orientation(&self) -> bool {
+        self.reverse_orientation
+    }
+    fn get_transform_swaps_handedness(&self) -> bool {
+        self.transform_swaps_handedness
+    }
+    fn area(&self) -> Float {
+        self.phi_max * (self.area_integral(1.0 as Float) - self.area_integral(0.0 as Float))
+    }
+    fn sample(&self, u: &Point2f, pdf: &mut Float) -> InteractionCommon {
+        // invert $G(v)$ (see area_integral()) with a safeguarded
+        // Newton-Bisection search
+        let (alpha, beta, gamma_q) = self.area_coefficients();
+        let g0: Float = self.area_integral(0.0 as Float);
+        let g1: Float = self.area_integral(1.0 as Float);
+        let target: Float = g0 + u[0] * (g1 - g0);
+        let mut v_lo: Float = 0.0 as Float;
+        let mut v_hi: Float = 1.0 as Float;
+        let mut v: Float = u[0];
+        for _i in 0..32 {
+            let f: Float = self.area_integral(v) - target;
+            if f.abs() <= 1e-6 as Float * (g1 - g0) {
+                break;
+            }
+            if f < 0.0 as Float {
+                v_lo = v;
+            } else {
+                v_hi = v;
+            }
+            let df: Float = ((alpha * v + beta) * v + gamma_q).max(0.0 as Float).sqrt();
+            let v_newton: Float = v - f / df;
+            if df == 0.0 as Float || !(v_newton > v_lo && v_newton < v_hi) {
+                v = 0.5 as Float * (v_lo + v_hi);
+            } else {
+                v = v_newton;
+            }
+        }
+        let phi: Float = u[1] * self.phi_max;
+        let cos_phi: Float = phi.cos();
+        let sin_phi: Float = phi.sin();
+        let pr: Point3f = self.p1 * (1.0 as Float - v) + self.p2 * v;
+        let p_obj: Point3f = Point3f {
+            x: pr.x * cos_phi - pr.y * sin_phi,
+            y: pr.x * sin_phi + pr.y * cos_phi,
+            z: pr.z,
+        };
+        let mut it: InteractionCommon = InteractionCommon::default();
+        let dpdu: Vector3f = Vector3f {
+            x: -p_obj.y,
+            y: p_obj.x,
+            z: 0.0,
+        };
+        let dpdv: Vector3f = Vector3f {
+            x: (self.p2.x - self.p1.x) * cos_phi - (self.p2.y - self.p1.y) * sin_phi,
+            y: (self.p2.x - self.p1.x) * sin_phi + (self.p2.y - self.p1.y) * cos_phi,
+            z: self.p2.z - self.p1.z,
+        };
+        let n: Vector3f = vec3_cross_vec3(&dpdu, &dpdv);
+        it.n = self
+            .object_to_world
+            .transform_normal(&Normal3f {
+                x: n.x,
+                y: n.y,
+                z: n.z,
+            }).normalize();
+        if self.reverse_orientation {
+            it.n *= -1.0 as Float;
+        }
+        let p_obj_error: Vector3f = Vector3f {
+            x: p_obj.x,
+            y: p_obj.y,
+            z: p_obj.z,
+        }.abs()
+            * gamma(5_i32);
+        it.p = self.object_to_world.transform_point_with_abs_error(
+            &p_obj,
+            &p_obj_error,
+            &mut it.p_error,
+        );
+        *pdf = 1.0 as Float / self.area();
+        it
+    }
+    fn sample_with_ref_point(
+        &self,
+        iref: &InteractionCommon,
+        u: &Point2f,
+        pdf: &mut Float,
+    ) -> InteractionCommon {
+        let intr: InteractionCommon = self.sample(u, pdf);
+        let mut wi: Vector3f = intr.p - iref.p;
+        if wi.length_squared() == 0.0 as Float {
+            *pdf = 0.0 as Float;
+        } else {
+            wi = wi.normalize();
+            // convert from area measure, as returned by the Sample()
+            // call above, to solid angle measure.
+            *pdf *= pnt3_distance_squared(&iref.p, &intr.p) / nrm_abs_dot_vec3(&intr.n, &-wi);
+            if (*pdf).is_infinite() {
+                *pdf = 0.0 as Float;
+            }
+        }
+        intr
+    }
+    fn pdf_with_ref_point(&self, iref: &Interaction, wi: &Vector3f) -> Float {
+        // intersect sample ray with area light geometry
+        let ray: Ray = iref.spawn_ray(wi);
+        if let Some((isect_light, _t_hit)) = self.intersect(&ray) {
+            // convert light sample weight to solid angle measure
+            let mut pdf: Float = pnt3_distance_squared(&iref.get_p(), &isect_light.p)
+                / (nrm_abs_dot_vec3(&isect_light.n, &-(*wi)) * self.area());
+            if pdf.is_infinite() {
+                pdf = 0.0 as Float;
+            }
+            pdf
+        } else {
+            0.0 as Float
+        }
+    }
+}
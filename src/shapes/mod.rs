@@ -17,7 +17,8 @@
 //!
 //! ## Cones
 //!
-//! TODO
+//! Cones are quadrics centered around the z axis with their base
+//! (of a given radius) at z = 0 and their tip at a given height.
 //!
 //! ## Curves
 //!
@@ -52,18 +53,24 @@
 //!
 //! ## Hyperboloids
 //!
-//! TODO
+//! A hyperboloid is the surface of revolution which is swept out by
+//! rotating a line segment (from **p1** to **p2**) around the z axis.
 //!
 //! ## Paraboloids
 //!
-//! TODO
+//! Paraboloids are quadrics centered around the z axis, clipped by
+//! **zmin** and **zmax**, and with a given radius at **zmax**.
 //!
 
+pub mod cone;
 pub mod curve;
 pub mod cylinder;
 pub mod disk;
+pub mod heightfield;
+pub mod hyperboloid;
 pub mod loopsubdiv;
 pub mod nurbs;
+pub mod paraboloid;
 pub mod plymesh;
 pub mod sphere;
 pub mod triangle;
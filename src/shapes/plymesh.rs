@@ -18,7 +18,7 @@ use core::pbrt::Float;
 use core::shape::Shape;
 use core::texture::Texture;
 use core::transform::Transform;
-use shapes::triangle::{Triangle, TriangleMesh};
+use shapes::triangle::create_triangle_mesh;
 
 pub fn create_ply_mesh(
     o2w: &Transform,
@@ -62,6 +62,7 @@ pub fn create_ply_mesh(
     let mut has_normals: bool = false;
     let mut has_uvs: bool = false;
     let mut tm_vertex_indices: Vec<usize> = Vec::new();
+    let mut face_indices: Vec<usize> = Vec::new();
     for (name, list) in payload.into_iter() {
         match name.as_ref() {
             "vertex" => {
@@ -133,6 +134,10 @@ pub fn create_ply_mesh(
             }
             "face" => {
                 for elem in list.into_iter() {
+                    // a quad is split into two triangles, which share
+                    // the same face index
+                    let mut n_face_triangles: usize = 0;
+                    let mut face_index: Option<usize> = None;
                     for (name2, list2) in elem.into_iter() {
                         match name2.as_ref() {
                             "vertex_indices" => {
@@ -150,6 +155,7 @@ pub fn create_ply_mesh(
                                             let v1 = vertex_indices[0];
                                             let v3 = vertex_indices[2];
                                             let v4 = vertex_indices[3];
+                                            vertex_indices.truncate(3);
                                             vertex_indices.push(v1);
                                             vertex_indices.push(v3);
                                             vertex_indices.push(v4);
@@ -158,15 +164,26 @@ pub fn create_ply_mesh(
                                                    vertex_indices.len());
                                         }
                                     }
+                                    n_face_triangles = vertex_indices.len() / 3;
                                     // now we can add the indices to the triangle mesh vertex indices
                                     for vi in vertex_indices {
                                         tm_vertex_indices.push(vi);
                                     }
                                 }
                             }
+                            "face_indices" => match list2 {
+                                ply::Property::Int(fi) => face_index = Some(fi as usize),
+                                ply::Property::UInt(fi) => face_index = Some(fi as usize),
+                                _ => {}
+                            },
                             _ => unreachable!(),
                         }
                     }
+                    if let Some(fi) = face_index {
+                        for _i in 0..n_face_triangles {
+                            face_indices.push(fi);
+                        }
+                    }
                 }
             }
             _ => unreachable!(),
@@ -176,45 +193,29 @@ pub fn create_ply_mesh(
     //     println!("{:?}: {:?}", i, p[i]);
     // }
     // println!("tm_vertex_indices = {:?}", tm_vertex_indices);
-    let mut n_ws: Vec<Normal3f> = Vec::new();
+    if !face_indices.is_empty() && face_indices.len() != tm_vertex_indices.len() / 3 {
+        println!(
+            "plymesh: Number of face indices {} does not match number of triangles {}, discarding face indices",
+            face_indices.len(),
+            tm_vertex_indices.len() / 3
+        );
+        face_indices.clear();
+    }
     if !n.is_empty() {
         assert!(n.len() == p.len());
-        // transform normals to world space
-        let n_normals: usize = n.len();
-        for i in 0..n_normals {
-            n_ws.push(o2w.transform_normal(&n[i]));
-        }
-    }
-    // transform mesh vertices to world space
-    let mut p_ws: Vec<Point3f> = Vec::new();
-    let n_vertices: usize = p.len();
-    for i in 0..n_vertices {
-        p_ws.push(o2w.transform_point(&p[i]));
     }
-    let s_ws: Vec<Vector3f> = Vec::new(); // TODO
-    let mesh = Arc::new(TriangleMesh::new(
-        *o2w,
-        *w2o,
+    let s: Vec<Vector3f> = Vec::new(); // TODO
+    create_triangle_mesh(
+        o2w,
+        w2o,
         reverse_orientation,
-        false,                       // transform_swaps_handedness
         tm_vertex_indices.len() / 3, // n_triangles
         tm_vertex_indices,
-        n_vertices,
-        p_ws, // in world space
-        s_ws, // in world space
-        n_ws, // in world space
+        p.len(),
+        &p,
+        &s,
+        &n,
         uvs,
-    ));
-    let mut shapes: Vec<Arc<Shape + Send + Sync>> = Vec::new();
-    for id in 0..mesh.n_triangles {
-        let triangle = Arc::new(Triangle::new(
-            mesh.object_to_world,
-            mesh.world_to_object,
-            mesh.transform_swaps_handedness,
-            mesh.clone(),
-            id,
-        ));
-        shapes.push(triangle.clone());
-    }
-    shapes
+        face_indices,
+    )
 }
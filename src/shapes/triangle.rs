@@ -34,6 +34,8 @@ pub struct TriangleMesh {
     pub s: Vec<Vector3f>,
     /// an optional vector of paramtric (u, v) values (texture coordinates)
     pub uv: Vec<Point2f>,
+    /// an optional vector of per-triangle face indices (e.g. for Ptex)
+    pub face_indices: Vec<usize>,
     // TODO: std::shared_ptr<Texture<Float>> alphaMask, shadowAlphaMask;
     // inherited from class Shape (see shape.h)
     pub object_to_world: Transform, // TODO: not pub?
@@ -70,6 +72,7 @@ impl TriangleMesh {
             n: n,
             s: s,
             uv: uv,
+            face_indices: Vec::new(),
         }
     }
 }
@@ -335,6 +338,9 @@ impl Shape for Triangle {
         let surface_normal: Normal3f = Normal3f::from(vec3_cross_vec3(&dp02, &dp12).normalize());
         si.n = surface_normal;
         si.shading.n = surface_normal;
+        if !self.mesh.face_indices.is_empty() {
+            si.face_index = self.mesh.face_indices[self.id];
+        }
         if !self.mesh.n.is_empty() || !self.mesh.s.is_empty() {
             // initialize _Triangle_ shading geometry
 
@@ -639,3 +645,61 @@ impl Shape for Triangle {
         }
     }
 }
+
+/// Creates a **TriangleMesh** from object space data (transformed to
+/// world space here) and returns one **Triangle** per face.
+pub fn create_triangle_mesh(
+    object_to_world: &Transform,
+    world_to_object: &Transform,
+    reverse_orientation: bool,
+    n_triangles: usize,
+    vertex_indices: Vec<usize>,
+    n_vertices: usize,
+    p: &Vec<Point3f>,
+    s: &Vec<Vector3f>,
+    n: &Vec<Normal3f>,
+    uv: Vec<Point2f>,
+    face_indices: Vec<usize>,
+) -> Vec<Arc<Shape + Send + Sync>> {
+    // transform mesh vertices to world space
+    let mut p_ws: Vec<Point3f> = Vec::with_capacity(n_vertices);
+    for i in 0..n_vertices {
+        p_ws.push(object_to_world.transform_point(&p[i]));
+    }
+    // transform tangents to world space
+    let mut s_ws: Vec<Vector3f> = Vec::with_capacity(s.len());
+    for i in 0..s.len() {
+        s_ws.push(object_to_world.transform_vector(&s[i]));
+    }
+    // transform normals to world space
+    let mut n_ws: Vec<Normal3f> = Vec::with_capacity(n.len());
+    for i in 0..n.len() {
+        n_ws.push(object_to_world.transform_normal(&n[i]));
+    }
+    let mut mesh: TriangleMesh = TriangleMesh::new(
+        *object_to_world,
+        *world_to_object,
+        reverse_orientation,
+        false, // transform_swaps_handedness
+        n_triangles,
+        vertex_indices,
+        n_vertices,
+        p_ws, // in world space
+        s_ws, // in world space
+        n_ws, // in world space
+        uv,
+    );
+    mesh.face_indices = face_indices;
+    let mesh = Arc::new(mesh);
+    let mut shapes: Vec<Arc<Shape + Send + Sync>> = Vec::with_capacity(n_triangles);
+    for id in 0..mesh.n_triangles {
+        shapes.push(Arc::new(Triangle::new(
+            mesh.object_to_world,
+            mesh.world_to_object,
+            mesh.reverse_orientation,
+            mesh.clone(),
+            id,
+        )));
+    }
+    shapes
+}
@@ -0,0 +1,50 @@
+// std
+use std::ops::{Add, Mul};
+// pbrt
+use core::geometry::{Point2f, Vector2f};
+use core::interaction::SurfaceInteraction;
+use core::pbrt::Float;
+use core::texture::{Texture, TextureMapping2D};
+
+// see bilerp.h
+
+pub struct BilerpTexture<T> {
+    pub mapping: Box<TextureMapping2D + Send + Sync>,
+    pub v00: T,
+    pub v01: T,
+    pub v10: T,
+    pub v11: T,
+}
+
+impl<T: Copy> BilerpTexture<T> {
+    pub fn new(
+        mapping: Box<TextureMapping2D + Send + Sync>,
+        v00: T,
+        v01: T,
+        v10: T,
+        v11: T,
+    ) -> Self {
+        BilerpTexture {
+            mapping: mapping,
+            v00: v00,
+            v01: v01,
+            v10: v10,
+            v11: v11,
+        }
+    }
+}
+
+impl<T: Copy> Texture<T> for BilerpTexture<T>
+where
+    T: Add<Output = T> + Mul<Float, Output = T>,
+{
+    fn evaluate(&self, si: &SurfaceInteraction) -> T {
+        let mut dstdx: Vector2f = Vector2f::default();
+        let mut dstdy: Vector2f = Vector2f::default();
+        let st: Point2f = self.mapping.map(si, &mut dstdx, &mut dstdy);
+        self.v00 * ((1.0 as Float - st.x) * (1.0 as Float - st.y))
+            + self.v01 * ((1.0 as Float - st.x) * st.y)
+            + self.v10 * (st.x * (1.0 as Float - st.y))
+            + self.v11 * (st.x * st.y)
+    }
+}
@@ -1,9 +1,9 @@
 // std
 use std::sync::Arc;
 // pbrt
-use core::geometry::{Point2f, Vector2f};
+use core::geometry::{Point2f, Point3f, Vector2f, Vector3f};
 use core::interaction::SurfaceInteraction;
-use core::texture::{Texture, TextureMapping2D};
+use core::texture::{Texture, TextureMapping2D, TextureMapping3D};
 
 // checkerboard.h
 
@@ -41,3 +41,36 @@ impl<T: Copy> Texture<T> for Checkerboard2DTexture<T> {
         }
     }
 }
+
+pub struct Checkerboard3DTexture<T> {
+    pub mapping: Box<TextureMapping3D + Send + Sync>,
+    pub tex1: Arc<Texture<T> + Send + Sync>,
+    pub tex2: Arc<Texture<T> + Send + Sync>,
+}
+
+impl<T: Copy> Checkerboard3DTexture<T> {
+    pub fn new(
+        mapping: Box<TextureMapping3D + Send + Sync>,
+        tex1: Arc<Texture<T> + Send + Sync>,
+        tex2: Arc<Texture<T> + Send + Sync>,
+    ) -> Self {
+        Checkerboard3DTexture {
+            mapping: mapping,
+            tex1: tex1,
+            tex2: tex2,
+        }
+    }
+}
+
+impl<T: Copy> Texture<T> for Checkerboard3DTexture<T> {
+    fn evaluate(&self, si: &SurfaceInteraction) -> T {
+        let mut dpdx: Vector3f = Vector3f::default();
+        let mut dpdy: Vector3f = Vector3f::default();
+        let p: Point3f = self.mapping.map(si, &mut dpdx, &mut dpdy);
+        if (p.x.floor() as i32 + p.y.floor() as i32 + p.z.floor() as i32) % 2 == 0 {
+            self.tex1.evaluate(si)
+        } else {
+            self.tex2.evaluate(si)
+        }
+    }
+}
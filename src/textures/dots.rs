@@ -0,0 +1,58 @@
+// std
+use std::sync::Arc;
+// pbrt
+use core::geometry::{Point2f, Vector2f};
+use core::interaction::SurfaceInteraction;
+use core::pbrt::Float;
+use core::texture::{noise_flt, Texture, TextureMapping2D};
+
+// see dots.h
+
+pub struct DotsTexture<T> {
+    pub mapping: Box<TextureMapping2D + Send + Sync>,
+    pub outside_dot: Arc<Texture<T> + Send + Sync>,
+    pub inside_dot: Arc<Texture<T> + Send + Sync>,
+}
+
+impl<T: Copy> DotsTexture<T> {
+    pub fn new(
+        mapping: Box<TextureMapping2D + Send + Sync>,
+        outside_dot: Arc<Texture<T> + Send + Sync>,
+        inside_dot: Arc<Texture<T> + Send + Sync>,
+    ) -> Self {
+        DotsTexture {
+            mapping: mapping,
+            outside_dot: outside_dot,
+            inside_dot: inside_dot,
+        }
+    }
+}
+
+impl<T: Copy> Texture<T> for DotsTexture<T> {
+    fn evaluate(&self, si: &SurfaceInteraction) -> T {
+        // compute cell indices for dots
+        let mut dstdx: Vector2f = Vector2f::default();
+        let mut dstdy: Vector2f = Vector2f::default();
+        let st: Point2f = self.mapping.map(si, &mut dstdx, &mut dstdy);
+        let s_cell: Float = (st.x + 0.5 as Float).floor();
+        let t_cell: Float = (st.y + 0.5 as Float).floor();
+        // return _insideDot_ result if point is inside dot
+        if noise_flt(s_cell + 0.5 as Float, t_cell + 0.5 as Float, 0.5 as Float) > 0.0 as Float {
+            let radius: Float = 0.35 as Float;
+            let max_shift: Float = 0.5 as Float - radius;
+            let s_center: Float = s_cell
+                + max_shift * noise_flt(s_cell + 1.5 as Float, t_cell + 2.8 as Float, 0.5 as Float);
+            let t_center: Float = t_cell
+                + max_shift * noise_flt(s_cell + 4.5 as Float, t_cell + 9.8 as Float, 0.5 as Float);
+            let dst: Vector2f = st
+                - Point2f {
+                    x: s_center,
+                    y: t_center,
+                };
+            if dst.length_squared() < radius * radius {
+                return self.inside_dot.evaluate(si);
+            }
+        }
+        self.outside_dot.evaluate(si)
+    }
+}
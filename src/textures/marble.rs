@@ -0,0 +1,86 @@
+// pbrt
+use core::geometry::{Point3f, Vector3f};
+use core::interaction::SurfaceInteraction;
+use core::pbrt::{Float, Spectrum};
+use core::texture::{fbm, Texture, TextureMapping3D};
+
+// see marble.h
+
+pub struct MarbleTexture {
+    pub mapping: Box<TextureMapping3D + Send + Sync>,
+    pub octaves: i32,
+    pub omega: Float,
+    pub scale: Float,
+    pub variation: Float,
+}
+
+impl MarbleTexture {
+    pub fn new(
+        mapping: Box<TextureMapping3D + Send + Sync>,
+        octaves: i32,
+        omega: Float,
+        scale: Float,
+        variation: Float,
+    ) -> Self {
+        MarbleTexture {
+            mapping: mapping,
+            octaves: octaves,
+            omega: omega,
+            scale: scale,
+            variation: variation,
+        }
+    }
+}
+
+impl Texture<Float> for MarbleTexture {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Float {
+        let s: Spectrum = self.evaluate(si);
+        s.y()
+    }
+}
+
+impl Texture<Spectrum> for MarbleTexture {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Spectrum {
+        let mut dpdx: Vector3f = Vector3f::default();
+        let mut dpdy: Vector3f = Vector3f::default();
+        let mut p: Point3f = self.mapping.map(si, &mut dpdx, &mut dpdy);
+        p = p * self.scale;
+        let marble: Float = p.y
+            + self.variation
+                * fbm(
+                    &p,
+                    &(dpdx * self.scale),
+                    &(dpdy * self.scale),
+                    self.omega,
+                    self.octaves,
+                );
+        let t: Float = 0.5 as Float + 0.5 as Float * marble.sin();
+        // evaluate marble spline at $t$
+        let c: [[Float; 3]; 9] = [
+            [0.58, 0.58, 0.6],
+            [0.58, 0.58, 0.6],
+            [0.58, 0.58, 0.6],
+            [0.5, 0.5, 0.5],
+            [0.6, 0.59, 0.58],
+            [0.58, 0.58, 0.6],
+            [0.58, 0.58, 0.6],
+            [0.2, 0.2, 0.33],
+            [0.58, 0.58, 0.6],
+        ];
+        let n_seg: usize = c.len() - 3;
+        let first: usize = ((t * n_seg as Float).floor() as usize).min(n_seg - 1);
+        let t: Float = t * n_seg as Float - first as Float;
+        let c0: Spectrum = Spectrum::from_rgb(&c[first]);
+        let c1: Spectrum = Spectrum::from_rgb(&c[first + 1]);
+        let c2: Spectrum = Spectrum::from_rgb(&c[first + 2]);
+        let c3: Spectrum = Spectrum::from_rgb(&c[first + 3]);
+        // Bezier spline evaluated with de Casteljau's algorithm
+        let s0: Spectrum = c0 * (1.0 as Float - t) + c1 * t;
+        let s1: Spectrum = c1 * (1.0 as Float - t) + c2 * t;
+        let s2: Spectrum = c2 * (1.0 as Float - t) + c3 * t;
+        let s0: Spectrum = s0 * (1.0 as Float - t) + s1 * t;
+        let s1: Spectrum = s1 * (1.0 as Float - t) + s2 * t;
+        // extra scale of 1.5 to increase variation among colors
+        (s0 * (1.0 as Float - t) + s1 * t) * 1.5 as Float
+    }
+}
@@ -0,0 +1,47 @@
+// std
+use std::ops::{Add, Mul};
+use std::sync::Arc;
+// pbrt
+use core::interaction::SurfaceInteraction;
+use core::pbrt::Float;
+use core::texture::Texture;
+
+// see mix.h
+
+pub struct MixTexture<T> {
+    pub tex1: Arc<Texture<T> + Send + Sync>,
+    pub tex2: Arc<Texture<T> + Send + Sync>,
+    pub amount: Arc<Texture<Float> + Send + Sync>,
+}
+
+impl<T: Copy> MixTexture<T> {
+    pub fn new(
+        tex1: Arc<Texture<T> + Send + Sync>,
+        tex2: Arc<Texture<T> + Send + Sync>,
+        amount: Arc<Texture<Float> + Send + Sync>,
+    ) -> Self {
+        MixTexture {
+            tex1: tex1,
+            tex2: tex2,
+            amount: amount,
+        }
+    }
+}
+
+impl<T: Copy> Texture<T> for MixTexture<T>
+where
+    T: Add<Output = T> + Mul<Float, Output = T>,
+{
+    fn evaluate(&self, si: &SurfaceInteraction) -> T {
+        let amt: Float = self.amount.evaluate(si);
+        if amt == 0.0 as Float {
+            return self.tex1.evaluate(si);
+        }
+        if amt == 1.0 as Float {
+            return self.tex2.evaluate(si);
+        }
+        let t1: T = self.tex1.evaluate(si);
+        let t2: T = self.tex2.evaluate(si);
+        t1 * (1.0 as Float - amt) + t2 * amt
+    }
+}
@@ -0,0 +1,93 @@
+// std
+use std::marker::PhantomData;
+use std::sync::Arc;
+// pbrt
+use core::interaction::SurfaceInteraction;
+use core::pbrt::{Float, Spectrum};
+use core::ptex::PtexFile;
+use core::texture::Texture;
+
+// see ptex.h
+
+pub struct PtexTexture<T> {
+    pub filename: String,
+    pub ptex: Option<Arc<PtexFile>>,
+    pub gamma: Float,
+    phantom: PhantomData<T>,
+}
+
+impl<T> PtexTexture<T> {
+    pub fn new(filename: String, ptex: Option<Arc<PtexFile>>, gamma: Float) -> Self {
+        let mut valid: Option<Arc<PtexFile>> = None;
+        if let Some(ptex) = ptex {
+            let nc: usize = ptex.n_channels;
+            if nc == 1 || nc == 3 {
+                valid = Some(ptex);
+            } else {
+                println!(
+                    "{}: only one and three channel ptex textures are supported",
+                    filename
+                );
+            }
+        }
+        PtexTexture {
+            filename: filename,
+            ptex: valid,
+            gamma: gamma,
+            phantom: PhantomData,
+        }
+    }
+    /// Looks up one or three channels (depending on the file) for the
+    /// face and face coordinates of _si_.
+    fn lookup(&self, si: &SurfaceInteraction) -> Option<Vec<Float>> {
+        if let Some(ref ptex) = self.ptex {
+            let nc: usize = ptex.n_channels;
+            // triangles map their barycentric coordinates to the default
+            // uvs (b1 + b2, b2), Ptex expects (b1, b2)
+            let (u, v): (Float, Float) = if ptex.is_triangle_mesh {
+                (si.uv[0] - si.uv[1], si.uv[1])
+            } else {
+                (si.uv[0], si.uv[1])
+            };
+            let mut result: Vec<Float> = ptex.lookup(si.face_index, u, v, 0, nc);
+            if self.gamma != 1.0 as Float {
+                for c in 0..nc {
+                    if result[c] >= 0.0 as Float && result[c] <= 1.0 as Float {
+                        result[c] = result[c].powf(self.gamma);
+                    }
+                }
+            }
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+impl Texture<Float> for PtexTexture<Float> {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Float {
+        if let Some(result) = self.lookup(si) {
+            if result.len() == 1 {
+                result[0]
+            } else {
+                (result[0] + result[1] + result[2]) / 3.0 as Float
+            }
+        } else {
+            0.0 as Float
+        }
+    }
+}
+
+impl Texture<Spectrum> for PtexTexture<Spectrum> {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Spectrum {
+        if let Some(result) = self.lookup(si) {
+            if result.len() == 1 {
+                Spectrum::new(result[0])
+            } else {
+                Spectrum::from_rgb(&[result[0], result[1], result[2]])
+            }
+        } else {
+            Spectrum::default()
+        }
+    }
+}
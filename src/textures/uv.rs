@@ -0,0 +1,27 @@
+// pbrt
+use core::geometry::{Point2f, Vector2f};
+use core::interaction::SurfaceInteraction;
+use core::pbrt::{Float, Spectrum};
+use core::texture::{Texture, TextureMapping2D};
+
+// see uv.h
+
+pub struct UVTexture {
+    pub mapping: Box<TextureMapping2D + Send + Sync>,
+}
+
+impl UVTexture {
+    pub fn new(mapping: Box<TextureMapping2D + Send + Sync>) -> Self {
+        UVTexture { mapping: mapping }
+    }
+}
+
+impl Texture<Spectrum> for UVTexture {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Spectrum {
+        let mut dstdx: Vector2f = Vector2f::default();
+        let mut dstdy: Vector2f = Vector2f::default();
+        let st: Point2f = self.mapping.map(si, &mut dstdx, &mut dstdy);
+        let rgb: [Float; 3] = [st.x - st.x.floor(), st.y - st.y.floor(), 0.0 as Float];
+        Spectrum::from_rgb(&rgb)
+    }
+}
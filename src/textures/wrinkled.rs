@@ -0,0 +1,41 @@
+// pbrt
+use core::geometry::{Point3f, Vector3f};
+use core::interaction::SurfaceInteraction;
+use core::pbrt::{Float, Spectrum};
+use core::texture::{turbulence, Texture, TextureMapping3D};
+
+// see wrinkled.h
+
+pub struct WrinkledTexture {
+    pub mapping: Box<TextureMapping3D + Send + Sync>,
+    pub omega: Float,
+    pub octaves: i32,
+}
+
+impl WrinkledTexture {
+    pub fn new(mapping: Box<TextureMapping3D + Send + Sync>, octaves: i32, omega: Float) -> Self {
+        WrinkledTexture {
+            mapping: mapping,
+            omega: omega,
+            octaves: octaves,
+        }
+    }
+}
+
+impl Texture<Float> for WrinkledTexture {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Float {
+        let mut dpdx: Vector3f = Vector3f::default();
+        let mut dpdy: Vector3f = Vector3f::default();
+        let p: Point3f = self.mapping.map(si, &mut dpdx, &mut dpdy);
+        turbulence(&p, &dpdx, &dpdy, self.omega, self.octaves)
+    }
+}
+
+impl Texture<Spectrum> for WrinkledTexture {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Spectrum {
+        let mut dpdx: Vector3f = Vector3f::default();
+        let mut dpdy: Vector3f = Vector3f::default();
+        let p: Point3f = self.mapping.map(si, &mut dpdx, &mut dpdy);
+        Spectrum::new(turbulence(&p, &dpdx, &dpdy, self.omega, self.octaves))
+    }
+}